@@ -1,4 +1,4 @@
-use iotics_grpc_client::IntoAuthBuilder;
+use iotics_grpc_client::{IntoAuthBuilder, TwinDid};
 use iotics_identity::{create_agent_auth_token, create_twin_did_with_control_delegation, Config};
 /// Minimal implementation of the `AuthBuilder` for use in example scripts
 use std::sync::{Arc, Mutex};
@@ -94,10 +94,12 @@ impl IntoAuthBuilder for AuthBuilder {
 }
 
 #[allow(dead_code)]
-pub fn generate_twin_did(config: &Config, name: &str) -> String {
+pub fn generate_twin_did(config: &Config, name: &str) -> TwinDid {
     let twin_name = "#".to_string() + name;
     create_twin_did_with_control_delegation(config, name, &twin_name)
         .expect("Creating twin DID failed.")
+        .parse()
+        .expect("Parsing twin DID failed.")
 }
 
 #[allow(dead_code)]
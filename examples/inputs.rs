@@ -7,7 +7,7 @@ use iotics_grpc_client::input::{delete_input, describe_input, receive_input_mess
 use iotics_grpc_client::interest::send_input_message;
 use iotics_grpc_client::properties::{common_keys::predicate::COMMENT, PropertyBuilder};
 use iotics_grpc_client::twin::{crud::delete_twin, upsert::upsert_twin, UpsertInputWithMeta};
-use iotics_grpc_client::FeedValue;
+use iotics_grpc_client::{FeedValue, InputRef, TwinDid};
 use log::{error, info, LevelFilter};
 use std::sync::Arc;
 
@@ -44,7 +44,7 @@ async fn main() {
     clean_space(auth_builder.clone(), &sender_twin_id, &receiver_twin_id).await;
 }
 
-async fn create_sender(auth_builder: Arc<AuthBuilder>, did: &str) {
+async fn create_sender(auth_builder: Arc<AuthBuilder>, did: &TwinDid) {
     upsert_twin(
         auth_builder,
         did,
@@ -60,7 +60,7 @@ async fn create_sender(auth_builder: Arc<AuthBuilder>, did: &str) {
     .expect("Upserting sender failed");
 }
 
-async fn create_receiver(auth_builder: Arc<AuthBuilder>, did: &str) {
+async fn create_receiver(auth_builder: Arc<AuthBuilder>, did: &TwinDid) {
     upsert_twin(
         auth_builder.clone(),
         did,
@@ -85,13 +85,14 @@ async fn create_receiver(auth_builder: Arc<AuthBuilder>, did: &str) {
     .await
     .expect("Upserting receiver failed");
     // TODO: Make such payloads printable
-    describe_input(auth_builder, did, INPUT_NAME, None)
+    describe_input(auth_builder, &InputRef::new(did.clone(), INPUT_NAME))
         .await
         .expect("Failed to describe input");
 }
 
-async fn activate_receiver_thread(auth_builder: Arc<AuthBuilder>, did: &str) {
-    let mut message_stream = receive_input_messages(auth_builder.clone(), did, INPUT_NAME)
+async fn activate_receiver_thread(auth_builder: Arc<AuthBuilder>, did: &TwinDid) {
+    let input = InputRef::new(did.clone(), INPUT_NAME);
+    let mut message_stream = receive_input_messages(auth_builder.clone(), &input)
         .await
         .expect("Activating receiver failed");
     let fut = async move {
@@ -120,14 +121,17 @@ async fn activate_receiver_thread(auth_builder: Arc<AuthBuilder>, did: &str) {
     tokio::spawn(fut);
 }
 
-async fn send_messages(auth_builder: Arc<AuthBuilder>, sender_did: &str, receiver_did: &str) {
+async fn send_messages(
+    auth_builder: Arc<AuthBuilder>,
+    sender_did: &TwinDid,
+    receiver_did: &TwinDid,
+) {
+    let receiver_input = InputRef::new(receiver_did.clone(), INPUT_NAME);
     for character in "HELLO, IOTICS!".chars() {
         let message = json!({ VALUE_LABEL: character });
         send_input_message(
             auth_builder.clone(),
-            None,
-            receiver_did,
-            INPUT_NAME,
+            &receiver_input,
             sender_did,
             message.to_string().into_bytes(),
         )
@@ -138,15 +142,18 @@ async fn send_messages(auth_builder: Arc<AuthBuilder>, sender_did: &str, receive
     }
 }
 
-async fn clean_space(auth_builder: Arc<AuthBuilder>, sender_did: &str, receiver_did: &str) {
+async fn clean_space(auth_builder: Arc<AuthBuilder>, sender_did: &TwinDid, receiver_did: &TwinDid) {
     info!("Deleting twins...");
     delete_twin(auth_builder.clone(), sender_did)
         .await
         .expect("Deleting sender failed");
     // For demonstrating the method; input would be deleted anyway when twin is deleted immediately afterward.
-    delete_input(auth_builder.clone(), receiver_did, INPUT_NAME)
-        .await
-        .expect("Deleting input failed");
+    delete_input(
+        auth_builder.clone(),
+        &InputRef::new(receiver_did.clone(), INPUT_NAME),
+    )
+    .await
+    .expect("Deleting input failed");
     delete_twin(auth_builder.clone(), receiver_did)
         .await
         .expect("Deleting receiver failed");
@@ -2,7 +2,7 @@
 mod auth;
 use std::{collections::HashMap, sync::Arc, time::Duration};
 
-use iotics_grpc_client::host::get_local_host_id;
+use iotics_grpc_client::host::LocalHostCache;
 use iotics_grpc_client::search::{search, Filter};
 use iotics_grpc_client::twin::crud::delete_twin;
 use iotics_grpc_client::twin::upsert::upsert_twin;
@@ -39,12 +39,12 @@ async fn main() {
         text: None,
     };
 
-    let local_host_id_response = get_local_host_id(auth_builder.clone())
+    let local_host = LocalHostCache::new(auth_builder.clone());
+    let local_host = local_host
+        .local_host()
         .await
         .expect("Failed to get local host id");
 
-    let local_host_id = local_host_id_response.payload.unwrap().host_id;
-
     // Create at least one twin to be found
     let london_twin_id = generate_twin_did(&auth_builder.config, "sender");
     upsert_twin(
@@ -72,9 +72,11 @@ async fn main() {
 
     while let Some(response_result) = search_stream.recv().await {
         match response_result {
-            Ok(page) => {
+            Ok(mut page) => {
+                local_host.normalize_search_response(&mut page);
+
                 if let Some(payload) = page.payload {
-                    let host_str = if payload.host_id == local_host_id {
+                    let host_str = if local_host.is_local(&payload.host_id) {
                         format!("host {} localhost", payload.host_id)
                     } else {
                         format!("host {} ", payload.host_id)
@@ -0,0 +1,418 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input, Data, DeriveInput, Expr, Field, Fields, GenericArgument, LitStr,
+    PathArguments, Type,
+};
+
+/// Derive `iotics_grpc_client::properties::TwinProperties` for a struct with named fields.
+///
+/// Every field needs a `#[twin_property(predicate = ...)]` attribute, unless it is marked with
+/// `#[twin_property(skip)]` in which case it is set to its `Default` value when reading.
+///
+/// - `predicate = "https://..."` or `predicate = SOME_CONST` - the property key
+/// - `lang = "en"` - the field is a `LangLiteral`
+/// - `uri` - the field is a `Uri`, of any type implementing `AsRef<str>` and `FromStr`, e.g. `String`
+///   or `TwinDid`
+/// - `datatype = "integer"` - the field is a `Literal` of that data type, converted with
+///   `ToString`/`FromStr`
+///
+/// Without `lang`, `uri` or `datatype`, `String` fields are `StringLiteral`s and any other type
+/// is converted through its `LiteralValue` implementation.
+/// `Option<T>` fields are skipped when `None` and are `None` when the property is missing.
+#[proc_macro_derive(TwinProperties, attributes(twin_property))]
+pub fn derive_twin_properties(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match expand(input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+enum Kind {
+    LangLiteral(LitStr),
+    Uri,
+    StringLiteral,
+    Literal(Option<LitStr>),
+}
+
+struct TwinPropertyField<'a> {
+    ident: &'a syn::Ident,
+    ty: &'a Type,
+    optional: bool,
+    predicate: Expr,
+    kind: Kind,
+}
+
+enum FieldAttributes<'a> {
+    Skip(&'a syn::Ident),
+    Property(Box<TwinPropertyField<'a>>),
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    "TwinProperties can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "TwinProperties can only be derived for structs",
+            ))
+        }
+    };
+
+    let fields = fields
+        .iter()
+        .map(parse_field)
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let to_properties = fields.iter().filter_map(|field| match field {
+        FieldAttributes::Skip(_) => None,
+        FieldAttributes::Property(field) => Some(to_property(field)),
+    });
+
+    let from_properties = fields.iter().map(|field| match field {
+        FieldAttributes::Skip(ident) => quote! {
+            #ident: ::std::default::Default::default()
+        },
+        FieldAttributes::Property(field) => from_property(field),
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::iotics_grpc_client::properties::TwinProperties for #name #ty_generics #where_clause {
+            fn to_properties(&self) -> ::std::vec::Vec<::iotics_grpc_client::Property> {
+                let mut properties = ::std::vec::Vec::new();
+                #(#to_properties)*
+                properties
+            }
+
+            fn from_properties(
+                properties: &[::iotics_grpc_client::Property],
+            ) -> ::std::result::Result<Self, ::iotics_grpc_client::properties::PropertyFinderError> {
+                ::std::result::Result::Ok(Self {
+                    #(#from_properties,)*
+                })
+            }
+        }
+    })
+}
+
+fn parse_field(field: &Field) -> syn::Result<FieldAttributes<'_>> {
+    let ident = field
+        .ident
+        .as_ref()
+        .expect("named fields always have an ident");
+
+    let mut skip = false;
+    let mut predicate = None;
+    let mut lang = None;
+    let mut uri = false;
+    let mut datatype = None;
+
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("twin_property"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                skip = true;
+            } else if meta.path.is_ident("uri") {
+                uri = true;
+            } else if meta.path.is_ident("predicate") {
+                predicate = Some(meta.value()?.parse::<Expr>()?);
+            } else if meta.path.is_ident("lang") {
+                lang = Some(meta.value()?.parse::<LitStr>()?);
+            } else if meta.path.is_ident("datatype") {
+                datatype = Some(meta.value()?.parse::<LitStr>()?);
+            } else {
+                return Err(meta.error("unsupported twin_property attribute"));
+            }
+            Ok(())
+        })?;
+    }
+
+    if skip {
+        return Ok(FieldAttributes::Skip(ident));
+    }
+
+    let predicate = predicate.ok_or_else(|| {
+        syn::Error::new_spanned(
+            ident,
+            "missing #[twin_property(predicate = ...)] or #[twin_property(skip)]",
+        )
+    })?;
+
+    let (optional, ty) = match option_inner_type(&field.ty) {
+        Some(ty) => (true, ty),
+        None => (false, &field.ty),
+    };
+
+    let kind = match (lang, uri, datatype) {
+        (Some(lang), false, None) => Kind::LangLiteral(lang),
+        (None, true, None) => Kind::Uri,
+        (None, false, Some(datatype)) => Kind::Literal(Some(datatype)),
+        (None, false, None) if is_string(ty) => Kind::StringLiteral,
+        (None, false, None) => Kind::Literal(None),
+        _ => {
+            return Err(syn::Error::new_spanned(
+                ident,
+                "`lang`, `uri` and `datatype` are mutually exclusive",
+            ))
+        }
+    };
+
+    Ok(FieldAttributes::Property(Box::new(TwinPropertyField {
+        ident,
+        ty,
+        optional,
+        predicate,
+        kind,
+    })))
+}
+
+fn to_property(field: &TwinPropertyField) -> TokenStream2 {
+    let ident = field.ident;
+    let predicate = &field.predicate;
+
+    let build = match &field.kind {
+        Kind::LangLiteral(lang) => quote! {
+            ::iotics_grpc_client::properties::PropertyBuilder::build_lang_literal(#predicate, #lang, value)
+        },
+        Kind::Uri => quote! {
+            ::iotics_grpc_client::properties::PropertyBuilder::build_uri_value(
+                #predicate,
+                ::std::convert::AsRef::<str>::as_ref(value),
+            )
+        },
+        Kind::StringLiteral => quote! {
+            ::iotics_grpc_client::properties::PropertyBuilder::build_string_literal_value(#predicate, value)
+        },
+        Kind::Literal(Some(datatype)) => quote! {
+            ::iotics_grpc_client::properties::PropertyBuilder::build_literal_value(
+                #predicate,
+                #datatype,
+                &::std::string::ToString::to_string(value),
+            )
+        },
+        Kind::Literal(None) => quote! {
+            ::iotics_grpc_client::properties::PropertyBuilder::literal(
+                #predicate,
+                ::std::clone::Clone::clone(value),
+            )
+        },
+    };
+
+    if field.optional {
+        quote! {
+            if let ::std::option::Option::Some(value) = &self.#ident {
+                properties.push(#build);
+            }
+        }
+    } else {
+        quote! {
+            {
+                let value = &self.#ident;
+                properties.push(#build);
+            }
+        }
+    }
+}
+
+fn from_property(field: &TwinPropertyField) -> TokenStream2 {
+    let ident = field.ident;
+    let ty = field.ty;
+    let predicate = &field.predicate;
+
+    let find = match &field.kind {
+        Kind::LangLiteral(lang) => quote! {
+            ::iotics_grpc_client::properties::PropertyFinder::find_lang_literal_value(properties, #predicate, #lang)
+                .map(::std::string::ToString::to_string)
+        },
+        Kind::StringLiteral => quote! {
+            ::iotics_grpc_client::properties::PropertyFinder::find_string_literal_value(properties, #predicate)
+                .map(::std::string::ToString::to_string)
+        },
+        Kind::Uri => quote! {
+            ::iotics_grpc_client::properties::PropertyFinder::find_uri_value(properties, #predicate)
+                .and_then(|value| {
+                    value.parse::<#ty>().map_err(|e| {
+                        ::iotics_grpc_client::properties::PropertyFinderError::ParseError {
+                            data_type: ::std::string::ToString::to_string("uri"),
+                            value: ::std::string::ToString::to_string(value),
+                            reason: ::std::string::ToString::to_string(&e),
+                        }
+                    })
+                })
+        },
+        Kind::Literal(Some(datatype)) => quote! {
+            ::iotics_grpc_client::properties::PropertyFinder::find_literal_value(properties, #predicate, #datatype)
+                .and_then(|value| {
+                    value.parse::<#ty>().map_err(|e| {
+                        ::iotics_grpc_client::properties::PropertyFinderError::ParseError {
+                            data_type: ::std::string::ToString::to_string(#datatype),
+                            value: ::std::string::ToString::to_string(value),
+                            reason: ::std::string::ToString::to_string(&e),
+                        }
+                    })
+                })
+        },
+        Kind::Literal(None) => quote! {
+            ::iotics_grpc_client::properties::PropertyFinder::find::<#ty>(properties, #predicate)
+        },
+    };
+
+    if field.optional {
+        quote! {
+            #ident: match #find {
+                ::std::result::Result::Ok(value) => ::std::option::Option::Some(value),
+                ::std::result::Result::Err(
+                    ::iotics_grpc_client::properties::PropertyFinderError::KeyNotFound
+                    | ::iotics_grpc_client::properties::PropertyFinderError::ValueNotFound
+                    | ::iotics_grpc_client::properties::PropertyFinderError::LanguageNotFound,
+                ) => ::std::option::Option::None,
+                ::std::result::Result::Err(e) => return ::std::result::Result::Err(e),
+            }
+        }
+    } else {
+        quote! {
+            #ident: #find?
+        }
+    }
+}
+
+fn option_inner_type(ty: &Type) -> Option<&Type> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+
+    if segment.ident != "Option" {
+        return None;
+    }
+
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) => match args.args.first()? {
+            GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn is_string(ty: &Type) -> bool {
+    match ty {
+        Type::Path(type_path) => type_path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident == "String")
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    fn expand_error(input: DeriveInput) -> String {
+        expand(input).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn expands_the_properties_of_a_struct() {
+        let input: DeriveInput = parse_quote! {
+            struct Sensor {
+                #[twin_property(predicate = LABEL, lang = "en")]
+                label: String,
+                #[twin_property(predicate = "https://example.com/owner", uri)]
+                owner: Option<TwinDid>,
+                #[twin_property(skip)]
+                cache: Vec<u8>,
+            }
+        };
+
+        let tokens = expand(input).unwrap().to_string();
+
+        assert!(tokens.contains("build_lang_literal"));
+        assert!(tokens.contains("find_uri_value"));
+        assert!(tokens.contains("cache : :: std :: default :: Default :: default ()"));
+    }
+
+    #[test]
+    fn rejects_mutually_exclusive_kinds() {
+        let input: DeriveInput = parse_quote! {
+            struct Sensor {
+                #[twin_property(predicate = LABEL, lang = "en", uri)]
+                label: String,
+            }
+        };
+
+        assert_eq!(
+            expand_error(input),
+            "`lang`, `uri` and `datatype` are mutually exclusive"
+        );
+    }
+
+    #[test]
+    fn rejects_a_field_without_predicate() {
+        let input: DeriveInput = parse_quote! {
+            struct Sensor {
+                #[twin_property(lang = "en")]
+                label: String,
+            }
+        };
+
+        assert_eq!(
+            expand_error(input),
+            "missing #[twin_property(predicate = ...)] or #[twin_property(skip)]"
+        );
+    }
+
+    #[test]
+    fn rejects_an_unsupported_attribute() {
+        let input: DeriveInput = parse_quote! {
+            struct Sensor {
+                #[twin_property(predicate = LABEL, unit = "celsius")]
+                temperature: f64,
+            }
+        };
+
+        assert_eq!(expand_error(input), "unsupported twin_property attribute");
+    }
+
+    #[test]
+    fn rejects_anything_but_a_struct_with_named_fields() {
+        let tuple: DeriveInput = parse_quote! {
+            struct Sensor(String);
+        };
+        let enumeration: DeriveInput = parse_quote! {
+            enum Sensor {
+                Temperature,
+            }
+        };
+
+        assert_eq!(
+            expand_error(tuple),
+            "TwinProperties can only be derived for structs with named fields"
+        );
+        assert_eq!(
+            expand_error(enumeration),
+            "TwinProperties can only be derived for structs"
+        );
+    }
+}
@@ -1,4 +1,11 @@
+use crate::client_config::ClientConfig;
+
 pub trait IntoAuthBuilder {
     fn get_host(&self) -> Result<String, anyhow::Error>;
     fn get_token(&self) -> Result<String, anyhow::Error>;
+
+    /// The configuration of the client, the defaults are used by default.
+    fn get_config(&self) -> ClientConfig {
+        ClientConfig::default()
+    }
 }
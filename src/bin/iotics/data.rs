@@ -0,0 +1,54 @@
+use anyhow::Context;
+use chrono::{DateTime, SecondsFormat};
+use serde_json::{json, Value as JsonValue};
+use std::io::Read;
+
+use iotics_grpc_client::client::google::protobuf::Timestamp;
+
+use crate::output::OutputFormat;
+
+/// Read the JSON data given as `--data`: inline, `@file.json` or `@-` for the standard input.
+pub fn read_data(data: &str) -> Result<Vec<u8>, anyhow::Error> {
+    let data = match data.strip_prefix('@') {
+        Some("-") => {
+            let mut data = Vec::new();
+            std::io::stdin()
+                .read_to_end(&mut data)
+                .context("Reading the standard input failed")?;
+            data
+        }
+        Some(path) => std::fs::read(path).with_context(|| format!("Reading [{}] failed", path))?,
+        None => data.as_bytes().to_vec(),
+    };
+
+    serde_json::from_slice::<JsonValue>(&data).context("The data must be JSON")?;
+
+    Ok(data)
+}
+
+/// Print a received sample or message on a single line so that the output can be piped, the data
+/// being decoded as JSON when possible.
+pub fn print_sample(
+    output: OutputFormat,
+    source: &str,
+    occurred_at: Option<&Timestamp>,
+    data: &[u8],
+) {
+    let data = serde_json::from_slice::<JsonValue>(data)
+        .unwrap_or_else(|_| JsonValue::String(String::from_utf8_lossy(data).into_owned()));
+
+    let occurred_at = occurred_at
+        .and_then(|timestamp| DateTime::from_timestamp(timestamp.seconds, timestamp.nanos as u32))
+        .map(|occurred_at| occurred_at.to_rfc3339_opts(SecondsFormat::Millis, true));
+
+    match output {
+        OutputFormat::Table => match occurred_at {
+            Some(occurred_at) => println!("{}  {}", occurred_at, data),
+            None => println!("{}", data),
+        },
+        OutputFormat::Json => println!(
+            "{}",
+            json!({ "source": source, "occurredAt": occurred_at, "data": data })
+        ),
+    }
+}
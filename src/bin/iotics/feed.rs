@@ -0,0 +1,200 @@
+use anyhow::Context;
+use clap::{Args, Subcommand};
+use std::sync::Arc;
+
+use iotics_grpc_client::json_ld::{describe_feed_to_json_ld, JsonLdContext};
+use iotics_grpc_client::properties::common_keys::predicate::COMMENT;
+use iotics_grpc_client::properties::PropertyBuilder;
+use iotics_grpc_client::twin::crud::{create_update_feed, delete_feed};
+use iotics_grpc_client::twin::describe::describe_feed;
+use iotics_grpc_client::twin::share::share_data;
+use iotics_grpc_client::{FeedRef, FeedValue, TwinDid};
+
+use crate::data::read_data;
+use crate::output::{print_done, print_json, OutputFormat, Table};
+use crate::settings::Settings;
+use crate::twin::label;
+
+#[derive(Subcommand)]
+pub enum FeedCommand {
+    /// Describe a feed and its values.
+    Describe {
+        /// `[host_did/]twin_did/feed_id`
+        feed: FeedRef,
+    },
+    /// Create a feed, or replace its properties and values when it exists.
+    Create {
+        /// `twin_did/feed_id`
+        feed: FeedRef,
+        /// Store the last shared sample.
+        #[arg(long)]
+        store_last: bool,
+        /// English label of the feed.
+        #[arg(long)]
+        label: Option<String>,
+        /// English comment of the feed.
+        #[arg(long)]
+        comment: Option<String>,
+        /// Value of the feed as `label:data_type[:unit]`, e.g. `reading:decimal`.
+        #[arg(long = "value", value_parser = parse_value)]
+        values: Vec<FeedValue>,
+    },
+    /// Delete a feed.
+    Delete {
+        /// `twin_did/feed_id`
+        feed: FeedRef,
+    },
+}
+
+impl FeedCommand {
+    pub async fn run(
+        self,
+        auth_builder: Arc<Settings>,
+        output: OutputFormat,
+    ) -> Result<(), anyhow::Error> {
+        match self {
+            Self::Describe { feed } => {
+                let response = describe_feed(auth_builder, &feed).await?;
+
+                match output {
+                    OutputFormat::Table => {
+                        let result = response
+                            .payload
+                            .and_then(|payload| payload.result)
+                            .context("failed to find result")?;
+
+                        println!(
+                            "{}  {}  store last: {}",
+                            feed,
+                            label(&result.properties),
+                            result.store_last
+                        );
+                        values_table(&result.values).print();
+                        Ok(())
+                    }
+                    OutputFormat::Json => print_json(&describe_feed_to_json_ld(
+                        &response,
+                        &JsonLdContext::default(),
+                    )?),
+                }
+            }
+            Self::Create {
+                feed,
+                store_last,
+                label,
+                comment,
+                values,
+            } => {
+                let mut properties = Vec::new();
+
+                if let Some(label) = label {
+                    properties.push(PropertyBuilder::build_label("en", &label));
+                }
+                if let Some(comment) = comment {
+                    properties.push(PropertyBuilder::build_lang_literal(COMMENT, "en", &comment));
+                }
+
+                create_update_feed(auth_builder, &feed, store_last, properties, values).await?;
+                print_done(output, "created", "feed", &feed.to_string())
+            }
+            Self::Delete { feed } => {
+                delete_feed(auth_builder, &feed).await?;
+                print_done(output, "deleted", "feed", &feed.to_string())
+            }
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct ShareArgs {
+    twin: TwinDid,
+    feed: String,
+    /// JSON sample: inline, `@file.json` or `@-` for the standard input.
+    #[arg(long)]
+    data: String,
+    /// Retry once when the host fails with an unknown error.
+    #[arg(long)]
+    retry_unknown: bool,
+}
+
+impl ShareArgs {
+    pub async fn run(
+        self,
+        auth_builder: Arc<Settings>,
+        output: OutputFormat,
+    ) -> Result<(), anyhow::Error> {
+        let feed = FeedRef::new(self.twin, self.feed);
+        let data = read_data(&self.data)?;
+
+        share_data(auth_builder, &feed, data, self.retry_unknown).await?;
+
+        print_done(output, "shared", "sample", &feed.to_string())
+    }
+}
+
+/// Table of the values of a feed or an input.
+pub fn values_table(values: &[FeedValue]) -> Table {
+    let mut table = Table::new(&["VALUE", "DATA TYPE", "UNIT", "COMMENT"]);
+
+    for value in values {
+        table.row(vec![
+            value.label.clone(),
+            value.data_type.clone(),
+            value.unit.clone(),
+            value.comment.clone(),
+        ]);
+    }
+
+    table
+}
+
+/// Parse `label:data_type[:unit]`, the unit may contain colons as it's an IRI.
+fn parse_value(value: &str) -> Result<FeedValue, String> {
+    let mut parts = value.splitn(3, ':');
+
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(label), Some(data_type), unit) if !label.is_empty() && !data_type.is_empty() => {
+            Ok(FeedValue {
+                label: label.to_string(),
+                data_type: data_type.to_string(),
+                unit: unit.unwrap_or_default().to_string(),
+                comment: String::new(),
+            })
+        }
+        _ => Err(format!(
+            "invalid value [{}], expected label:data_type[:unit]",
+            value
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_value_with_a_unit() {
+        let value = parse_value("temperature:decimal:http://qudt.org/vocab/unit/DEG_C").unwrap();
+
+        assert_eq!(value.label, "temperature");
+        assert_eq!(value.data_type, "decimal");
+        assert_eq!(value.unit, "http://qudt.org/vocab/unit/DEG_C");
+        assert_eq!(value.comment, "");
+    }
+
+    #[test]
+    fn parses_a_value_without_unit() {
+        let value = parse_value("on:boolean").unwrap();
+
+        assert_eq!(value.label, "on");
+        assert_eq!(value.data_type, "boolean");
+        assert_eq!(value.unit, "");
+    }
+
+    #[test]
+    fn rejects_an_incomplete_value() {
+        for value in ["", "temperature", "temperature:", ":decimal"] {
+            assert!(parse_value(value).is_err(), "{}", value);
+        }
+    }
+}
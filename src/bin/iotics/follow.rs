@@ -0,0 +1,56 @@
+use anyhow::Context;
+use clap::Args;
+use std::sync::Arc;
+
+use iotics_grpc_client::interest::follow;
+use iotics_grpc_client::{FeedRef, HostDid, TwinDid};
+
+use crate::data::print_sample;
+use crate::output::OutputFormat;
+use crate::settings::Settings;
+
+#[derive(Args)]
+pub struct FollowArgs {
+    twin: TwinDid,
+    feed: String,
+    /// Host of the twin when it's remote.
+    #[arg(long)]
+    remote_host: Option<HostDid>,
+    /// Twin following the feed.
+    #[arg(long, env = "IOTICS_FOLLOWER_TWIN_DID")]
+    follower: TwinDid,
+    /// Start with the last sample stored by the feed.
+    #[arg(long)]
+    fetch_last: bool,
+}
+
+impl FollowArgs {
+    /// Print the samples of the feed as they're shared, until the stream ends.
+    pub async fn run(
+        self,
+        auth_builder: Arc<Settings>,
+        output: OutputFormat,
+    ) -> Result<(), anyhow::Error> {
+        let feed = FeedRef::new(self.twin, self.feed).with_host(self.remote_host);
+        let source = feed.to_string();
+
+        let mut stream = follow(auth_builder, &feed, &self.follower, self.fetch_last).await?;
+
+        while let Some(response) = stream
+            .message()
+            .await
+            .with_context(|| format!("Following [{}] failed", feed))?
+        {
+            if let Some(feed_data) = response.payload.and_then(|payload| payload.feed_data) {
+                print_sample(
+                    output,
+                    &source,
+                    feed_data.occurred_at.as_ref(),
+                    &feed_data.data,
+                );
+            }
+        }
+
+        anyhow::bail!("The host closed the stream of [{}]", feed)
+    }
+}
@@ -0,0 +1,38 @@
+use anyhow::Context;
+use clap::Subcommand;
+use serde_json::json;
+use std::sync::Arc;
+
+use iotics_grpc_client::host::get_local_host_id;
+
+use crate::output::{print_json, OutputFormat};
+use crate::settings::Settings;
+
+#[derive(Subcommand)]
+pub enum HostCommand {
+    /// Print the DID of the host.
+    Id,
+}
+
+impl HostCommand {
+    pub async fn run(
+        self,
+        auth_builder: Arc<Settings>,
+        output: OutputFormat,
+    ) -> Result<(), anyhow::Error> {
+        match self {
+            Self::Id => {
+                let response = get_local_host_id(auth_builder).await?;
+                let host_id = response.payload.context("failed to find payload")?.host_id;
+
+                match output {
+                    OutputFormat::Table => {
+                        println!("{}", host_id);
+                        Ok(())
+                    }
+                    OutputFormat::Json => print_json(&json!({ "hostId": host_id })),
+                }
+            }
+        }
+    }
+}
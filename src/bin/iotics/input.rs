@@ -0,0 +1,99 @@
+use anyhow::Context;
+use clap::Subcommand;
+use std::sync::Arc;
+
+use iotics_grpc_client::input::{delete_input, describe_input, receive_input_messages};
+use iotics_grpc_client::interest::send_input_message;
+use iotics_grpc_client::json_ld::{describe_input_to_json_ld, JsonLdContext};
+use iotics_grpc_client::{InputRef, TwinDid};
+
+use crate::data::{print_sample, read_data};
+use crate::feed::values_table;
+use crate::output::{print_done, print_json, OutputFormat};
+use crate::settings::Settings;
+use crate::twin::label;
+
+#[derive(Subcommand)]
+pub enum InputCommand {
+    /// Describe an input and its values.
+    Describe {
+        /// `[host_did/]twin_did/input_id`
+        input: InputRef,
+    },
+    /// Delete an input.
+    Delete {
+        /// `twin_did/input_id`
+        input: InputRef,
+    },
+    /// Print the messages received by an input as they arrive.
+    Listen {
+        /// `twin_did/input_id`
+        input: InputRef,
+    },
+    /// Send a message to an input.
+    Send {
+        /// `[host_did/]twin_did/input_id`
+        input: InputRef,
+        /// Twin sending the message.
+        #[arg(long, env = "IOTICS_SENDER_TWIN_DID")]
+        sender: TwinDid,
+        /// JSON message: inline, `@file.json` or `@-` for the standard input.
+        #[arg(long)]
+        data: String,
+    },
+}
+
+impl InputCommand {
+    pub async fn run(
+        self,
+        auth_builder: Arc<Settings>,
+        output: OutputFormat,
+    ) -> Result<(), anyhow::Error> {
+        match self {
+            Self::Describe { input } => {
+                let response = describe_input(auth_builder, &input).await?;
+
+                match output {
+                    OutputFormat::Table => {
+                        let result = response
+                            .payload
+                            .and_then(|payload| payload.result)
+                            .context("failed to find result")?;
+
+                        println!("{}  {}", input, label(&result.properties));
+                        values_table(&result.values).print();
+                        Ok(())
+                    }
+                    OutputFormat::Json => print_json(&describe_input_to_json_ld(
+                        &response,
+                        &JsonLdContext::default(),
+                    )?),
+                }
+            }
+            Self::Delete { input } => {
+                delete_input(auth_builder, &input).await?;
+                print_done(output, "deleted", "input", &input.to_string())
+            }
+            Self::Listen { input } => {
+                let source = input.to_string();
+                let mut messages = receive_input_messages(auth_builder, &input).await?;
+
+                while let Some(message) = messages.recv().await {
+                    print_sample(output, &source, None, &message?);
+                }
+
+                anyhow::bail!("The host closed the stream of [{}]", input)
+            }
+            Self::Send {
+                input,
+                sender,
+                data,
+            } => {
+                let data = read_data(&data)?;
+
+                send_input_message(auth_builder, &input, &sender, data).await?;
+                print_done(output, "sent", "message", &input.to_string())
+            }
+        }
+    }
+}
@@ -0,0 +1,79 @@
+//! `iotics` command line tool, built with the `cli` feature.
+
+mod data;
+mod feed;
+mod follow;
+mod host;
+mod input;
+mod output;
+mod search;
+mod settings;
+mod spec;
+mod twin;
+
+use clap::{Parser, Subcommand};
+use std::sync::Arc;
+
+use output::OutputFormat;
+use settings::Settings;
+
+/// Inspect and manage the twins of an IOTICS host.
+#[derive(Parser)]
+#[command(name = "iotics", version)]
+struct Cli {
+    #[command(flatten)]
+    settings: Settings,
+
+    /// Output format.
+    #[arg(long, short, value_enum, global = true, default_value_t = OutputFormat::Table)]
+    output: OutputFormat,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List, describe, upsert and delete twins.
+    #[command(subcommand)]
+    Twin(twin::TwinCommand),
+    /// Describe, create and delete feeds.
+    #[command(subcommand)]
+    Feed(feed::FeedCommand),
+    /// Describe and delete inputs, send and receive their messages.
+    #[command(subcommand)]
+    Input(input::InputCommand),
+    /// Print the samples of a feed as they're shared, like `tail -f`.
+    Follow(follow::FollowArgs),
+    /// Share a sample of a feed.
+    Share(feed::ShareArgs),
+    /// Search twins on the local host or on all the hosts of the network.
+    Search(search::SearchArgs),
+    /// Information about the host.
+    #[command(subcommand)]
+    Host(host::HostCommand),
+}
+
+#[tokio::main]
+async fn main() {
+    // loaded before parsing the arguments so that it provides their env fallbacks
+    dotenvy::dotenv().ok();
+
+    let cli = Cli::parse();
+    let auth_builder = Arc::new(cli.settings);
+
+    let result = match cli.command {
+        Command::Twin(command) => command.run(auth_builder, cli.output).await,
+        Command::Feed(command) => command.run(auth_builder, cli.output).await,
+        Command::Input(command) => command.run(auth_builder, cli.output).await,
+        Command::Follow(args) => args.run(auth_builder, cli.output).await,
+        Command::Share(args) => args.run(auth_builder, cli.output).await,
+        Command::Search(args) => args.run(auth_builder, cli.output).await,
+        Command::Host(command) => command.run(auth_builder, cli.output).await,
+    };
+
+    if let Err(e) = result {
+        eprintln!("Error: {:#}", e);
+        std::process::exit(1);
+    }
+}
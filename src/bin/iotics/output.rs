@@ -0,0 +1,80 @@
+use clap::ValueEnum;
+use serde_json::{json, Value as JsonValue};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Table,
+    Json,
+}
+
+/// Rows printed with their columns aligned.
+pub struct Table {
+    headers: Vec<&'static str>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new(headers: &[&'static str]) -> Self {
+        Self {
+            headers: headers.to_vec(),
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn row(&mut self, row: Vec<String>) {
+        self.rows.push(row);
+    }
+
+    pub fn print(&self) {
+        let mut widths = self.headers.iter().map(|h| h.len()).collect::<Vec<_>>();
+
+        for row in &self.rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let headers = self
+            .headers
+            .iter()
+            .map(|h| h.to_string())
+            .collect::<Vec<_>>();
+
+        print_row(&headers, &widths);
+        for row in &self.rows {
+            print_row(row, &widths);
+        }
+    }
+}
+
+fn print_row(row: &[String], widths: &[usize]) {
+    let line = row
+        .iter()
+        .zip(widths)
+        .map(|(cell, width)| format!("{:width$}", cell, width = width))
+        .collect::<Vec<_>>()
+        .join("  ");
+
+    println!("{}", line.trim_end());
+}
+
+pub fn print_json(value: &JsonValue) -> Result<(), anyhow::Error> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+/// Report a completed operation on an entity, e.g. `deleted` twin.
+pub fn print_done(
+    output: OutputFormat,
+    action: &str,
+    kind: &str,
+    id: &str,
+) -> Result<(), anyhow::Error> {
+    match output {
+        OutputFormat::Table => {
+            println!("{} {} {}", action, kind, id);
+            Ok(())
+        }
+        OutputFormat::Json => print_json(&json!({ "action": action, "kind": kind, "id": id })),
+    }
+}
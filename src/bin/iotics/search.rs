@@ -0,0 +1,221 @@
+use clap::{Args, ValueEnum};
+use indicatif::{ProgressBar, ProgressStyle};
+use serde_json::{Map, Value as JsonValue};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use iotics_grpc_client::client::google::protobuf::StringValue;
+use iotics_grpc_client::host::LocalHostCache;
+use iotics_grpc_client::json_ld::{search_twin_to_json_ld, JsonLdContext};
+use iotics_grpc_client::search::{search, Filter, TwinDetails};
+use iotics_grpc_client::{GeoCircle, GeoLocation, Scope};
+
+use crate::output::{print_json, OutputFormat, Table};
+use crate::settings::Settings;
+use crate::twin::{label, location};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum SearchScope {
+    Local,
+    Global,
+}
+
+impl From<SearchScope> for Scope {
+    fn from(scope: SearchScope) -> Self {
+        match scope {
+            SearchScope::Local => Scope::Local,
+            SearchScope::Global => Scope::Global,
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct SearchArgs {
+    /// Words to find in the labels, comments and string properties.
+    #[arg(long)]
+    text: Option<String>,
+    /// Twins located in a circle, as `lat,lon,radius_km`.
+    #[arg(long, value_parser = parse_circle, allow_hyphen_values = true)]
+    near: Option<GeoCircle>,
+    #[arg(long, value_enum, default_value_t = SearchScope::Local)]
+    scope: SearchScope,
+    /// Seconds to wait for the responses of the hosts.
+    #[arg(long, default_value_t = 10)]
+    timeout: u64,
+}
+
+impl SearchArgs {
+    /// Collect the responses until the timeout, then print the twins found grouped by host.
+    pub async fn run(
+        self,
+        auth_builder: Arc<Settings>,
+        output: OutputFormat,
+    ) -> Result<(), anyhow::Error> {
+        let local_host = LocalHostCache::new(auth_builder.clone());
+        let local_host = local_host.local_host().await?;
+
+        let filter = Filter {
+            properties: Vec::new(),
+            location: self.near,
+            text: self.text.map(|value| StringValue { value }),
+        };
+
+        let mut responses = search(
+            auth_builder,
+            filter,
+            self.scope.into(),
+            Some(Duration::from_secs(self.timeout)),
+        )
+        .await?;
+
+        let progress = ProgressBar::new_spinner();
+        progress.set_style(
+            ProgressStyle::with_template("{spinner} {elapsed} {msg}")
+                .expect("the template is valid"),
+        );
+        progress.enable_steady_tick(Duration::from_millis(100));
+
+        let mut found: BTreeMap<String, Vec<TwinDetails>> = BTreeMap::new();
+        let mut twins_count = 0;
+
+        while let Some(response) = responses.recv().await {
+            let mut response = match response {
+                Ok(response) => response,
+                Err(e) => {
+                    progress.suspend(|| eprintln!("Warning: {:#}", e));
+                    continue;
+                }
+            };
+
+            local_host.normalize_search_response(&mut response);
+
+            if let Some(payload) = response.payload {
+                twins_count += payload.twins.len();
+                found
+                    .entry(payload.host_id)
+                    .or_default()
+                    .extend(payload.twins);
+            }
+
+            progress.set_message(format!(
+                "{} twins found on {} hosts",
+                twins_count,
+                found.len()
+            ));
+        }
+
+        progress.finish_and_clear();
+
+        match output {
+            OutputFormat::Table => {
+                for (host_id, twins) in &found {
+                    let local = if local_host.is_local(host_id) {
+                        " (local)"
+                    } else {
+                        ""
+                    };
+                    println!("host {}{}: {} twins", host_id, local, twins.len());
+
+                    if twins.is_empty() {
+                        println!();
+                        continue;
+                    }
+
+                    let mut table = Table::new(&["TWIN", "LABEL", "LOCATION", "FEEDS"]);
+
+                    for twin in twins {
+                        let feeds = twin
+                            .feeds
+                            .iter()
+                            .filter_map(|feed| feed.feed_id.as_ref())
+                            .map(|feed_id| feed_id.id.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ");
+
+                        table.row(vec![
+                            twin.twin_id
+                                .as_ref()
+                                .map(|twin_id| twin_id.id.clone())
+                                .unwrap_or_default(),
+                            label(&twin.properties),
+                            location(twin.location.as_ref()),
+                            feeds,
+                        ]);
+                    }
+
+                    table.print();
+                    println!();
+                }
+
+                Ok(())
+            }
+            OutputFormat::Json => {
+                let context = JsonLdContext::default();
+                let mut hosts = Map::new();
+
+                for (host_id, twins) in &found {
+                    let twins = twins
+                        .iter()
+                        .map(|twin| search_twin_to_json_ld(twin, &context))
+                        .collect::<Result<Vec<_>, _>>()?;
+
+                    hosts.insert(host_id.clone(), twins.into());
+                }
+
+                print_json(&JsonValue::Object(hosts))
+            }
+        }
+    }
+}
+
+/// Parse `lat,lon,radius_km`.
+fn parse_circle(value: &str) -> Result<GeoCircle, String> {
+    let parts = value
+        .split(',')
+        .map(|part| part.trim().parse::<f64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("invalid circle [{}]: {}", value, e))?;
+
+    match parts.as_slice() {
+        [lat, lon, radius_km] => Ok(GeoCircle {
+            location: Some(GeoLocation {
+                lat: *lat,
+                lon: *lon,
+            }),
+            radius_km: *radius_km,
+        }),
+        _ => Err(format!(
+            "invalid circle [{}], expected lat,lon,radius_km",
+            value
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_circle() {
+        let circle = parse_circle("51.5, -0.12, 10").unwrap();
+
+        assert_eq!(
+            circle,
+            GeoCircle {
+                location: Some(GeoLocation {
+                    lat: 51.5,
+                    lon: -0.12,
+                }),
+                radius_km: 10.0,
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_an_invalid_circle() {
+        for value in ["", "51.5,-0.12", "51.5,-0.12,10,1", "north,-0.12,10"] {
+            assert!(parse_circle(value).is_err(), "{}", value);
+        }
+    }
+}
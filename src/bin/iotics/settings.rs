@@ -0,0 +1,38 @@
+use anyhow::Context;
+use clap::Args;
+
+use iotics_grpc_client::IntoAuthBuilder;
+
+/// Connection settings, read from the arguments or the environment, including a `.env` file.
+#[derive(Clone, Args)]
+pub struct Settings {
+    /// Address of the host, e.g. `https://my-space.iotics.space`.
+    #[arg(long = "host", env = "IOTICS_HOST_ADDRESS", global = true)]
+    host_address: Option<String>,
+
+    /// Token of the agent, with or without its `bearer` prefix.
+    #[arg(long, env = "IOTICS_TOKEN", hide_env_values = true, global = true)]
+    token: Option<String>,
+}
+
+impl IntoAuthBuilder for Settings {
+    fn get_host(&self) -> Result<String, anyhow::Error> {
+        self.host_address
+            .clone()
+            .context("the host address is missing, set IOTICS_HOST_ADDRESS or pass --host")
+    }
+
+    fn get_token(&self) -> Result<String, anyhow::Error> {
+        let token = self
+            .token
+            .as_deref()
+            .context("the token is missing, set IOTICS_TOKEN or pass --token")?
+            .trim();
+
+        if token.to_lowercase().starts_with("bearer ") {
+            Ok(token.to_string())
+        } else {
+            Ok(format!("bearer {}", token))
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use anyhow::Context;
+use serde::Deserialize;
+use serde_json::{Map, Value as JsonValue};
+use std::path::Path;
+
+use iotics_grpc_client::json_ld::properties_from_json_ld;
+use iotics_grpc_client::twin::bulk::UpsertTwinSpec;
+use iotics_grpc_client::twin::{UpsertFeedWithMeta, UpsertInputWithMeta};
+use iotics_grpc_client::{FeedValue, GeoLocation, Property, TwinDid};
+
+/// Twin read by `twin upsert` from a YAML file.
+///
+/// The properties are JSON-LD nodes, their compact IRIs are expanded with the `@context` given at
+/// the top level of the file unless they have their own.
+///
+/// ```yaml
+/// "@context":
+///   rdfs: http://www.w3.org/2000/01/rdf-schema#
+/// id: did:iotics:iotXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX
+/// location: { lat: 51.5, lon: -0.12 }
+/// properties:
+///   rdfs:label: { "@value": Weather station, "@language": en }
+/// feeds:
+///   - id: temperature
+///     store_last: true
+///     properties:
+///       rdfs:label: { "@value": Temperature, "@language": en }
+///     values:
+///       - { label: reading, data_type: decimal, unit: http://qudt.org/vocab/unit/DEG_C }
+/// ```
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TwinSpec {
+    #[serde(rename = "@context", default)]
+    context: Option<JsonValue>,
+    id: TwinDid,
+    #[serde(default)]
+    location: Option<LocationSpec>,
+    #[serde(default)]
+    properties: Option<JsonValue>,
+    #[serde(default)]
+    feeds: Vec<FeedSpec>,
+    #[serde(default)]
+    inputs: Vec<InputSpec>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LocationSpec {
+    lat: f64,
+    lon: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FeedSpec {
+    id: String,
+    #[serde(default)]
+    store_last: bool,
+    #[serde(default)]
+    properties: Option<JsonValue>,
+    #[serde(default)]
+    values: Vec<ValueSpec>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct InputSpec {
+    id: String,
+    #[serde(default)]
+    properties: Option<JsonValue>,
+    #[serde(default)]
+    values: Vec<ValueSpec>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ValueSpec {
+    label: String,
+    data_type: String,
+    #[serde(default)]
+    unit: String,
+    #[serde(default)]
+    comment: String,
+}
+
+impl From<ValueSpec> for FeedValue {
+    fn from(value: ValueSpec) -> Self {
+        FeedValue {
+            label: value.label,
+            comment: value.comment,
+            unit: value.unit,
+            data_type: value.data_type,
+        }
+    }
+}
+
+impl TwinSpec {
+    pub fn load(path: &Path) -> Result<Self, anyhow::Error> {
+        let file = std::fs::read_to_string(path)
+            .with_context(|| format!("Reading [{}] failed", path.display()))?;
+
+        serde_yaml::from_str(&file).with_context(|| format!("Parsing [{}] failed", path.display()))
+    }
+
+    pub fn into_upsert(self) -> Result<UpsertTwinSpec, anyhow::Error> {
+        let context = self.context.as_ref();
+
+        let feeds = self
+            .feeds
+            .into_iter()
+            .map(|feed| {
+                Ok(UpsertFeedWithMeta {
+                    properties: properties(feed.properties, context)
+                        .with_context(|| format!("Invalid properties of feed [{}]", feed.id))?,
+                    id: feed.id,
+                    store_last: feed.store_last,
+                    values: feed.values.into_iter().map(FeedValue::from).collect(),
+                })
+            })
+            .collect::<Result<Vec<_>, anyhow::Error>>()?;
+
+        let inputs = self
+            .inputs
+            .into_iter()
+            .map(|input| {
+                Ok(UpsertInputWithMeta {
+                    properties: properties(input.properties, context)
+                        .with_context(|| format!("Invalid properties of input [{}]", input.id))?,
+                    id: input.id,
+                    values: input.values.into_iter().map(FeedValue::from).collect(),
+                })
+            })
+            .collect::<Result<Vec<_>, anyhow::Error>>()?;
+
+        Ok(UpsertTwinSpec {
+            properties: properties(self.properties, context)
+                .context("Invalid properties of the twin")?,
+            twin_id: self.id,
+            feeds,
+            inputs,
+            location: self.location.map(|location| GeoLocation {
+                lat: location.lat,
+                lon: location.lon,
+            }),
+        })
+    }
+}
+
+/// Build the properties of a JSON-LD node, adding the top level context when it has none.
+fn properties(
+    node: Option<JsonValue>,
+    context: Option<&JsonValue>,
+) -> Result<Vec<Property>, anyhow::Error> {
+    let mut node = match node {
+        Some(JsonValue::Object(node)) => node,
+        Some(JsonValue::Null) | None => Map::new(),
+        Some(_) => anyhow::bail!("properties must be a map"),
+    };
+
+    if let Some(context) = context {
+        node.entry("@context").or_insert_with(|| context.clone());
+    }
+
+    properties_from_json_ld(&JsonValue::Object(node))
+}
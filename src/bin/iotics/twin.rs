@@ -0,0 +1,168 @@
+use anyhow::Context;
+use clap::Subcommand;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use iotics_grpc_client::json_ld::{describe_twin_to_json_ld, list_twin_to_json_ld, JsonLdContext};
+use iotics_grpc_client::properties::PropertyFinder;
+use iotics_grpc_client::twin::crud::delete_twin;
+use iotics_grpc_client::twin::describe::describe_twin;
+use iotics_grpc_client::twin::list::list_all_twins;
+use iotics_grpc_client::twin::upsert::upsert_twin;
+use iotics_grpc_client::{FeedValue, GeoLocation, HostDid, Property, TwinDid};
+
+use crate::output::{print_done, print_json, OutputFormat, Table};
+use crate::settings::Settings;
+use crate::spec::TwinSpec;
+
+#[derive(Subcommand)]
+pub enum TwinCommand {
+    /// List the twins of the host.
+    List,
+    /// Describe a twin with its feeds and inputs.
+    Describe {
+        twin: TwinDid,
+        /// Host of the twin when it's remote.
+        #[arg(long)]
+        remote_host: Option<HostDid>,
+    },
+    /// Create or update a twin, its feeds and inputs from a YAML file.
+    Upsert {
+        #[arg(long, short)]
+        file: PathBuf,
+    },
+    /// Delete a twin.
+    Delete { twin: TwinDid },
+}
+
+impl TwinCommand {
+    pub async fn run(
+        self,
+        auth_builder: Arc<Settings>,
+        output: OutputFormat,
+    ) -> Result<(), anyhow::Error> {
+        match self {
+            Self::List => {
+                let twins = list_all_twins(auth_builder).await?;
+
+                match output {
+                    OutputFormat::Table => {
+                        let mut table = Table::new(&["TWIN", "LABEL", "LOCATION"]);
+
+                        for twin in &twins {
+                            let twin_id =
+                                twin.twin_id.as_ref().context("failed to find twin id")?;
+
+                            table.row(vec![
+                                twin_id.id.clone(),
+                                label(&twin.properties),
+                                location(twin.location.as_ref()),
+                            ]);
+                        }
+
+                        table.print();
+                        Ok(())
+                    }
+                    OutputFormat::Json => {
+                        let context = JsonLdContext::default();
+                        let twins = twins
+                            .iter()
+                            .map(|twin| list_twin_to_json_ld(twin, &context))
+                            .collect::<Result<Vec<_>, _>>()?;
+
+                        print_json(&twins.into())
+                    }
+                }
+            }
+            Self::Describe { twin, remote_host } => {
+                let response = describe_twin(auth_builder, &twin, remote_host.as_ref()).await?;
+
+                match output {
+                    OutputFormat::Table => {
+                        let payload = response.payload.context("failed to find payload")?;
+                        let mut table = Table::new(&["KIND", "ID", "LABEL", "DETAILS"]);
+
+                        table.row(vec![
+                            "twin".to_string(),
+                            twin.to_string(),
+                            label(&payload.properties),
+                            location(payload.location.as_ref()),
+                        ]);
+
+                        for feed in &payload.feeds {
+                            let feed_id =
+                                feed.feed_id.as_ref().context("failed to find feed id")?;
+
+                            table.row(vec![
+                                "feed".to_string(),
+                                feed_id.id.clone(),
+                                label(&feed.properties),
+                                values(&feed.values),
+                            ]);
+                        }
+
+                        for input in &payload.inputs {
+                            let input_id =
+                                input.input_id.as_ref().context("failed to find input id")?;
+
+                            table.row(vec![
+                                "input".to_string(),
+                                input_id.id.clone(),
+                                label(&input.properties),
+                                values(&input.values),
+                            ]);
+                        }
+
+                        table.print();
+                        Ok(())
+                    }
+                    OutputFormat::Json => print_json(&describe_twin_to_json_ld(
+                        &response,
+                        &JsonLdContext::default(),
+                    )?),
+                }
+            }
+            Self::Upsert { file } => {
+                let spec = TwinSpec::load(&file)?.into_upsert()?;
+
+                upsert_twin(
+                    auth_builder,
+                    &spec.twin_id,
+                    spec.properties,
+                    spec.feeds,
+                    spec.inputs,
+                    spec.location,
+                )
+                .await?;
+
+                print_done(output, "upserted", "twin", spec.twin_id.as_str())
+            }
+            Self::Delete { twin } => {
+                delete_twin(auth_builder, &twin).await?;
+                print_done(output, "deleted", "twin", twin.as_str())
+            }
+        }
+    }
+}
+
+/// The English label, or the label without language when there's none.
+pub fn label(properties: &[Property]) -> String {
+    PropertyFinder::find_label_by_preference(properties, &["en"])
+        .unwrap_or_default()
+        .to_string()
+}
+
+pub fn location(location: Option<&GeoLocation>) -> String {
+    location
+        .map(|location| format!("{}, {}", location.lat, location.lon))
+        .unwrap_or_default()
+}
+
+/// The labels of the values with their data type.
+pub fn values(values: &[FeedValue]) -> String {
+    values
+        .iter()
+        .map(|value| format!("{}: {}", value.label, value.data_type))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
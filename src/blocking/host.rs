@@ -0,0 +1,26 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::auth_builder::IntoAuthBuilder;
+use crate::blocking::block_on;
+use crate::health::HealthReport;
+use crate::host::GetHostIdResponse;
+
+pub fn get_local_host_id(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+) -> Result<GetHostIdResponse, anyhow::Error> {
+    block_on(crate::host::get_local_host_id(auth_builder))
+}
+
+/// See [`crate::health::health`].
+pub fn health(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    check_grpc_health: bool,
+    timeout: Duration,
+) -> Result<HealthReport, anyhow::Error> {
+    block_on(crate::health::health(
+        auth_builder,
+        check_grpc_health,
+        timeout,
+    ))
+}
@@ -0,0 +1,29 @@
+use std::sync::Arc;
+
+use crate::auth_builder::IntoAuthBuilder;
+use crate::blocking::{block_on, BlockingIter};
+use crate::client::iotics::api::{DeleteInputResponse, DescribeInputResponse};
+use crate::ids::InputRef;
+
+/// Iterator over the messages received by the input, until the stream ends.
+pub fn receive_input_messages(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    input: &InputRef,
+) -> Result<BlockingIter<Result<Vec<u8>, anyhow::Error>>, anyhow::Error> {
+    let receiver = block_on(crate::input::receive_input_messages(auth_builder, input))?;
+    Ok(BlockingIter::from_receiver(receiver))
+}
+
+pub fn describe_input(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    input: &InputRef,
+) -> Result<DescribeInputResponse, anyhow::Error> {
+    block_on(crate::input::describe_input(auth_builder, input))
+}
+
+pub fn delete_input(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    input: &InputRef,
+) -> Result<DeleteInputResponse, anyhow::Error> {
+    block_on(crate::input::delete_input(auth_builder, input))
+}
@@ -0,0 +1,40 @@
+use futures::stream::StreamExt;
+use std::sync::Arc;
+
+use crate::auth_builder::IntoAuthBuilder;
+use crate::blocking::{block_on, BlockingIter};
+use crate::ids::{FeedRef, InputRef, TwinDid};
+use crate::interest::FetchInterestResponse;
+
+/// Iterator over the samples shared by the followed feed, until the stream ends.
+pub fn follow(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    followed_feed: &FeedRef,
+    follower_twin_id: &TwinDid,
+    fetch_last_stored: bool,
+) -> Result<BlockingIter<Result<FetchInterestResponse, anyhow::Error>>, anyhow::Error> {
+    let stream = block_on(crate::interest::follow(
+        auth_builder,
+        followed_feed,
+        follower_twin_id,
+        fetch_last_stored,
+    ))?;
+
+    Ok(BlockingIter::new(
+        stream.map(|response| response.map_err(anyhow::Error::from)),
+    ))
+}
+
+pub fn send_input_message<T: Into<Vec<u8>>>(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    receiver_input: &InputRef,
+    sender_twin_id: &TwinDid,
+    data: T,
+) -> Result<(), anyhow::Error> {
+    block_on(crate::interest::send_input_message(
+        auth_builder,
+        receiver_input,
+        sender_twin_id,
+        data,
+    ))
+}
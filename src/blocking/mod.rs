@@ -0,0 +1,126 @@
+//! Synchronous API behind the `blocking` feature, mirroring the async functions of the crate that
+//! create their own channel, in the `host`, `input`, `interest`, `search` and `twin` modules.
+//! The `*_with_channel` variants, the bulk helpers, the feed publisher and the connectors have no
+//! blocking counterpart.
+//!
+//! The calls run on a multi-threaded runtime started on first use and shared by the whole process,
+//! which also runs the tasks receiving the streams between two calls. Like any other blocking call,
+//! they must not be made from an async context: they panic when called within a tokio runtime.
+
+pub mod host;
+pub mod input;
+pub mod interest;
+pub mod search;
+pub mod twin;
+
+use futures::stream::{Stream, StreamExt};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::OnceLock;
+use tokio::runtime::Runtime;
+use tokio::sync::mpsc;
+
+fn runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .thread_name("iotics-blocking")
+            .enable_all()
+            .build()
+            .expect("Failed to start the runtime of the blocking API")
+    })
+}
+
+pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+    runtime().block_on(future)
+}
+
+/// Blocking iterator over the items of a stream, e.g. the samples of a followed feed.
+///
+/// Each call to `next` blocks until the next item is received, `None` is returned once the stream
+/// has ended.
+pub struct BlockingIter<T> {
+    stream: Pin<Box<dyn Stream<Item = T> + Send>>,
+}
+
+impl<T> BlockingIter<T> {
+    pub(crate) fn new(stream: impl Stream<Item = T> + Send + 'static) -> Self {
+        Self {
+            stream: Box::pin(stream),
+        }
+    }
+
+    pub(crate) fn from_receiver(receiver: mpsc::Receiver<T>) -> Self
+    where
+        T: Send + 'static,
+    {
+        Self::new(futures::stream::unfold(
+            receiver,
+            |mut receiver| async move { receiver.recv().await.map(|item| (item, receiver)) },
+        ))
+    }
+}
+
+impl<T> Iterator for BlockingIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        block_on(self.stream.next())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::sync::oneshot;
+
+    #[test]
+    fn iterates_over_the_received_items_until_the_sender_is_dropped() {
+        let (tx, rx) = mpsc::channel(4);
+
+        for item in 1..=3 {
+            tx.try_send(item).unwrap();
+        }
+        drop(tx);
+
+        let items = BlockingIter::from_receiver(rx).collect::<Vec<_>>();
+
+        assert_eq!(items, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn blocks_until_the_next_item_is_received() {
+        let (tx, rx) = mpsc::channel(1);
+
+        let sender = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            tx.blocking_send("late").unwrap();
+        });
+
+        let mut items = BlockingIter::from_receiver(rx);
+
+        assert_eq!(items.next(), Some("late"));
+        assert_eq!(items.next(), None);
+        sender.join().unwrap();
+    }
+
+    #[test]
+    fn shares_the_runtime_between_the_calls() {
+        assert!(std::ptr::eq(runtime(), runtime()));
+
+        // a task spawned by a call keeps running after it returns, on the threads of the runtime
+        let (tx, rx) = oneshot::channel();
+        block_on(async {
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                let _ = tx.send(std::thread::current().name().map(str::to_string));
+            });
+        });
+
+        let thread_name = block_on(rx).unwrap();
+
+        assert_eq!(thread_name.as_deref(), Some("iotics-blocking"));
+    }
+}
@@ -0,0 +1,20 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::auth_builder::IntoAuthBuilder;
+use crate::blocking::{block_on, BlockingIter};
+use crate::client::iotics::api::Scope;
+use crate::search::{Filter, SearchResponse};
+
+/// Iterator over the responses of the hosts, one per host and page, until the timeout.
+///
+/// Without a timeout, the iterator never ends.
+pub fn search(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    filter: Filter,
+    scope: Scope,
+    timeout: Option<Duration>,
+) -> Result<BlockingIter<Result<SearchResponse, anyhow::Error>>, anyhow::Error> {
+    let receiver = block_on(crate::search::search(auth_builder, filter, scope, timeout))?;
+    Ok(BlockingIter::from_receiver(receiver))
+}
@@ -0,0 +1,142 @@
+use std::sync::Arc;
+use tonic::Response;
+
+use crate::auth_builder::IntoAuthBuilder;
+use crate::blocking::{block_on, BlockingIter};
+use crate::client::iotics::api::{GeoLocation, Property, PropertyUpdate, Value as FeedValue};
+use crate::ids::{FeedRef, HostDid, TwinDid};
+use crate::twin::list::ListOptions;
+use crate::twin::{
+    DescribeFeedResponse, DescribeTwinResponse, TwinDetails, UpsertFeedWithMeta,
+    UpsertInputWithMeta, UpsertTwinResponse,
+};
+
+pub fn describe_twin(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    twin_id: &TwinDid,
+    remote_host_id: Option<&HostDid>,
+) -> Result<DescribeTwinResponse, anyhow::Error> {
+    block_on(crate::twin::describe::describe_twin(
+        auth_builder,
+        twin_id,
+        remote_host_id,
+    ))
+}
+
+pub fn describe_feed(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    feed: &FeedRef,
+) -> Result<DescribeFeedResponse, anyhow::Error> {
+    block_on(crate::twin::describe::describe_feed(auth_builder, feed))
+}
+
+pub fn list_all_twins(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+) -> Result<Vec<TwinDetails>, anyhow::Error> {
+    block_on(crate::twin::list::list_all_twins(auth_builder))
+}
+
+/// Iterator over the twins of the host, a page is requested when the previous one has been
+/// consumed, see [`crate::twin::list::list_all_twins_stream`].
+pub fn list_all_twins_iter<A>(
+    auth_builder: Arc<A>,
+    options: ListOptions,
+) -> Result<BlockingIter<Result<TwinDetails, anyhow::Error>>, anyhow::Error>
+where
+    A: IntoAuthBuilder + Send + Sync + 'static,
+{
+    let stream = block_on(crate::twin::list::list_all_twins_stream(
+        auth_builder,
+        options,
+    ))?;
+
+    Ok(BlockingIter::new(stream))
+}
+
+pub fn upsert_twin(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    twin_id: &TwinDid,
+    properties: Vec<Property>,
+    feeds: Vec<UpsertFeedWithMeta>,
+    inputs: Vec<UpsertInputWithMeta>,
+    location: Option<GeoLocation>,
+) -> Result<Response<UpsertTwinResponse>, anyhow::Error> {
+    block_on(crate::twin::upsert::upsert_twin(
+        auth_builder,
+        twin_id,
+        properties,
+        feeds,
+        inputs,
+        location,
+    ))
+}
+
+pub fn create_update_twin(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    twin_id: &TwinDid,
+    properties: Vec<Property>,
+    location: Option<GeoLocation>,
+) -> Result<(), anyhow::Error> {
+    block_on(crate::twin::crud::create_update_twin(
+        auth_builder,
+        twin_id,
+        properties,
+        location,
+    ))
+}
+
+pub fn update_twin(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    twin_id: &TwinDid,
+    properties: PropertyUpdate,
+) -> Result<(), anyhow::Error> {
+    block_on(crate::twin::crud::update_twin(
+        auth_builder,
+        twin_id,
+        properties,
+    ))
+}
+
+pub fn delete_twin(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    twin_id: &TwinDid,
+) -> Result<(), anyhow::Error> {
+    block_on(crate::twin::crud::delete_twin(auth_builder, twin_id))
+}
+
+pub fn create_update_feed(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    feed: &FeedRef,
+    store_last: bool,
+    properties: Vec<Property>,
+    values: Vec<FeedValue>,
+) -> Result<(), anyhow::Error> {
+    block_on(crate::twin::crud::create_update_feed(
+        auth_builder,
+        feed,
+        store_last,
+        properties,
+        values,
+    ))
+}
+
+pub fn delete_feed(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    feed: &FeedRef,
+) -> Result<(), anyhow::Error> {
+    block_on(crate::twin::crud::delete_feed(auth_builder, feed))
+}
+
+pub fn share_data<T: Into<Vec<u8>>>(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    feed: &FeedRef,
+    data: T,
+    retry_unknown: bool,
+) -> Result<(), anyhow::Error> {
+    block_on(crate::twin::share::share_data(
+        auth_builder,
+        feed,
+        data,
+        retry_unknown,
+    ))
+}
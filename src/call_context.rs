@@ -0,0 +1,249 @@
+use std::future::Future;
+use std::time::{Duration, SystemTime};
+
+use crate::client::google::protobuf::{StringValue, Timestamp};
+use crate::client::iotics::api::Headers;
+use crate::helpers::generate_client_app_id;
+
+tokio::task_local! {
+    static CALL_CONTEXT: CallContext;
+}
+
+/// The values sent in the `Headers` of the requests.
+///
+/// The defaults of a client are set in [`crate::ClientConfig::call_context`], they're
+/// overridden for the calls made within [`CallContext::scope`]. A random client app id is generated
+/// when none is given, and it's used as transaction ref when there's none.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CallContext {
+    client_app_id: Option<String>,
+    client_ref: Option<String>,
+    transaction_ref: Vec<String>,
+    consumer_group: Option<String>,
+    request_timeout: Option<Duration>,
+}
+
+impl CallContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn client_app_id(mut self, client_app_id: impl Into<String>) -> Self {
+        self.client_app_id = Some(client_app_id.into());
+        self
+    }
+
+    /// Reference echoed in the responses. The searches append a random suffix unique to each search
+    /// and the page number to it.
+    pub fn client_ref(mut self, client_ref: impl Into<String>) -> Self {
+        self.client_ref = Some(client_ref.into());
+        self
+    }
+
+    /// Add a transaction ref, e.g. the id of the request being served, to correlate a chain of calls.
+    pub fn transaction_ref(mut self, transaction_ref: impl Into<String>) -> Self {
+        self.transaction_ref.push(transaction_ref.into());
+        self
+    }
+
+    /// Consumer group of the followers and input receivers sharing the messages.
+    pub fn consumer_group(mut self, consumer_group: impl Into<String>) -> Self {
+        self.consumer_group = Some(consumer_group.into());
+        self
+    }
+
+    /// Time after which the host gives up on the request, from the time the request is built.
+    pub fn request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = Some(request_timeout);
+        self
+    }
+
+    /// Run the future with this context overriding the defaults of the client for every call it makes.
+    /// The tasks it spawns don't inherit it.
+    pub async fn scope<F: Future>(self, f: F) -> F::Output {
+        CALL_CONTEXT.scope(self, f).await
+    }
+
+    /// The context of the enclosing [`CallContext::scope`], if any.
+    pub fn current() -> Option<Self> {
+        CALL_CONTEXT.try_with(Clone::clone).ok()
+    }
+
+    /// Build the headers of a request from the defaults of the client and the current scope.
+    pub(crate) fn headers(defaults: CallContext) -> Headers {
+        let context = match Self::current() {
+            Some(scoped) => defaults.merge(scoped),
+            None => defaults,
+        };
+
+        let client_app_id = context.client_app_id.unwrap_or_else(generate_client_app_id);
+
+        let transaction_ref = if context.transaction_ref.is_empty() {
+            vec![client_app_id.clone()]
+        } else {
+            context.transaction_ref
+        };
+
+        Headers {
+            client_app_id,
+            client_ref: context.client_ref.unwrap_or_default(),
+            transaction_ref,
+            consumer_group: context.consumer_group.map(|value| StringValue { value }),
+            request_timeout: context.request_timeout.map(Self::deadline),
+        }
+    }
+
+    /// The request timeout of the current scope or of the defaults, for the calls that rebuild
+    /// the deadline of their headers with [`CallContext::deadline`] when they're sent later.
+    pub(crate) fn current_request_timeout(defaults: &CallContext) -> Option<Duration> {
+        Self::current()
+            .and_then(|scoped| scoped.request_timeout)
+            .or(defaults.request_timeout)
+    }
+
+    /// The `request_timeout` of the headers of a request built now.
+    pub(crate) fn deadline(request_timeout: Duration) -> Timestamp {
+        let deadline = (SystemTime::now() + request_timeout)
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default();
+
+        Timestamp {
+            seconds: deadline.as_secs() as i64,
+            nanos: deadline.subsec_nanos() as i32,
+        }
+    }
+
+    fn merge(self, scoped: CallContext) -> Self {
+        Self {
+            client_app_id: scoped.client_app_id.or(self.client_app_id),
+            client_ref: scoped.client_ref.or(self.client_ref),
+            transaction_ref: if scoped.transaction_ref.is_empty() {
+                self.transaction_ref
+            } else {
+                scoped.transaction_ref
+            },
+            consumer_group: scoped.consumer_group.or(self.consumer_group),
+            request_timeout: scoped.request_timeout.or(self.request_timeout),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scoped_values_override_the_defaults() {
+        let defaults = CallContext::new()
+            .client_app_id("app")
+            .client_ref("default-ref")
+            .transaction_ref("default-tx")
+            .consumer_group("default-group")
+            .request_timeout(Duration::from_secs(10));
+
+        let scoped = CallContext::new()
+            .client_ref("scoped-ref")
+            .transaction_ref("scoped-tx-1")
+            .transaction_ref("scoped-tx-2")
+            .request_timeout(Duration::from_secs(1));
+
+        assert_eq!(
+            defaults.merge(scoped),
+            CallContext::new()
+                .client_app_id("app")
+                .client_ref("scoped-ref")
+                .transaction_ref("scoped-tx-1")
+                .transaction_ref("scoped-tx-2")
+                .consumer_group("default-group")
+                .request_timeout(Duration::from_secs(1))
+        );
+    }
+
+    #[test]
+    fn an_empty_scope_keeps_the_defaults() {
+        let defaults = CallContext::new()
+            .client_app_id("app")
+            .transaction_ref("default-tx");
+
+        assert_eq!(defaults.clone().merge(CallContext::new()), defaults);
+    }
+
+    #[test]
+    fn builds_the_headers_from_the_defaults() {
+        let headers = CallContext::headers(
+            CallContext::new()
+                .client_app_id("app")
+                .client_ref("ref")
+                .consumer_group("group"),
+        );
+
+        assert_eq!(headers.client_app_id, "app");
+        assert_eq!(headers.client_ref, "ref");
+        assert_eq!(headers.transaction_ref, vec!["app".to_string()]);
+        assert_eq!(
+            headers.consumer_group,
+            Some(StringValue {
+                value: "group".to_string()
+            })
+        );
+        assert_eq!(headers.request_timeout, None);
+    }
+
+    #[test]
+    fn generates_a_client_app_id_when_none_is_given() {
+        let headers = CallContext::headers(CallContext::new());
+
+        assert!(!headers.client_app_id.is_empty());
+        assert_eq!(headers.transaction_ref, vec![headers.client_app_id.clone()]);
+    }
+
+    #[tokio::test]
+    async fn builds_the_headers_from_the_current_scope() {
+        let defaults = CallContext::new()
+            .client_app_id("app")
+            .transaction_ref("default-tx");
+
+        let headers = CallContext::new()
+            .transaction_ref("scoped-tx")
+            .request_timeout(Duration::from_secs(60))
+            .scope(async { CallContext::headers(defaults) })
+            .await;
+
+        assert_eq!(headers.client_app_id, "app");
+        assert_eq!(headers.transaction_ref, vec!["scoped-tx".to_string()]);
+
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        let deadline = headers.request_timeout.unwrap().seconds;
+        assert!(deadline > now && deadline <= now + 60);
+    }
+
+    #[tokio::test]
+    async fn scoped_request_timeout_overrides_the_default_one() {
+        let defaults = CallContext::new().request_timeout(Duration::from_secs(10));
+
+        assert_eq!(
+            CallContext::current_request_timeout(&defaults),
+            Some(Duration::from_secs(10))
+        );
+
+        let scoped = CallContext::new()
+            .request_timeout(Duration::from_secs(1))
+            .scope(async { CallContext::current_request_timeout(&defaults) })
+            .await;
+        assert_eq!(scoped, Some(Duration::from_secs(1)));
+
+        let unset = CallContext::new()
+            .client_ref("ref")
+            .scope(async { CallContext::current_request_timeout(&defaults) })
+            .await;
+        assert_eq!(unset, Some(Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn has_no_scope_outside_of_scope() {
+        assert_eq!(CallContext::current(), None);
+    }
+}
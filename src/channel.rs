@@ -1,42 +1,848 @@
+use std::collections::BTreeSet;
 use std::sync::Arc;
 use std::time::Duration;
 
+#[cfg(feature = "tls-custom-roots")]
+use anyhow::{bail, Context};
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+#[cfg(feature = "tls-custom-roots")]
+use tokio_rustls::rustls;
+#[cfg(feature = "gzip")]
+use tonic::codec::CompressionEncoding;
+#[cfg(feature = "tls-custom-roots")]
+use tonic::transport::{Certificate, ClientTlsConfig, Identity};
 use tonic::transport::{Channel, Endpoint};
+use tonic::Code;
+use tonic_health::pb::health_check_response::ServingStatus;
+use tonic_health::pb::health_client::HealthClient;
+use tonic_health::pb::HealthCheckRequest;
+use tower::discover::Change;
+
+use crate::client::iotics::api::feed_api_client::FeedApiClient;
+use crate::client::iotics::api::host_api_client::HostApiClient;
+use crate::client::iotics::api::input_api_client::InputApiClient;
+use crate::client::iotics::api::interest_api_client::InterestApiClient;
+use crate::client::iotics::api::search_api_client::SearchApiClient;
+use crate::client::iotics::api::twin_api_client::TwinApiClient;
 
 use crate::auth_builder::IntoAuthBuilder;
 
+/// Configuration of the channels created by [`create_channel`], and of the clients created on them.
+///
+/// ```ignore
+/// let options = ChannelOptions::new()
+///     .connect_timeout(Duration::from_secs(5))
+///     .timeout(Duration::from_secs(30))
+///     .max_decoding_message_size(16 * 1024 * 1024)
+///     .lazy(true);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ChannelOptions {
+    concurrency_limit: Option<usize>,
+    rate_limit: Option<(u64, Duration)>,
+    keep_alive_interval: Option<Duration>,
+    keep_alive_timeout: Option<Duration>,
+    keep_alive_while_idle: bool,
+    connect_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    tcp_keepalive: Option<Duration>,
+    initial_stream_window_size: Option<u32>,
+    initial_connection_window_size: Option<u32>,
+    lazy: bool,
+    #[cfg(feature = "gzip")]
+    gzip: bool,
+    max_decoding_message_size: Option<usize>,
+    max_encoding_message_size: Option<usize>,
+    #[cfg(feature = "tls-custom-roots")]
+    tls: Option<TlsOptions>,
+}
+
+impl ChannelOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The maximum number of concurrent requests that can be made on the channel
+    pub fn concurrency_limit(mut self, limit: usize) -> Self {
+        self.concurrency_limit = Some(limit);
+        self
+    }
+
+    /// The maximum number of requests that can be made over a specific interval
+    pub fn rate_limit(mut self, limit: u64, duration: Duration) -> Self {
+        self.rate_limit = Some((limit, duration));
+        self
+    }
+
+    /// The interval at which HTTP/2 keep alive pings are sent
+    pub fn keep_alive_interval(mut self, interval: Duration) -> Self {
+        self.keep_alive_interval = Some(interval);
+        self
+    }
+
+    /// The time waited for the acknowledgement of a keep alive ping before closing the connection
+    pub fn keep_alive_timeout(mut self, timeout: Duration) -> Self {
+        self.keep_alive_timeout = Some(timeout);
+        self
+    }
+
+    /// Send the keep alive pings even when there is no request in flight
+    pub fn keep_alive_while_idle(mut self, enabled: bool) -> Self {
+        self.keep_alive_while_idle = enabled;
+        self
+    }
+
+    /// The maximum time spent connecting to the host
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// The maximum duration of each request, including the streams of `follow`, `search` and
+    /// `receive_input_messages`, so it should be left unset for long running streams
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// The interval of the TCP keepalive probes
+    pub fn tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.tcp_keepalive = Some(interval);
+        self
+    }
+
+    /// The HTTP/2 window size of each stream
+    pub fn initial_stream_window_size(mut self, size: u32) -> Self {
+        self.initial_stream_window_size = Some(size);
+        self
+    }
+
+    /// The HTTP/2 window size of the connection
+    pub fn initial_connection_window_size(mut self, size: u32) -> Self {
+        self.initial_connection_window_size = Some(size);
+        self
+    }
+
+    /// Connect on the first request instead of in [`create_channel`], so that services can start
+    /// while the host is down. With several hosts, see [`create_channel`] and
+    /// [`create_balanced_channel`]
+    pub fn lazy(mut self, enabled: bool) -> Self {
+        self.lazy = enabled;
+        self
+    }
+
+    /// Compress the requests and accept compressed responses with gzip
+    #[cfg(feature = "gzip")]
+    pub fn gzip(mut self, enabled: bool) -> Self {
+        self.gzip = enabled;
+        self
+    }
+
+    /// The maximum size of a decoded response, 4 MB by default, large describe responses or
+    /// search pages can exceed it
+    pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+        self.max_decoding_message_size = Some(limit);
+        self
+    }
+
+    /// The maximum size of an encoded request
+    pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+        self.max_encoding_message_size = Some(limit);
+        self
+    }
+
+    /// TLS configuration of `https` hosts, only the roots enabled by the `tls` features are trusted by default
+    #[cfg(feature = "tls-custom-roots")]
+    pub fn tls(mut self, tls: TlsOptions) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+}
+
+/// Apply the client level [`ChannelOptions`], which tonic configures per client rather than per channel.
+pub(crate) trait ConfigureClient {
+    fn configure(self, options: &ChannelOptions) -> Self;
+}
+
+macro_rules! impl_configure_client {
+    ($($client:ident),*) => {
+        $(
+            impl ConfigureClient for $client<Channel> {
+                fn configure(mut self, options: &ChannelOptions) -> Self {
+                    #[cfg(feature = "gzip")]
+                    if options.gzip {
+                        self = self
+                            .send_compressed(CompressionEncoding::Gzip)
+                            .accept_compressed(CompressionEncoding::Gzip);
+                    }
+
+                    if let Some(limit) = options.max_decoding_message_size {
+                        self = self.max_decoding_message_size(limit);
+                    }
+
+                    if let Some(limit) = options.max_encoding_message_size {
+                        self = self.max_encoding_message_size(limit);
+                    }
+
+                    self
+                }
+            }
+        )*
+    };
+}
+
+impl_configure_client!(
+    FeedApiClient,
+    HostApiClient,
+    InputApiClient,
+    InterestApiClient,
+    SearchApiClient,
+    TwinApiClient
+);
+
+/// A client certificate and its private key, both PEM encoded, used for mutual TLS.
+#[cfg(feature = "tls-custom-roots")]
+#[derive(Clone)]
+pub struct ClientIdentity {
+    pub certificate_pem: Vec<u8>,
+    pub key_pem: Vec<u8>,
+}
+
+#[cfg(feature = "tls-custom-roots")]
+impl std::fmt::Debug for ClientIdentity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // don't leak the private key in logs
+        f.debug_struct("ClientIdentity")
+            .field("certificate_pem", &self.certificate_pem.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The certificate authorities trusted by a TLS channel, see [`TlsOptions::roots`].
+///
+/// Tonic always trusts the roots enabled by the cargo features, `WebPki` and `Native` check that
+/// theirs is enabled: `WebPki` with the `tls` feature and `Native` with the `tls-native-roots`
+/// feature. `Custom` only trusts [`TlsOptions::ca_certificate_pem`], whatever the features.
+#[cfg(feature = "tls-custom-roots")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TlsRoots {
+    /// The Mozilla roots bundled in the binary.
+    WebPki,
+    /// The roots of the operating system certificate store.
+    Native,
+    /// Only [`TlsOptions::ca_certificate_pem`], e.g. a private CA.
+    Custom,
+}
+
+#[cfg(feature = "tls-custom-roots")]
+impl Default for TlsRoots {
+    /// The roots enabled by the cargo features, `Custom` when none is.
+    fn default() -> Self {
+        if cfg!(feature = "tls") {
+            Self::WebPki
+        } else if cfg!(feature = "tls-native-roots") {
+            Self::Native
+        } else {
+            Self::Custom
+        }
+    }
+}
+
+/// TLS configuration of the channels, see [`ChannelOptions::tls`].
+///
+/// The host certificates are checked against the selected `roots` and `ca_certificate_pem`.
+#[cfg(feature = "tls-custom-roots")]
+#[derive(Debug, Clone, Default)]
+pub struct TlsOptions {
+    /// The roots trusted with the CA certificate, the ones enabled by the cargo features by default.
+    pub roots: TlsRoots,
+    /// A PEM encoded certificate authority trusted in addition to the roots, e.g. a private CA.
+    pub ca_certificate_pem: Option<Vec<u8>>,
+    /// The client certificate presented to hosts requiring mutual TLS.
+    pub client_identity: Option<ClientIdentity>,
+    /// The name checked against the host certificate and sent as SNI, the host of the address by default.
+    pub domain_name: Option<String>,
+}
+
+#[cfg(feature = "tls-custom-roots")]
+impl TlsOptions {
+    fn to_client_tls_config(&self) -> Result<ClientTlsConfig, anyhow::Error> {
+        let mut tls_config = ClientTlsConfig::new();
+
+        match self.roots {
+            TlsRoots::WebPki if !cfg!(feature = "tls") => {
+                bail!("the webpki TLS roots require the `tls` feature")
+            }
+            TlsRoots::Native if !cfg!(feature = "tls-native-roots") => {
+                bail!("the native TLS roots require the `tls-native-roots` feature")
+            }
+            TlsRoots::Custom => {
+                // tonic adds the roots of the features to its own rustls configuration, only a
+                // configuration built here leaves them out
+                tls_config = tls_config.rustls_client_config(self.to_custom_rustls_config()?);
+            }
+            TlsRoots::WebPki | TlsRoots::Native => {
+                if let Some(ca_certificate_pem) = &self.ca_certificate_pem {
+                    tls_config =
+                        tls_config.ca_certificate(Certificate::from_pem(ca_certificate_pem));
+                }
+
+                if let Some(identity) = &self.client_identity {
+                    tls_config = tls_config.identity(Identity::from_pem(
+                        &identity.certificate_pem,
+                        &identity.key_pem,
+                    ));
+                }
+            }
+        }
+
+        if let Some(domain_name) = &self.domain_name {
+            tls_config = tls_config.domain_name(domain_name);
+        }
+
+        Ok(tls_config)
+    }
+
+    fn to_custom_rustls_config(&self) -> Result<rustls::ClientConfig, anyhow::Error> {
+        let ca_certificate_pem = self
+            .ca_certificate_pem
+            .as_ref()
+            .context("the custom TLS roots require a CA certificate")?;
+
+        let mut roots = rustls::RootCertStore::empty();
+        for certificate in read_certificates(ca_certificate_pem)? {
+            roots.add(&certificate).context("invalid CA certificate")?;
+        }
+
+        let builder = rustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots);
+
+        let config = match &self.client_identity {
+            Some(identity) => builder
+                .with_client_auth_cert(
+                    read_certificates(&identity.certificate_pem)?,
+                    read_private_key(&identity.key_pem)?,
+                )
+                .context("invalid client identity")?,
+            None => builder.with_no_client_auth(),
+        };
+
+        Ok(config)
+    }
+}
+
+#[cfg(feature = "tls-custom-roots")]
+fn read_certificates(pem: &[u8]) -> Result<Vec<rustls::Certificate>, anyhow::Error> {
+    let certificates = rustls_pemfile::certs(&mut &pem[..]).context("invalid PEM certificate")?;
+
+    if certificates.is_empty() {
+        bail!("no certificate found in the PEM");
+    }
+
+    Ok(certificates.into_iter().map(rustls::Certificate).collect())
+}
+
+#[cfg(feature = "tls-custom-roots")]
+fn read_private_key(pem: &[u8]) -> Result<rustls::PrivateKey, anyhow::Error> {
+    use rustls_pemfile::Item;
+
+    rustls_pemfile::read_all(&mut &pem[..])
+        .context("invalid PEM private key")?
+        .into_iter()
+        .find_map(|item| match item {
+            Item::RSAKey(key) | Item::PKCS8Key(key) | Item::ECKey(key) => Some(key),
+            _ => None,
+        })
+        .map(rustls::PrivateKey)
+        .context("no private key found in the PEM")
+}
+
 /// Create a [`tonic::transport::Channel`] that can be re-used between multiple calls.
 /// It provides a `Clone` implementation that is _cheap_.
 ///
+/// When [`ClientConfig::hosts`](crate::ClientConfig::hosts) lists several hosts, the requests are
+/// balanced over all of them. Unless [`ChannelOptions::lazy`] is set, it fails when none of them
+/// can be connected to, the connections used by the requests are still opened on demand. Use
+/// [`create_balanced_channel`] to stop sending requests to the hosts that are down.
+///
 /// # Arguments
 ///
 /// - `auth_builder` - [`crate::IntoAuthBuilder`] implementation that provides the authentication
-/// - `concurrency_limit` - The maximum number of concurrent requests that can be made on the channel
-/// - `rate_limit` - The maximum number of requests that can be made over a specific interval
-/// - `keep_alive_interval` - The interval at which keep alive pings are sent
+/// - `options` - [`ChannelOptions`] of the channel, usually `auth_builder.get_config().channel_options`
 pub async fn create_channel(
     auth_builder: Arc<impl IntoAuthBuilder>,
-    concurrency_limit: Option<usize>,
-    rate_limit: Option<(u64, Duration)>,
-    keep_alive_interval: Option<Duration>,
+    options: ChannelOptions,
 ) -> Result<Channel, anyhow::Error> {
-    let host_address = auth_builder.get_host()?;
+    let hosts = get_hosts(auth_builder.as_ref())?;
+
+    let endpoint = match hosts.as_slice() {
+        [] => return Err(anyhow::anyhow!("no host address")),
+        [host_address] => create_endpoint(host_address, &options)?,
+        _ => {
+            let endpoints = hosts
+                .iter()
+                .map(|host_address| create_endpoint(host_address, &options))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            if !options.lazy {
+                connect_any(&endpoints).await?;
+            }
+
+            return Ok(Channel::balance_list(endpoints.into_iter()));
+        }
+    };
+
+    if options.lazy {
+        return Ok(endpoint.connect_lazy());
+    }
 
-    let mut endpoint = Endpoint::new(host_address)?;
+    let channel = endpoint.connect().await?;
 
-    if let Some(concurrency_limit) = concurrency_limit {
+    Ok(channel)
+}
+
+/// Check that at least one of `endpoints` can be connected to.
+async fn connect_any(endpoints: &[Endpoint]) -> Result<(), anyhow::Error> {
+    let mut last_error = None;
+
+    for endpoint in endpoints {
+        match endpoint.connect().await {
+            Ok(_) => return Ok(()),
+            Err(e) => last_error = Some(e),
+        }
+    }
+
+    match last_error {
+        Some(e) => Err(anyhow::Error::new(e).context("none of the hosts is reachable")),
+        None => Err(anyhow::anyhow!("no host address")),
+    }
+}
+
+/// A channel balancing the requests over the reachable hosts, see [`create_balanced_channel`].
+///
+/// The hosts are no longer probed once it's dropped, the channel and its clones keep working
+/// with the hosts that were reachable.
+pub struct BalancedChannel {
+    channel: Channel,
+    available_hosts: watch::Receiver<Vec<String>>,
+    probe: JoinHandle<()>,
+}
+
+impl BalancedChannel {
+    pub fn channel(&self) -> Channel {
+        self.channel.clone()
+    }
+
+    /// The hosts receiving requests, updated after each probe.
+    pub fn available_hosts(&self) -> watch::Receiver<Vec<String>> {
+        self.available_hosts.clone()
+    }
+}
+
+impl Drop for BalancedChannel {
+    fn drop(&mut self) {
+        self.probe.abort();
+    }
+}
+
+/// Create a channel balancing the requests over all the hosts of
+/// [`ClientConfig::hosts`](crate::ClientConfig::hosts).
+///
+/// The hosts are probed every `probe_interval` by connecting to them and calling their
+/// `grpc.health.v1` service: the unreachable and not serving ones are removed from the channel and
+/// added back once they're serving again, so that the requests fail over to the other hosts. A
+/// host that doesn't implement `grpc.health.v1` or rejects the unauthenticated check is considered
+/// serving once connected.
+///
+/// Unless [`ChannelOptions::lazy`] is set, the hosts are probed before returning and it fails
+/// when none is serving. When it's set, all the hosts receive requests until the first probe,
+/// which runs in the background.
+pub async fn create_balanced_channel(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    options: ChannelOptions,
+    probe_interval: Duration,
+) -> Result<BalancedChannel, anyhow::Error> {
+    let hosts = get_hosts(auth_builder.as_ref())?;
+
+    if hosts.is_empty() {
+        return Err(anyhow::anyhow!("no host address"));
+    }
+
+    let endpoints = hosts
+        .into_iter()
+        .map(|host_address| {
+            let endpoint = create_endpoint(&host_address, &options)?;
+            Ok((host_address, endpoint))
+        })
+        .collect::<Result<Vec<_>, anyhow::Error>>()?;
+
+    let (channel, changes) = Channel::balance_channel(endpoints.len());
+
+    let mut probe = EndpointsProbe {
+        endpoints,
+        changes,
+        available: BTreeSet::new(),
+        timeout: options.connect_timeout.unwrap_or(DEFAULT_PROBE_TIMEOUT),
+    };
+
+    let lazy = options.lazy;
+
+    if lazy {
+        probe.insert_all().await;
+    } else {
+        probe.run().await;
+
+        if probe.available.is_empty() {
+            return Err(anyhow::anyhow!("none of the hosts is reachable"));
+        }
+    }
+
+    let (available_tx, available_hosts) = watch::channel(probe.available_hosts());
+
+    let probe = tokio::spawn(async move {
+        let mut interval = tokio::time::interval(probe_interval);
+
+        if !lazy {
+            // the first tick completes immediately and the hosts have just been probed
+            interval.tick().await;
+        }
+
+        loop {
+            interval.tick().await;
+
+            if !probe.run().await {
+                return;
+            }
+
+            available_tx.send_replace(probe.available_hosts());
+        }
+    });
+
+    Ok(BalancedChannel {
+        channel,
+        available_hosts,
+        probe,
+    })
+}
+
+/// The replicas of the client configuration, or the host of the auth builder when there's none.
+fn get_hosts(auth_builder: &impl IntoAuthBuilder) -> Result<Vec<String>, anyhow::Error> {
+    let hosts = auth_builder.get_config().hosts;
+
+    if hosts.is_empty() {
+        return Ok(vec![auth_builder.get_host()?]);
+    }
+
+    Ok(hosts)
+}
+
+const DEFAULT_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+struct EndpointsProbe {
+    endpoints: Vec<(String, Endpoint)>,
+    changes: mpsc::Sender<Change<String, Endpoint>>,
+    available: BTreeSet<String>,
+    timeout: Duration,
+}
+
+impl EndpointsProbe {
+    /// Send requests to all the hosts until they're probed.
+    async fn insert_all(&mut self) {
+        for (host_address, endpoint) in &self.endpoints {
+            self.available.insert(host_address.clone());
+
+            let change = Change::Insert(host_address.clone(), endpoint.clone());
+
+            if self.changes.send(change).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Probe every host and update the endpoints of the channel, returns `false` once the
+    /// channel has been dropped.
+    async fn run(&mut self) -> bool {
+        for (host_address, endpoint) in &self.endpoints {
+            let reachable = tokio::time::timeout(self.timeout, is_serving(endpoint))
+                .await
+                .unwrap_or(false);
+
+            let change = if reachable && !self.available.contains(host_address) {
+                self.available.insert(host_address.clone());
+                Change::Insert(host_address.clone(), endpoint.clone())
+            } else if !reachable && self.available.remove(host_address) {
+                Change::Remove(host_address.clone())
+            } else {
+                continue;
+            };
+
+            if self.changes.send(change).await.is_err() {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn available_hosts(&self) -> Vec<String> {
+        self.available.iter().cloned().collect()
+    }
+}
+
+/// Whether the host can be connected to and its `grpc.health.v1` service doesn't report it as not
+/// serving.
+async fn is_serving(endpoint: &Endpoint) -> bool {
+    let channel = match endpoint.connect().await {
+        Ok(channel) => channel,
+        Err(_) => return false,
+    };
+
+    let response = HealthClient::new(channel)
+        .check(HealthCheckRequest {
+            service: String::new(),
+        })
+        .await;
+
+    match response {
+        Ok(response) => response.into_inner().status != ServingStatus::NotServing as i32,
+        // the host answered, e.g. with `Unimplemented` or `Unauthenticated`
+        Err(status) => !matches!(
+            status.code(),
+            Code::Unavailable | Code::DeadlineExceeded | Code::Cancelled
+        ),
+    }
+}
+
+fn create_endpoint(
+    host_address: &str,
+    options: &ChannelOptions,
+) -> Result<Endpoint, anyhow::Error> {
+    let mut endpoint = Endpoint::new(host_address.to_string())?
+        .keep_alive_while_idle(options.keep_alive_while_idle)
+        .tcp_keepalive(options.tcp_keepalive)
+        .initial_stream_window_size(options.initial_stream_window_size)
+        .initial_connection_window_size(options.initial_connection_window_size);
+
+    if let Some(concurrency_limit) = options.concurrency_limit {
         endpoint = endpoint.concurrency_limit(concurrency_limit);
     }
 
-    if let Some((limit, duration)) = rate_limit {
+    if let Some((limit, duration)) = options.rate_limit {
         endpoint = endpoint.rate_limit(limit, duration);
     }
 
-    if let Some(keep_alive_interval) = keep_alive_interval {
+    if let Some(keep_alive_interval) = options.keep_alive_interval {
         endpoint = endpoint.http2_keep_alive_interval(keep_alive_interval);
     }
 
-    let channel = endpoint.connect().await?;
+    if let Some(keep_alive_timeout) = options.keep_alive_timeout {
+        endpoint = endpoint.keep_alive_timeout(keep_alive_timeout);
+    }
 
-    Ok(channel)
+    if let Some(connect_timeout) = options.connect_timeout {
+        endpoint = endpoint.connect_timeout(connect_timeout);
+    }
+
+    if let Some(timeout) = options.timeout {
+        endpoint = endpoint.timeout(timeout);
+    }
+
+    #[cfg(feature = "tls-custom-roots")]
+    if let Some(tls) = &options.tls {
+        endpoint = endpoint.tls_config(tls.to_client_tls_config()?)?;
+    }
+
+    Ok(endpoint)
+}
+
+#[cfg(all(test, feature = "tls-custom-roots"))]
+mod tests {
+    use super::*;
+    use tonic::transport::{Server, ServerTlsConfig};
+
+    // generated by `tests/fixtures/tls/generate.sh`
+    const CA_PEM: &[u8] = include_bytes!("../tests/fixtures/tls/ca.pem");
+    const HOST_PEM: &[u8] = include_bytes!("../tests/fixtures/tls/host.pem");
+    const HOST_KEY: &[u8] = include_bytes!("../tests/fixtures/tls/host.key");
+    const CLIENT_PEM: &[u8] = include_bytes!("../tests/fixtures/tls/client.pem");
+    const CLIENT_KEY: &[u8] = include_bytes!("../tests/fixtures/tls/client.key");
+
+    /// Serve `grpc.health.v1` over TLS with a certificate of the private CA, requiring a client
+    /// certificate of the same CA, and return the address of the host.
+    async fn serve_mutual_tls() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let incoming = futures::stream::unfold(listener, |listener| async move {
+            let stream = listener.accept().await.map(|(stream, _)| stream);
+            Some((stream, listener))
+        });
+
+        let (_, health_service) = tonic_health::server::health_reporter();
+
+        let tls = ServerTlsConfig::new()
+            .identity(Identity::from_pem(HOST_PEM, HOST_KEY))
+            .client_ca_root(Certificate::from_pem(CA_PEM));
+
+        let server = Server::builder()
+            .tls_config(tls)
+            .unwrap()
+            .add_service(health_service)
+            .serve_with_incoming(incoming);
+
+        tokio::spawn(server);
+
+        format!("https://localhost:{}", port)
+    }
+
+    async fn check(host_address: &str, tls: TlsOptions) -> Result<i32, anyhow::Error> {
+        let options = ChannelOptions::new()
+            .connect_timeout(Duration::from_secs(5))
+            .tls(tls);
+
+        let channel = create_endpoint(host_address, &options)?.connect().await?;
+
+        let response = HealthClient::new(channel)
+            .check(HealthCheckRequest {
+                service: String::new(),
+            })
+            .await?;
+
+        Ok(response.into_inner().status)
+    }
+
+    fn client_identity() -> Option<ClientIdentity> {
+        Some(ClientIdentity {
+            certificate_pem: CLIENT_PEM.to_vec(),
+            key_pem: CLIENT_KEY.to_vec(),
+        })
+    }
+
+    #[tokio::test]
+    async fn connects_to_a_host_of_a_private_ca_with_mutual_tls() {
+        let host_address = serve_mutual_tls().await;
+
+        let tls = TlsOptions {
+            roots: TlsRoots::Custom,
+            ca_certificate_pem: Some(CA_PEM.to_vec()),
+            client_identity: client_identity(),
+            domain_name: None,
+        };
+
+        let status = check(&host_address, tls).await.unwrap();
+
+        assert_eq!(status, ServingStatus::Serving as i32);
+    }
+
+    #[cfg(feature = "tls")]
+    #[tokio::test]
+    async fn connects_to_a_host_of_a_private_ca_on_top_of_the_webpki_roots() {
+        let host_address = serve_mutual_tls().await;
+
+        let tls = TlsOptions {
+            roots: TlsRoots::WebPki,
+            ca_certificate_pem: Some(CA_PEM.to_vec()),
+            client_identity: client_identity(),
+            domain_name: None,
+        };
+
+        let status = check(&host_address, tls).await.unwrap();
+
+        assert_eq!(status, ServingStatus::Serving as i32);
+    }
+
+    #[tokio::test]
+    async fn fails_without_the_client_certificate() {
+        let host_address = serve_mutual_tls().await;
+
+        let tls = TlsOptions {
+            roots: TlsRoots::Custom,
+            ca_certificate_pem: Some(CA_PEM.to_vec()),
+            client_identity: None,
+            domain_name: None,
+        };
+
+        assert!(check(&host_address, tls).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn fails_without_the_private_ca() {
+        let host_address = serve_mutual_tls().await;
+
+        let tls = TlsOptions {
+            ca_certificate_pem: None,
+            client_identity: client_identity(),
+            ..Default::default()
+        };
+
+        assert!(check(&host_address, tls).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn checks_the_domain_name() {
+        let host_address = serve_mutual_tls().await;
+
+        let tls = TlsOptions {
+            roots: TlsRoots::Custom,
+            ca_certificate_pem: Some(CA_PEM.to_vec()),
+            client_identity: client_identity(),
+            domain_name: Some("iotics.example".to_string()),
+        };
+
+        assert!(check(&host_address, tls).await.is_err());
+    }
+
+    #[test]
+    fn requires_a_ca_certificate_for_the_custom_roots() {
+        let tls = TlsOptions {
+            roots: TlsRoots::Custom,
+            ..Default::default()
+        };
+
+        assert!(tls.to_client_tls_config().is_err());
+    }
+
+    #[test]
+    fn rejects_an_invalid_ca_certificate_for_the_custom_roots() {
+        let tls = TlsOptions {
+            roots: TlsRoots::Custom,
+            ca_certificate_pem: Some(b"not a certificate".to_vec()),
+            ..Default::default()
+        };
+
+        assert!(tls.to_client_tls_config().is_err());
+    }
+
+    #[test]
+    fn requires_the_feature_of_the_selected_roots() {
+        let webpki = TlsOptions {
+            roots: TlsRoots::WebPki,
+            ..Default::default()
+        };
+        let native = TlsOptions {
+            roots: TlsRoots::Native,
+            ..Default::default()
+        };
+
+        assert_eq!(webpki.to_client_tls_config().is_ok(), cfg!(feature = "tls"));
+        assert_eq!(
+            native.to_client_tls_config().is_ok(),
+            cfg!(feature = "tls-native-roots")
+        );
+    }
+
+    #[test]
+    fn defaults_to_the_roots_of_the_features() {
+        let expected = if cfg!(feature = "tls") {
+            TlsRoots::WebPki
+        } else if cfg!(feature = "tls-native-roots") {
+            TlsRoots::Native
+        } else {
+            TlsRoots::Custom
+        };
+
+        assert_eq!(TlsRoots::default(), expected);
+    }
 }
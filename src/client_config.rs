@@ -0,0 +1,30 @@
+use std::sync::Arc;
+
+use crate::call_context::CallContext;
+use crate::channel::ChannelOptions;
+use crate::properties::validation::PropertyValidator;
+
+/// Configuration of a client, returned by [`crate::IntoAuthBuilder::get_config`].
+///
+/// ```ignore
+/// let config = ClientConfig {
+///     channel_options: ChannelOptions::new().connect_timeout(Duration::from_secs(5)),
+///     call_context: CallContext::new().client_app_id("my-connector"),
+///     property_validator: Some(Arc::new(PropertyValidator::default())),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ClientConfig {
+    /// The addresses of all the replicas of the host, the requests are balanced over them.
+    /// Only [`crate::IntoAuthBuilder::get_host`] when empty.
+    pub hosts: Vec<String>,
+    /// The options of the channels created by the functions that don't take a channel, and of the
+    /// clients created on any channel.
+    pub channel_options: ChannelOptions,
+    /// The defaults of the `Headers` sent with the requests.
+    pub call_context: CallContext,
+    /// The validator applied to the properties sent by every function creating or updating twins
+    /// and feeds. No validation is done when it's `None`.
+    pub property_validator: Option<Arc<PropertyValidator>>,
+}
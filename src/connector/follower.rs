@@ -0,0 +1,692 @@
+use anyhow::Context;
+use futures::future::{BoxFuture, FutureExt};
+use serde::de::DeserializeOwned;
+use serde_json::Value as JsonValue;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tokio::sync::{watch, Semaphore};
+use tokio::task::JoinHandle;
+use tonic::transport::Channel;
+
+use crate::client::iotics::api::{FeedData, Scope};
+
+use crate::auth_builder::IntoAuthBuilder;
+use crate::channel::create_channel;
+use crate::connector::{shutdown_signal, ConnectorHealth, HealthReporter};
+use crate::helpers::Backoff;
+use crate::ids::{FeedRef, TwinDid};
+use crate::interest::{follow_with_channel, FetchInterestResponse};
+use crate::metric_names;
+use crate::search::{search_with_channel, Filter};
+use crate::telemetry::{increment, warn};
+use crate::twin::bulk::UpsertTwinSpec;
+use crate::twin::crud::delete_twin_with_channel;
+use crate::twin::upsert::upsert_twin_with_channel;
+
+/// A sample received from a followed feed.
+#[derive(Debug, Clone)]
+pub struct FollowedSample {
+    pub feed: FeedRef,
+    pub occurred_at: Option<SystemTime>,
+    pub mime: String,
+    pub data: Vec<u8>,
+}
+
+impl FollowedSample {
+    /// Decode the JSON data of the sample.
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        serde_json::from_slice(&self.data)
+    }
+
+    fn from_feed_data(feed: FeedRef, feed_data: FeedData) -> Self {
+        let occurred_at = feed_data.occurred_at.map(|timestamp| {
+            SystemTime::UNIX_EPOCH
+                + Duration::new(
+                    timestamp.seconds.max(0) as u64,
+                    timestamp.nanos.max(0) as u32,
+                )
+        });
+
+        Self {
+            feed,
+            occurred_at,
+            mime: feed_data.mime,
+            data: feed_data.data,
+        }
+    }
+}
+
+type Handler =
+    Arc<dyn Fn(FollowedSample) -> BoxFuture<'static, Result<(), anyhow::Error>> + Send + Sync>;
+
+enum Selector {
+    /// A feed followed directly.
+    Feed(FeedRef),
+    /// Any followed feed with this id.
+    FeedId(String),
+    /// Any followed feed whose JSON samples have this value label.
+    ValueLabel(String),
+    /// The feeds of the twins found by a search.
+    Search,
+}
+
+struct Route {
+    selector: Selector,
+    handler: Handler,
+}
+
+struct Search {
+    filter: Filter,
+    scope: Scope,
+    route: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FollowerConfig {
+    /// Ask for the last stored sample when a feed is followed for the first time.
+    pub fetch_last_stored: bool,
+    /// Time between two runs of the discovery searches.
+    pub discovery_interval: Duration,
+    /// Time given to a discovery search to collect the responses of the hosts.
+    pub search_timeout: Duration,
+    /// Time waited before following a feed again after its stream failed or ended, doubled after
+    /// each attempt without any sample up to `max_refollow_delay`.
+    pub refollow_delay: Duration,
+    /// Longest time waited between two attempts to follow a feed.
+    pub max_refollow_delay: Duration,
+    /// Maximum number of handlers running at the same time.
+    pub max_concurrent_handlers: usize,
+    /// Delete the follower twin when the runtime stops.
+    pub delete_twin_on_shutdown: bool,
+}
+
+impl Default for FollowerConfig {
+    fn default() -> Self {
+        Self {
+            fetch_last_stored: true,
+            discovery_interval: Duration::from_secs(60),
+            search_timeout: Duration::from_secs(10),
+            refollow_delay: Duration::from_secs(5),
+            max_refollow_delay: Duration::from_secs(300),
+            max_concurrent_handlers: 16,
+            delete_twin_on_shutdown: false,
+        }
+    }
+}
+
+/// Runs a follower: upserts the follower twin, follows the registered feeds and the feeds of the
+/// twins found by the discovery searches, and dispatches their samples to the matching handlers.
+pub struct FollowerRuntime<A> {
+    auth_builder: Arc<A>,
+    follower: UpsertTwinSpec,
+    config: FollowerConfig,
+    routes: Vec<Route>,
+    searches: Vec<Search>,
+    health: Arc<HealthReporter>,
+}
+
+impl<A> FollowerRuntime<A>
+where
+    A: IntoAuthBuilder + Send + Sync + 'static,
+{
+    pub fn new(auth_builder: Arc<A>, follower: UpsertTwinSpec, config: FollowerConfig) -> Self {
+        Self {
+            auth_builder,
+            follower,
+            config,
+            routes: Vec::new(),
+            searches: Vec::new(),
+            health: Arc::new(HealthReporter::new()),
+        }
+    }
+
+    pub fn health(&self) -> watch::Receiver<ConnectorHealth> {
+        self.health.subscribe()
+    }
+
+    /// Follow `feed` and handle its samples.
+    pub fn on_feed<F, Fut>(mut self, feed: FeedRef, handler: F) -> Self
+    where
+        F: Fn(FollowedSample) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), anyhow::Error>> + Send + 'static,
+    {
+        self.add_route(Selector::Feed(feed), handler);
+        self
+    }
+
+    /// Handle the samples of all the followed feeds with the id `feed_id`.
+    pub fn on_feed_id<F, Fut>(mut self, feed_id: impl Into<String>, handler: F) -> Self
+    where
+        F: Fn(FollowedSample) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), anyhow::Error>> + Send + 'static,
+    {
+        self.add_route(Selector::FeedId(feed_id.into()), handler);
+        self
+    }
+
+    /// Handle the value labelled `label` of the JSON samples of all the followed feeds.
+    pub fn on_value<F, Fut>(mut self, label: impl Into<String>, handler: F) -> Self
+    where
+        F: Fn(FollowedSample, JsonValue) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), anyhow::Error>> + Send + 'static,
+    {
+        let label = label.into();
+        let route_label = label.clone();
+
+        self.add_route(
+            Selector::ValueLabel(route_label),
+            move |sample: FollowedSample| {
+                let value = sample
+                    .json::<JsonValue>()
+                    .ok()
+                    .and_then(|json| json.get(&label).cloned());
+
+                match value {
+                    Some(value) => handler(sample, value).boxed(),
+                    None => async { Ok(()) }.boxed(),
+                }
+            },
+        );
+        self
+    }
+
+    /// Periodically search for twins and handle the samples of all their feeds. The feeds that
+    /// aren't found anymore stop being followed, unless they're followed for another reason.
+    pub fn on_search<F, Fut>(mut self, filter: Filter, scope: Scope, handler: F) -> Self
+    where
+        F: Fn(FollowedSample) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), anyhow::Error>> + Send + 'static,
+    {
+        self.add_route(Selector::Search, handler);
+        self.searches.push(Search {
+            filter,
+            scope,
+            route: Some(self.routes.len() - 1),
+        });
+        self
+    }
+
+    /// Periodically search for twins and follow all their feeds, their samples are handled by the
+    /// feed id and value label handlers. The feeds that aren't found anymore stop being followed.
+    pub fn discover(mut self, filter: Filter, scope: Scope) -> Self {
+        self.searches.push(Search {
+            filter,
+            scope,
+            route: None,
+        });
+        self
+    }
+
+    /// Run until Ctrl+C or `SIGTERM`.
+    pub async fn run(self) -> Result<(), anyhow::Error> {
+        self.run_until(shutdown_signal()).await
+    }
+
+    /// Run until `shutdown` completes.
+    pub async fn run_until(self, shutdown: impl Future<Output = ()>) -> Result<(), anyhow::Error> {
+        let channel = create_channel(
+            self.auth_builder.clone(),
+            self.auth_builder.get_config().channel_options,
+        )
+        .await?;
+        self.run_with_channel_until(channel, shutdown).await
+    }
+
+    pub async fn run_with_channel_until(
+        self,
+        channel: Channel,
+        shutdown: impl Future<Output = ()>,
+    ) -> Result<(), anyhow::Error> {
+        let FollowerRuntime {
+            auth_builder,
+            follower,
+            config,
+            routes,
+            searches,
+            health,
+        } = self;
+
+        health.set(ConnectorHealth::Starting);
+
+        let follower_twin_id = follower.twin_id.clone();
+
+        let upserted = upsert_twin_with_channel(
+            auth_builder.clone(),
+            channel.clone(),
+            &follower.twin_id,
+            follower.properties,
+            follower.feeds,
+            follower.inputs,
+            follower.location,
+        )
+        .await
+        .context("upserting the follower twin failed");
+
+        if let Err(e) = upserted {
+            health.set(ConnectorHealth::Failed {
+                reason: format!("{:#}", e),
+            });
+            return Err(e);
+        }
+
+        let dispatcher = Arc::new(Dispatcher {
+            routes,
+            found_by: Mutex::new(HashMap::new()),
+            semaphore: Arc::new(Semaphore::new(config.max_concurrent_handlers.max(1))),
+            health: health.clone(),
+        });
+
+        let mut followed = Followed {
+            auth_builder: auth_builder.clone(),
+            channel: channel.clone(),
+            follower_twin_id: follower_twin_id.clone(),
+            config: config.clone(),
+            dispatcher: dispatcher.clone(),
+            direct: HashSet::new(),
+            discovered: HashMap::new(),
+            tasks: HashMap::new(),
+        };
+
+        let direct_feeds = dispatcher
+            .routes
+            .iter()
+            .filter_map(|route| match &route.selector {
+                Selector::Feed(feed) => Some(feed.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        for feed in direct_feeds {
+            followed.follow_directly(feed);
+        }
+
+        health.set(ConnectorHealth::Running);
+
+        tokio::pin!(shutdown);
+        let mut discovery = tokio::time::interval(config.discovery_interval);
+
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => break,
+                _ = discovery.tick(), if !searches.is_empty() => {
+                    // a round can take up to a search timeout per search, stop in the middle of it
+                    let round = discover_all(
+                        &auth_builder,
+                        &channel,
+                        &searches,
+                        &config,
+                        &mut followed,
+                        &health,
+                    );
+
+                    tokio::select! {
+                        _ = &mut shutdown => break,
+                        _ = round => {}
+                    }
+                }
+            }
+        }
+
+        health.set(ConnectorHealth::Stopping);
+
+        for task in followed.tasks.into_values() {
+            task.abort();
+        }
+
+        let result = if config.delete_twin_on_shutdown {
+            delete_twin_with_channel(auth_builder, channel, &follower_twin_id)
+                .await
+                .context("deleting the follower twin failed")
+        } else {
+            Ok(())
+        };
+
+        health.set(ConnectorHealth::Stopped);
+
+        result
+    }
+
+    fn add_route<F, Fut>(&mut self, selector: Selector, handler: F)
+    where
+        F: Fn(FollowedSample) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), anyhow::Error>> + Send + 'static,
+    {
+        self.routes.push(Route {
+            selector,
+            handler: Arc::new(move |sample| handler(sample).boxed()),
+        });
+    }
+}
+
+/// Run the discovery searches one after the other and update the followed feeds with the result
+/// of each of them.
+async fn discover_all<A>(
+    auth_builder: &Arc<A>,
+    channel: &Channel,
+    searches: &[Search],
+    config: &FollowerConfig,
+    followed: &mut Followed<A>,
+    health: &HealthReporter,
+) where
+    A: IntoAuthBuilder + Send + Sync + 'static,
+{
+    for (index, search) in searches.iter().enumerate() {
+        let part = format!("discovery search [{}]", index);
+
+        match discover_feeds(auth_builder, channel, search, config).await {
+            Ok(Discovered { feeds, error: None }) => {
+                followed.update_discovered(index, search.route, feeds);
+                health.recover(&part);
+            }
+            // the feeds missing from a partial result aren't unfollowed
+            Ok(Discovered {
+                feeds,
+                error: Some(e),
+            }) => {
+                followed.add_discovered(index, search.route, feeds);
+                health.fail(&part, &e);
+            }
+            Err(e) => health.fail(&part, &e),
+        }
+    }
+}
+
+/// The feeds found by a discovery search.
+struct Discovered {
+    feeds: HashSet<FeedRef>,
+    /// Set when the responses stopped with an error, `feeds` are the ones found until then.
+    error: Option<anyhow::Error>,
+}
+
+/// Run a discovery search and collect the feeds of the twins found, skipping the invalid feed ids.
+async fn discover_feeds(
+    auth_builder: &Arc<impl IntoAuthBuilder>,
+    channel: &Channel,
+    search: &Search,
+    config: &FollowerConfig,
+) -> Result<Discovered, anyhow::Error> {
+    let mut responses = search_with_channel(
+        auth_builder.clone(),
+        channel.clone(),
+        search.filter.clone(),
+        search.scope,
+        Some(config.search_timeout),
+    )
+    .await?;
+
+    let mut feeds = HashSet::new();
+
+    // the responses stop once the search times out
+    while let Some(response) = responses.recv().await {
+        let payload = match response {
+            Ok(response) => match response.payload {
+                Some(payload) => payload,
+                None => continue,
+            },
+            Err(e) => {
+                return Ok(Discovered {
+                    feeds,
+                    error: Some(e),
+                })
+            }
+        };
+
+        for twin in payload.twins {
+            for feed in twin.feeds {
+                if let Some(feed_id) = &feed.feed_id {
+                    match FeedRef::try_from(feed_id) {
+                        Ok(feed) => {
+                            feeds.insert(feed);
+                        }
+                        Err(e) => warn(&format_args!(
+                            "skipping a feed found by the discovery search: {:#}",
+                            e
+                        )),
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(Discovered { feeds, error: None })
+}
+
+struct Followed<A> {
+    auth_builder: Arc<A>,
+    channel: Channel,
+    follower_twin_id: TwinDid,
+    config: FollowerConfig,
+    dispatcher: Arc<Dispatcher>,
+    // the feeds registered with `on_feed`, never unfollowed
+    direct: HashSet<FeedRef>,
+    // the indexes of the discovery searches that found each feed in their last round
+    discovered: HashMap<FeedRef, HashSet<usize>>,
+    tasks: HashMap<FeedRef, JoinHandle<()>>,
+}
+
+impl<A> Followed<A>
+where
+    A: IntoAuthBuilder + Send + Sync + 'static,
+{
+    fn follow_directly(&mut self, feed: FeedRef) {
+        self.direct.insert(feed.clone());
+        self.ensure_followed(feed);
+    }
+
+    /// Follow the `feeds` found by the search `index`, and stop following the feeds it found
+    /// before that aren't found by any search anymore.
+    fn update_discovered(&mut self, index: usize, route: Option<usize>, feeds: HashSet<FeedRef>) {
+        let lost = self
+            .discovered
+            .iter()
+            .filter(|(feed, searches)| searches.contains(&index) && !feeds.contains(*feed))
+            .map(|(feed, _)| feed.clone())
+            .collect::<Vec<_>>();
+
+        for feed in lost {
+            if let Some(route) = route {
+                self.dispatcher.remove_found_by(&feed, route);
+            }
+
+            let searches = self.discovered.entry(feed.clone()).or_default();
+            searches.remove(&index);
+
+            if searches.is_empty() {
+                self.discovered.remove(&feed);
+
+                if !self.direct.contains(&feed) {
+                    if let Some(task) = self.tasks.remove(&feed) {
+                        task.abort();
+                    }
+                    self.dispatcher.forget(&feed);
+                }
+            }
+        }
+
+        self.add_discovered(index, route, feeds);
+    }
+
+    /// Follow the `feeds` found by the search `index`.
+    fn add_discovered(&mut self, index: usize, route: Option<usize>, feeds: HashSet<FeedRef>) {
+        for feed in feeds {
+            if let Some(route) = route {
+                self.dispatcher.add_found_by(&feed, route);
+            }
+
+            self.discovered
+                .entry(feed.clone())
+                .or_default()
+                .insert(index);
+
+            self.ensure_followed(feed);
+        }
+    }
+
+    /// Follow `feed` unless it's already followed.
+    fn ensure_followed(&mut self, feed: FeedRef) {
+        if self.tasks.contains_key(&feed) {
+            return;
+        }
+
+        let task = tokio::spawn(follow_feed(
+            self.auth_builder.clone(),
+            self.channel.clone(),
+            feed.clone(),
+            self.follower_twin_id.clone(),
+            self.config.clone(),
+            self.dispatcher.clone(),
+        ));
+
+        self.tasks.insert(feed, task);
+    }
+}
+
+/// Follow `feed` until the task is aborted, following it again with a growing delay when its
+/// stream fails or ends.
+async fn follow_feed<A>(
+    auth_builder: Arc<A>,
+    channel: Channel,
+    feed: FeedRef,
+    follower_twin_id: TwinDid,
+    config: FollowerConfig,
+    dispatcher: Arc<Dispatcher>,
+) where
+    A: IntoAuthBuilder + Send + Sync + 'static,
+{
+    let part = format!("feed [{}]", feed);
+    let mut fetch_last_stored = config.fetch_last_stored;
+    let mut backoff = Backoff::new(config.refollow_delay, config.max_refollow_delay);
+
+    loop {
+        let stream = follow_with_channel(
+            auth_builder.clone(),
+            channel.clone(),
+            &feed,
+            &follower_twin_id,
+            fetch_last_stored,
+        )
+        .await;
+
+        match stream {
+            Ok(mut stream) => {
+                dispatcher.health.recover(&part);
+
+                loop {
+                    match stream.message().await {
+                        Ok(Some(response)) => {
+                            backoff.reset();
+                            dispatcher.dispatch(&feed, response).await;
+                        }
+                        Ok(None) => {
+                            dispatcher
+                                .health
+                                .fail(&part, &anyhow::anyhow!("the stream ended"));
+                            break;
+                        }
+                        Err(e) => {
+                            dispatcher.health.fail(&part, &anyhow::Error::new(e));
+                            break;
+                        }
+                    }
+                }
+            }
+            Err(e) => dispatcher.health.fail(&part, &e),
+        }
+
+        // the samples shared in the meantime are lost, the last one would be a duplicate
+        fetch_last_stored = false;
+        tokio::time::sleep(backoff.next_delay()).await;
+
+        increment(metric_names::FOLLOW_RECONNECTS_TOTAL, 1);
+    }
+}
+
+struct Dispatcher {
+    routes: Vec<Route>,
+    // routes of the searches that found each feed
+    found_by: Mutex<HashMap<FeedRef, HashSet<usize>>>,
+    semaphore: Arc<Semaphore>,
+    health: Arc<HealthReporter>,
+}
+
+impl Dispatcher {
+    fn add_found_by(&self, feed: &FeedRef, route: usize) {
+        self.lock_found_by()
+            .entry(feed.clone())
+            .or_default()
+            .insert(route);
+    }
+
+    fn remove_found_by(&self, feed: &FeedRef, route: usize) {
+        if let Some(routes) = self.lock_found_by().get_mut(feed) {
+            routes.remove(&route);
+        }
+    }
+
+    /// Drop the state of a feed that isn't followed anymore.
+    fn forget(&self, feed: &FeedRef) {
+        self.lock_found_by().remove(feed);
+        self.health.recover(&format!("feed [{}]", feed));
+        self.health.recover(&format!("handler of feed [{}]", feed));
+    }
+
+    fn lock_found_by(&self) -> std::sync::MutexGuard<'_, HashMap<FeedRef, HashSet<usize>>> {
+        self.found_by
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Run the handlers matching the sample, waiting for a free slot when too many handlers are running.
+    async fn dispatch(&self, feed: &FeedRef, response: FetchInterestResponse) {
+        let feed_data = match response.payload.and_then(|payload| payload.feed_data) {
+            Some(feed_data) => feed_data,
+            None => return,
+        };
+
+        let sample = FollowedSample::from_feed_data(feed.clone(), feed_data);
+
+        let found_by = self.lock_found_by().get(feed).cloned().unwrap_or_default();
+
+        let json = sample.json::<JsonValue>().ok();
+
+        let handlers = self
+            .routes
+            .iter()
+            .enumerate()
+            .filter(|(index, route)| match &route.selector {
+                Selector::Feed(route_feed) => route_feed == feed,
+                Selector::FeedId(feed_id) => *feed_id == feed.feed,
+                Selector::ValueLabel(label) => json
+                    .as_ref()
+                    .map(|json| json.get(label).is_some())
+                    .unwrap_or(false),
+                Selector::Search => found_by.contains(index),
+            })
+            .map(|(_, route)| route.handler.clone())
+            .collect::<Vec<_>>();
+
+        for handler in handlers {
+            let permit = match self.semaphore.clone().acquire_owned().await {
+                Ok(permit) => permit,
+                Err(_) => return,
+            };
+
+            let sample = sample.clone();
+            let health = self.health.clone();
+
+            tokio::spawn(async move {
+                let part = format!("handler of feed [{}]", sample.feed);
+
+                // the feed stays degraded until one of its samples is handled again
+                match handler(sample).await {
+                    Ok(()) => health.recover(&part),
+                    Err(e) => health.fail(&part, &e),
+                }
+
+                drop(permit);
+            });
+        }
+    }
+}
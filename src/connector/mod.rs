@@ -0,0 +1,140 @@
+//! Runtimes for the connectors publishing to or following IOTICS twins, behind the `connector` feature.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use tokio::sync::watch;
+
+pub mod follower;
+pub mod publisher;
+
+/// Health of a connector runtime, see [`publisher::PublisherRuntime::health`] and
+/// [`follower::FollowerRuntime::health`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectorHealth {
+    Starting,
+    Running,
+    /// The connector is running but some of its parts are failing.
+    Degraded {
+        reason: String,
+    },
+    Stopping,
+    Stopped,
+    /// The connector couldn't start, or stopped because of an error.
+    Failed {
+        reason: String,
+    },
+}
+
+/// Publishes the [`ConnectorHealth`] of a runtime, degraded while any of its parts is failing.
+pub(crate) struct HealthReporter {
+    sender: watch::Sender<ConnectorHealth>,
+    // the last error of each failing part, e.g. an input or a followed feed
+    failures: Mutex<BTreeMap<String, String>>,
+}
+
+impl HealthReporter {
+    pub(crate) fn new() -> Self {
+        let (sender, _) = watch::channel(ConnectorHealth::Starting);
+
+        Self {
+            sender,
+            failures: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub(crate) fn subscribe(&self) -> watch::Receiver<ConnectorHealth> {
+        self.sender.subscribe()
+    }
+
+    /// Move to a new stage of the lifecycle, `Running` stays degraded while a part is failing.
+    pub(crate) fn set(&self, health: ConnectorHealth) {
+        let mut failures = self.lock_failures();
+
+        if health == ConnectorHealth::Starting {
+            failures.clear();
+        }
+
+        let health = match health {
+            ConnectorHealth::Running => running_health(&failures),
+            health => health,
+        };
+
+        self.sender.send_replace(health);
+    }
+
+    /// Report the failure of `part`, until it recovers.
+    pub(crate) fn fail(&self, part: &str, error: &anyhow::Error) {
+        let mut failures = self.lock_failures();
+        failures.insert(part.to_string(), format!("{:#}", error));
+        self.publish(&failures);
+    }
+
+    /// Report that `part` works again.
+    pub(crate) fn recover(&self, part: &str) {
+        let mut failures = self.lock_failures();
+
+        if failures.remove(part).is_some() {
+            self.publish(&failures);
+        }
+    }
+
+    fn publish(&self, failures: &BTreeMap<String, String>) {
+        let health = running_health(failures);
+
+        // the failures reported while starting or stopping don't change the stage
+        self.sender.send_if_modified(|current| {
+            let running = matches!(
+                current,
+                ConnectorHealth::Running | ConnectorHealth::Degraded { .. }
+            );
+
+            if running && *current != health {
+                *current = health;
+                true
+            } else {
+                false
+            }
+        });
+    }
+
+    fn lock_failures(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, String>> {
+        self.failures
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn running_health(failures: &BTreeMap<String, String>) -> ConnectorHealth {
+    if failures.is_empty() {
+        return ConnectorHealth::Running;
+    }
+
+    ConnectorHealth::Degraded {
+        reason: failures
+            .iter()
+            .map(|(part, error)| format!("{}: {}", part, error))
+            .collect::<Vec<_>>()
+            .join("; "),
+    }
+}
+
+/// Wait for Ctrl+C or, on Unix, `SIGTERM`.
+pub async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        if let Ok(mut terminate) = signal(SignalKind::terminate()) {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {},
+                _ = terminate.recv() => {},
+            }
+            return;
+        }
+    }
+
+    if tokio::signal::ctrl_c().await.is_err() {
+        // without any signal handler, the connector runs until it's stopped another way
+        std::future::pending::<()>().await;
+    }
+}
@@ -0,0 +1,363 @@
+use anyhow::Context;
+use async_trait::async_trait;
+use futures::future::join_all;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+use tonic::transport::Channel;
+
+use crate::auth_builder::IntoAuthBuilder;
+use crate::channel::create_channel;
+use crate::connector::{shutdown_signal, ConnectorHealth, HealthReporter};
+use crate::helpers::Backoff;
+use crate::ids::{FeedRef, InputRef, TwinDid};
+use crate::input::receive_input_messages_with_channel;
+use crate::twin::bulk::{delete_twins_with_channel, upsert_twins_with_channel, UpsertTwinSpec};
+use crate::twin::share::share_data_with_channel;
+
+/// A sample returned by [`Publisher::next_samples`].
+#[derive(Debug, Clone)]
+pub struct FeedSample {
+    pub feed: FeedRef,
+    pub data: Vec<u8>,
+}
+
+/// The connector specific part of a publisher, run by a [`PublisherRuntime`].
+#[async_trait]
+pub trait Publisher: Send + Sync + 'static {
+    /// The twins to upsert when the runtime starts, with their feeds and inputs.
+    async fn setup_twins(&self) -> Result<Vec<UpsertTwinSpec>, anyhow::Error>;
+
+    /// Poll the source, called every [`PublisherConfig::interval`].
+    async fn next_samples(&self) -> Result<Vec<FeedSample>, anyhow::Error>;
+
+    /// Handle a message received on one of the inputs declared by [`Publisher::setup_twins`].
+    async fn on_input(&self, _input: &InputRef, _data: Vec<u8>) -> Result<(), anyhow::Error> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PublisherConfig {
+    /// Time between two calls to [`Publisher::next_samples`].
+    pub interval: Duration,
+    /// Delete the twins returned by [`Publisher::setup_twins`] when the runtime stops, or when it
+    /// fails to start after upserting some of them.
+    pub delete_twins_on_shutdown: bool,
+    /// See [`share_data`](crate::twin::share::share_data).
+    pub retry_unknown: bool,
+    /// Maximum number of twins upserted or deleted at the same time.
+    pub concurrency: usize,
+    /// Time waited before subscribing to an input again after its stream failed or ended, doubled
+    /// after each attempt without any message up to `max_resubscribe_delay`.
+    pub resubscribe_delay: Duration,
+    /// Longest time waited between two subscriptions to an input.
+    pub max_resubscribe_delay: Duration,
+}
+
+impl Default for PublisherConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(10),
+            delete_twins_on_shutdown: false,
+            retry_unknown: false,
+            concurrency: crate::twin::bulk::DEFAULT_CONCURRENCY,
+            resubscribe_delay: Duration::from_secs(1),
+            max_resubscribe_delay: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Runs a [`Publisher`]: upserts its twins, listens to their inputs, shares the polled samples
+/// and cleans up on shutdown.
+pub struct PublisherRuntime<P, A> {
+    publisher: Arc<P>,
+    auth_builder: Arc<A>,
+    config: PublisherConfig,
+    health: Arc<HealthReporter>,
+}
+
+impl<P, A> PublisherRuntime<P, A>
+where
+    P: Publisher,
+    A: IntoAuthBuilder + Send + Sync + 'static,
+{
+    pub fn new(auth_builder: Arc<A>, publisher: P, config: PublisherConfig) -> Self {
+        Self {
+            publisher: Arc::new(publisher),
+            auth_builder,
+            config,
+            health: Arc::new(HealthReporter::new()),
+        }
+    }
+
+    pub fn health(&self) -> watch::Receiver<ConnectorHealth> {
+        self.health.subscribe()
+    }
+
+    /// Run until Ctrl+C or `SIGTERM`.
+    pub async fn run(self) -> Result<(), anyhow::Error> {
+        self.run_until(shutdown_signal()).await
+    }
+
+    /// Run until `shutdown` completes.
+    pub async fn run_until(self, shutdown: impl Future<Output = ()>) -> Result<(), anyhow::Error> {
+        let channel = create_channel(
+            self.auth_builder.clone(),
+            self.auth_builder.get_config().channel_options,
+        )
+        .await?;
+        self.run_with_channel_until(channel, shutdown).await
+    }
+
+    pub async fn run_with_channel_until(
+        self,
+        channel: Channel,
+        shutdown: impl Future<Output = ()>,
+    ) -> Result<(), anyhow::Error> {
+        self.health.set(ConnectorHealth::Starting);
+
+        let (twin_ids, input_tasks) = match self.start(&channel).await {
+            Ok(started) => started,
+            Err(e) => {
+                self.health.set(ConnectorHealth::Failed {
+                    reason: format!("{:#}", e),
+                });
+                return Err(e);
+            }
+        };
+
+        self.health.set(ConnectorHealth::Running);
+
+        tokio::pin!(shutdown);
+        let mut interval = tokio::time::interval(self.config.interval);
+        // a slow poll delays the next ones instead of bursting to catch up
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => break,
+                _ = interval.tick() => {
+                    match self.share_next_samples(&channel).await {
+                        Ok(()) => self.health.recover(POLLING),
+                        Err(e) => self.health.fail(POLLING, &e),
+                    }
+                }
+            }
+        }
+
+        self.health.set(ConnectorHealth::Stopping);
+
+        for task in input_tasks {
+            task.abort();
+        }
+
+        let result = if self.config.delete_twins_on_shutdown {
+            self.delete_twins(&channel, twin_ids).await
+        } else {
+            Ok(())
+        };
+
+        self.health.set(ConnectorHealth::Stopped);
+
+        result
+    }
+
+    /// Upsert the twins and subscribe to their inputs. When it fails, the upserted twins are
+    /// deleted again if [`PublisherConfig::delete_twins_on_shutdown`] is set.
+    async fn start(
+        &self,
+        channel: &Channel,
+    ) -> Result<(Vec<TwinDid>, Vec<JoinHandle<()>>), anyhow::Error> {
+        let specs = self
+            .publisher
+            .setup_twins()
+            .await
+            .context("setting up the publisher twins failed")?;
+
+        let inputs = specs
+            .iter()
+            .flat_map(|spec| {
+                spec.inputs
+                    .iter()
+                    .map(|input| InputRef::new(spec.twin_id.clone(), input.id.clone()))
+            })
+            .collect::<Vec<_>>();
+
+        let results = upsert_twins_with_channel(
+            self.auth_builder.clone(),
+            channel.clone(),
+            specs,
+            self.config.concurrency,
+            |_| {},
+        )
+        .await;
+
+        let mut twin_ids = Vec::with_capacity(results.len());
+        let mut upsert_error = None;
+
+        for result in results {
+            match result.result {
+                Ok(_) => twin_ids.push(result.twin_id),
+                Err(e) => {
+                    upsert_error.get_or_insert_with(|| {
+                        e.context(format!("upserting twin [{}] failed", result.twin_id))
+                    });
+                }
+            }
+        }
+
+        if let Some(e) = upsert_error {
+            return Err(self.clean_up_failed_start(channel, twin_ids, e).await);
+        }
+
+        let mut input_tasks = Vec::with_capacity(inputs.len());
+
+        for input in inputs {
+            match self.listen_to_input(channel.clone(), input).await {
+                Ok(task) => input_tasks.push(task),
+                Err(e) => {
+                    for task in input_tasks {
+                        task.abort();
+                    }
+                    return Err(self.clean_up_failed_start(channel, twin_ids, e).await);
+                }
+            }
+        }
+
+        Ok((twin_ids, input_tasks))
+    }
+
+    /// Delete the twins upserted by a failed start if they're deleted on shutdown, the returned
+    /// error is the one of the start.
+    async fn clean_up_failed_start(
+        &self,
+        channel: &Channel,
+        twin_ids: Vec<TwinDid>,
+        error: anyhow::Error,
+    ) -> anyhow::Error {
+        if !self.config.delete_twins_on_shutdown {
+            return error;
+        }
+
+        match self.delete_twins(channel, twin_ids).await {
+            Ok(()) => error,
+            Err(e) => error.context(format!("cleaning up the twins failed: {:#}", e)),
+        }
+    }
+
+    async fn delete_twins(
+        &self,
+        channel: &Channel,
+        twin_ids: Vec<TwinDid>,
+    ) -> Result<(), anyhow::Error> {
+        delete_twins_with_channel(
+            self.auth_builder.clone(),
+            channel.clone(),
+            twin_ids,
+            self.config.concurrency,
+            |_| {},
+        )
+        .await
+        .into_iter()
+        .try_for_each(|result| {
+            result
+                .result
+                .with_context(|| format!("deleting twin [{}] failed", result.twin_id))
+        })
+    }
+
+    async fn share_next_samples(&self, channel: &Channel) -> Result<(), anyhow::Error> {
+        let samples = self.publisher.next_samples().await?;
+
+        let results = join_all(samples.into_iter().map(|sample| {
+            let auth_builder = self.auth_builder.clone();
+            let channel = channel.clone();
+            let retry_unknown = self.config.retry_unknown;
+
+            async move {
+                share_data_with_channel(
+                    auth_builder,
+                    channel,
+                    &sample.feed,
+                    sample.data,
+                    retry_unknown,
+                )
+                .await
+                .with_context(|| format!("sharing to feed [{}] failed", sample.feed))
+            }
+        }))
+        .await;
+
+        results.into_iter().collect::<Result<Vec<_>, _>>()?;
+
+        Ok(())
+    }
+
+    /// Handle the messages of `input` in a task, subscribing to it again when its stream fails
+    /// or ends. The input is reported as failing until it receives a message again.
+    async fn listen_to_input(
+        &self,
+        channel: Channel,
+        input: InputRef,
+    ) -> Result<JoinHandle<()>, anyhow::Error> {
+        let mut messages =
+            receive_input_messages_with_channel(self.auth_builder.clone(), channel.clone(), &input)
+                .await?;
+
+        let auth_builder = self.auth_builder.clone();
+        let publisher = self.publisher.clone();
+        let health = self.health.clone();
+        let mut backoff = Backoff::new(
+            self.config.resubscribe_delay,
+            self.config.max_resubscribe_delay,
+        );
+
+        Ok(tokio::spawn(async move {
+            let part = format!("input [{}]", input);
+
+            loop {
+                while let Some(message) = messages.recv().await {
+                    let result = match message {
+                        Ok(data) => {
+                            backoff.reset();
+                            publisher.on_input(&input, data).await
+                        }
+                        Err(e) => Err(e),
+                    };
+
+                    match result {
+                        Ok(()) => health.recover(&part),
+                        Err(e) => health.fail(&part, &e),
+                    }
+                }
+
+                // the host closed the stream or the connection dropped
+                health.fail(&part, &anyhow::anyhow!("the input stream ended"));
+
+                loop {
+                    tokio::time::sleep(backoff.next_delay()).await;
+
+                    match receive_input_messages_with_channel(
+                        auth_builder.clone(),
+                        channel.clone(),
+                        &input,
+                    )
+                    .await
+                    {
+                        Ok(receiver) => {
+                            messages = receiver;
+                            break;
+                        }
+                        Err(e) => health.fail(&part, &e.context("subscribing again failed")),
+                    }
+                }
+            }
+        }))
+    }
+}
+
+const POLLING: &str = "polling";
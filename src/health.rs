@@ -0,0 +1,305 @@
+use anyhow::Context;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tonic::transport::Channel;
+use tonic::Code;
+use tonic_health::pb::health_check_response::ServingStatus as GrpcServingStatus;
+use tonic_health::pb::health_client::HealthClient;
+use tonic_health::pb::HealthCheckRequest;
+
+use crate::auth_builder::IntoAuthBuilder;
+use crate::call_context::CallContext;
+use crate::channel::create_channel;
+use crate::helpers::is_unreachable;
+use crate::host::get_local_host_id_with_channel;
+use crate::telemetry::Rpc;
+
+/// Status reported by the `grpc.health.v1` service of the host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServingStatus {
+    Serving,
+    NotServing,
+    Unknown,
+}
+
+/// Result of a successful [`health`] check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthReport {
+    pub host_id: String,
+    /// Latency of the authenticated `GetHostId` call.
+    pub latency: Duration,
+    /// `None` when it wasn't requested or the host doesn't implement `grpc.health.v1`.
+    pub serving_status: Option<ServingStatus>,
+}
+
+/// Check that the host is reachable and that the token is valid with a lightweight authenticated
+/// call, optionally followed by a `grpc.health.v1` check.
+///
+/// Connecting to the host and each check fail with `DeadlineExceeded` when they take longer than
+/// `timeout`.
+pub async fn health(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    check_grpc_health: bool,
+    timeout: Duration,
+) -> Result<HealthReport, anyhow::Error> {
+    let channel = with_timeout(
+        timeout,
+        "Connecting to the host",
+        create_channel(
+            auth_builder.clone(),
+            auth_builder.get_config().channel_options,
+        ),
+    )
+    .await?;
+    health_with_channel(auth_builder, channel, check_grpc_health, timeout).await
+}
+
+pub async fn health_with_channel(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    channel: Channel,
+    check_grpc_health: bool,
+    timeout: Duration,
+) -> Result<HealthReport, anyhow::Error> {
+    let started_at = Instant::now();
+
+    let response = with_timeout(
+        timeout,
+        "Getting the host id",
+        get_local_host_id_with_channel(auth_builder.clone(), channel.clone()),
+    )
+    .await?;
+
+    let latency = started_at.elapsed();
+
+    let host_id = response.payload.context("failed to find payload")?.host_id;
+
+    let serving_status = if check_grpc_health {
+        with_timeout(
+            timeout,
+            "Checking the gRPC health",
+            grpc_health(auth_builder, channel),
+        )
+        .await?
+    } else {
+        None
+    };
+
+    Ok(HealthReport {
+        host_id,
+        latency,
+        serving_status,
+    })
+}
+
+/// Run a step of the health check, failing with `DeadlineExceeded` when it takes longer than `timeout`.
+async fn with_timeout<T>(
+    timeout: Duration,
+    step: &str,
+    fut: impl Future<Output = Result<T, anyhow::Error>>,
+) -> Result<T, anyhow::Error> {
+    match tokio::time::timeout(timeout, fut).await {
+        Ok(result) => result,
+        Err(_) => Err(anyhow::Error::new(tonic::Status::deadline_exceeded(
+            format!("{} took longer than {} ms", step, timeout.as_millis()),
+        ))),
+    }
+}
+
+async fn grpc_health(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    channel: Channel,
+) -> Result<Option<ServingStatus>, anyhow::Error> {
+    let mut client = HealthClient::new(channel);
+    let headers = CallContext::headers(auth_builder.get_config().call_context);
+
+    let rpc = Rpc::new("grpc.health.v1.Health", "Check", &headers);
+
+    let mut request = tonic::Request::new(HealthCheckRequest {
+        service: String::new(),
+    });
+
+    let token = auth_builder.get_token()?;
+
+    request.metadata_mut().append(
+        "authorization",
+        token.parse().context("parse token failed")?,
+    );
+
+    rpc.inject(&mut request);
+
+    let response = match rpc.run(client.check(request)).await {
+        Ok(response) => response.into_inner(),
+        Err(status) if status.code() == Code::Unimplemented => return Ok(None),
+        Err(status) => return Err(status).context("Checking the gRPC health failed"),
+    };
+
+    let serving_status = match GrpcServingStatus::from_i32(response.status) {
+        Some(GrpcServingStatus::Serving) => ServingStatus::Serving,
+        Some(GrpcServingStatus::NotServing) => ServingStatus::NotServing,
+        _ => ServingStatus::Unknown,
+    };
+
+    Ok(Some(serving_status))
+}
+
+/// Connectivity to the host as seen by a [`HealthWatcher`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectivityState {
+    Connected,
+    /// The host is reachable but the token was rejected, it's not serving or it's too slow.
+    Degraded {
+        reason: String,
+    },
+    /// The host can't be reached.
+    Disconnected {
+        reason: String,
+    },
+}
+
+/// Suggested timeout of the [`health`] checks.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone)]
+pub struct HealthWatcherConfig {
+    /// Time between two checks.
+    pub interval: Duration,
+    /// Also check the `grpc.health.v1` service of the host.
+    pub check_grpc_health: bool,
+    /// The host is degraded when a check takes longer.
+    pub max_latency: Option<Duration>,
+    /// The host is disconnected when a check doesn't complete in time, see [`health`].
+    pub timeout: Duration,
+}
+
+impl Default for HealthWatcherConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(30),
+            check_grpc_health: false,
+            max_latency: None,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+/// Checks the host periodically and publishes the changes of [`ConnectivityState`], e.g. to back
+/// the readiness endpoint of a service. The checks stop when it's dropped.
+pub struct HealthWatcher {
+    state: watch::Receiver<ConnectivityState>,
+    task: JoinHandle<()>,
+}
+
+impl HealthWatcher {
+    /// Start watching the host, the channel connects lazily so that it also starts while the host is down.
+    pub async fn start<A>(
+        auth_builder: Arc<A>,
+        config: HealthWatcherConfig,
+    ) -> Result<Self, anyhow::Error>
+    where
+        A: IntoAuthBuilder + Send + Sync + 'static,
+    {
+        let options = auth_builder.get_config().channel_options.lazy(true);
+        let channel = create_channel(auth_builder.clone(), options).await?;
+        Ok(Self::start_with_channel(auth_builder, channel, config).await)
+    }
+
+    /// Start watching the host, the first check is done before returning.
+    pub async fn start_with_channel<A>(
+        auth_builder: Arc<A>,
+        channel: Channel,
+        config: HealthWatcherConfig,
+    ) -> Self
+    where
+        A: IntoAuthBuilder + Send + Sync + 'static,
+    {
+        let initial_state = check(&auth_builder, &channel, &config).await;
+        let (state_tx, state) = watch::channel(initial_state);
+
+        let task = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(config.interval);
+            // the first tick completes immediately and the host has just been checked
+            interval.tick().await;
+
+            loop {
+                interval.tick().await;
+
+                let new_state = check(&auth_builder, &channel, &config).await;
+
+                state_tx.send_if_modified(|state| {
+                    if *state == new_state {
+                        return false;
+                    }
+
+                    *state = new_state;
+                    true
+                });
+            }
+        });
+
+        Self { state, task }
+    }
+
+    /// Receiver notified on every state change.
+    pub fn state(&self) -> watch::Receiver<ConnectivityState> {
+        self.state.clone()
+    }
+
+    pub fn current(&self) -> ConnectivityState {
+        self.state.borrow().clone()
+    }
+}
+
+impl Drop for HealthWatcher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn check(
+    auth_builder: &Arc<impl IntoAuthBuilder>,
+    channel: &Channel,
+    config: &HealthWatcherConfig,
+) -> ConnectivityState {
+    let report = health_with_channel(
+        auth_builder.clone(),
+        channel.clone(),
+        config.check_grpc_health,
+        config.timeout,
+    )
+    .await;
+
+    match report {
+        Ok(report) if report.serving_status == Some(ServingStatus::NotServing) => {
+            ConnectivityState::Degraded {
+                reason: "the host is not serving".to_string(),
+            }
+        }
+        Ok(report) => match config.max_latency {
+            Some(max_latency) if report.latency > max_latency => ConnectivityState::Degraded {
+                reason: format!(
+                    "latency of {} ms above {} ms",
+                    report.latency.as_millis(),
+                    max_latency.as_millis()
+                ),
+            },
+            _ => ConnectivityState::Connected,
+        },
+        // the host didn't answer in time
+        Err(e) if is_unreachable(&e) || is_timed_out(&e) => ConnectivityState::Disconnected {
+            reason: format!("{:#}", e),
+        },
+        Err(e) => ConnectivityState::Degraded {
+            reason: format!("{:#}", e),
+        },
+    }
+}
+
+fn is_timed_out(error: &anyhow::Error) -> bool {
+    matches!(
+        error.downcast_ref::<tonic::Status>(),
+        Some(status) if status.code() == Code::DeadlineExceeded
+    )
+}
@@ -1,6 +1,9 @@
 use rand::distributions::Alphanumeric;
 use rand::{thread_rng, Rng};
+use std::io::{self, ErrorKind};
 use std::iter;
+use std::time::Duration;
+use tonic::Code;
 
 pub fn generate_client_app_id() -> String {
     let mut rng = thread_rng();
@@ -13,3 +16,108 @@ pub fn generate_client_app_id() -> String {
 
     chars
 }
+
+/// Whether a call failed because the host couldn't be reached, rather than the request being rejected.
+///
+/// Only `Unavailable` and the transport errors caused by a failed connection or I/O count, an invalid
+/// URI or TLS configuration doesn't. `DeadlineExceeded` doesn't either, the host may have handled
+/// the request.
+pub fn is_unreachable(error: &anyhow::Error) -> bool {
+    if let Some(status) = error.downcast_ref::<tonic::Status>() {
+        return status.code() == Code::Unavailable;
+    }
+
+    error
+        .chain()
+        .skip_while(|cause| !cause.is::<tonic::transport::Error>())
+        .filter_map(|cause| cause.downcast_ref::<io::Error>())
+        // the TLS handshake errors, e.g. an untrusted certificate, are reported as invalid data
+        .any(|e| !matches!(e.kind(), ErrorKind::InvalidData | ErrorKind::InvalidInput))
+}
+
+/// Exponential backoff between retries, doubling the delay from `min` up to `max`.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    min: Duration,
+    max: Duration,
+    next: Duration,
+}
+
+impl Backoff {
+    pub fn new(min: Duration, max: Duration) -> Self {
+        Self {
+            min,
+            max: max.max(min),
+            next: min,
+        }
+    }
+
+    /// The delay before the next retry, the following one is twice as long.
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self.next;
+        self.next = self.next.saturating_mul(2).min(self.max);
+        delay
+    }
+
+    /// Start again from `min` once a retry succeeded.
+    pub fn reset(&mut self) {
+        self.next = self.min;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tonic::transport::Endpoint;
+
+    #[test]
+    fn an_unavailable_host_is_unreachable() {
+        assert!(is_unreachable(&anyhow::Error::new(
+            tonic::Status::unavailable("connection refused")
+        )));
+    }
+
+    #[test]
+    fn a_rejected_or_timed_out_call_is_not_unreachable() {
+        for status in [
+            tonic::Status::deadline_exceeded("too slow"),
+            tonic::Status::cancelled("cancelled"),
+            tonic::Status::invalid_argument("bad request"),
+        ] {
+            assert!(!is_unreachable(&anyhow::Error::new(status)));
+        }
+    }
+
+    #[test]
+    fn an_invalid_uri_is_not_unreachable() {
+        let error = Endpoint::from_shared("not a uri".to_string()).unwrap_err();
+
+        assert!(!is_unreachable(
+            &anyhow::Error::new(error).context("failed to create the channel")
+        ));
+    }
+
+    #[tokio::test]
+    async fn a_refused_connection_is_unreachable() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        drop(listener);
+
+        let error = Endpoint::from_shared(format!("http://{}", address))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap_err();
+
+        assert!(is_unreachable(&anyhow::Error::new(error)));
+    }
+
+    #[test]
+    fn a_local_io_error_is_not_unreachable() {
+        let error = io::Error::new(ErrorKind::NotFound, "no such file");
+
+        assert!(!is_unreachable(
+            &anyhow::Error::new(error).context("failed to read the outbox log")
+        ));
+    }
+}
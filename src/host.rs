@@ -1,20 +1,31 @@
 use anyhow::Context;
 use std::result::Result;
 use std::sync::Arc;
+use tokio::sync::OnceCell;
 use tonic::transport::Channel;
 
 use crate::client::iotics::api::host_api_client::HostApiClient;
 
+use crate::client::iotics::api::{FeedId, InputId, TwinId};
+use crate::search::SearchResponse;
+use crate::twin::DescribeTwinResponse;
+
 pub use crate::client::iotics::api::{GetHostIdRequest, GetHostIdResponse, Headers};
 
 use crate::auth_builder::IntoAuthBuilder;
-use crate::channel::create_channel;
-use crate::helpers::generate_client_app_id;
+use crate::call_context::CallContext;
+use crate::channel::{create_channel, ConfigureClient};
+use crate::ids::HostDid;
+use crate::telemetry::Rpc;
 
 pub async fn get_local_host_id(
     auth_builder: Arc<impl IntoAuthBuilder>,
 ) -> Result<GetHostIdResponse, anyhow::Error> {
-    let channel = create_channel(auth_builder.clone(), None, None, None).await?;
+    let channel = create_channel(
+        auth_builder.clone(),
+        auth_builder.get_config().channel_options,
+    )
+    .await?;
     get_local_host_id_with_channel(auth_builder, channel).await
 }
 
@@ -22,15 +33,12 @@ pub async fn get_local_host_id_with_channel(
     auth_builder: Arc<impl IntoAuthBuilder>,
     channel: Channel,
 ) -> Result<GetHostIdResponse, anyhow::Error> {
-    let mut client = HostApiClient::new(channel);
-    let client_app_id = generate_client_app_id();
-    let transaction_ref = vec![client_app_id.clone()];
+    let config = auth_builder.get_config();
+    let mut client = HostApiClient::new(channel).configure(&config.channel_options);
+    let headers = CallContext::headers(config.call_context);
+    let transaction_ref = headers.transaction_ref.clone();
 
-    let headers = Headers {
-        client_app_id,
-        transaction_ref: transaction_ref.clone(),
-        ..Default::default()
-    };
+    let rpc = Rpc::new("HostAPI", "GetHostId", &headers);
 
     let mut request = tonic::Request::new(GetHostIdRequest {
         headers: Some(headers),
@@ -43,13 +51,199 @@ pub async fn get_local_host_id_with_channel(
         token.parse().context("parse token failed")?,
     );
 
-    let result = client.get_host_id(request).await.with_context(|| {
-        format!(
-            "Getting local host id failed, transaction ref [{}]",
-            transaction_ref.join(", ")
-        )
-    })?;
+    rpc.inject(&mut request);
+
+    let result = rpc
+        .run(client.get_host_id(request))
+        .await
+        .with_context(|| {
+            format!(
+                "Getting local host id failed, transaction ref [{}]",
+                transaction_ref.join(", ")
+            )
+        })?;
     let result = result.into_inner();
 
     Ok(result)
 }
+
+/// Resolves the id of the local host once and shares it between its clones.
+pub struct LocalHostCache<A> {
+    auth_builder: Arc<A>,
+    local_host: Arc<OnceCell<LocalHost>>,
+}
+
+impl<A> Clone for LocalHostCache<A> {
+    fn clone(&self) -> Self {
+        Self {
+            auth_builder: self.auth_builder.clone(),
+            local_host: self.local_host.clone(),
+        }
+    }
+}
+
+impl<A: IntoAuthBuilder> LocalHostCache<A> {
+    pub fn new(auth_builder: Arc<A>) -> Self {
+        Self {
+            auth_builder,
+            local_host: Arc::new(OnceCell::new()),
+        }
+    }
+
+    pub async fn local_host_id(&self) -> Result<HostDid, anyhow::Error> {
+        Ok(self.local_host().await?.host_id().clone())
+    }
+
+    pub async fn local_host_id_with_channel(
+        &self,
+        channel: Channel,
+    ) -> Result<HostDid, anyhow::Error> {
+        Ok(self
+            .local_host_with_channel(channel)
+            .await?
+            .host_id()
+            .clone())
+    }
+
+    /// The [`LocalHost`] used to classify and normalize ids, only the first call reaches the host.
+    pub async fn local_host(&self) -> Result<&LocalHost, anyhow::Error> {
+        self.local_host
+            .get_or_try_init(|| async {
+                let channel = create_channel(
+                    self.auth_builder.clone(),
+                    self.auth_builder.get_config().channel_options,
+                )
+                .await?;
+                fetch_local_host(self.auth_builder.clone(), channel).await
+            })
+            .await
+    }
+
+    pub async fn local_host_with_channel(
+        &self,
+        channel: Channel,
+    ) -> Result<&LocalHost, anyhow::Error> {
+        self.local_host
+            .get_or_try_init(|| fetch_local_host(self.auth_builder.clone(), channel))
+            .await
+    }
+}
+
+async fn fetch_local_host(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    channel: Channel,
+) -> Result<LocalHost, anyhow::Error> {
+    let response = get_local_host_id_with_channel(auth_builder, channel).await?;
+    let host_id = response.payload.context("failed to find payload")?.host_id;
+
+    Ok(LocalHost::new(host_id.parse()?))
+}
+
+/// Classifies ids as local or remote, an empty host id being the local host, and fills in the
+/// empty host ids.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalHost {
+    host_id: HostDid,
+}
+
+impl LocalHost {
+    pub fn new(host_id: HostDid) -> Self {
+        Self { host_id }
+    }
+
+    pub fn host_id(&self) -> &HostDid {
+        &self.host_id
+    }
+
+    pub fn is_local(&self, host_id: &str) -> bool {
+        host_id.is_empty() || host_id == self.host_id.as_str()
+    }
+
+    pub fn is_local_twin(&self, twin_id: &TwinId) -> bool {
+        self.is_local(&twin_id.host_id)
+    }
+
+    pub fn is_local_feed(&self, feed_id: &FeedId) -> bool {
+        self.is_local(&feed_id.host_id)
+    }
+
+    pub fn is_local_input(&self, input_id: &InputId) -> bool {
+        self.is_local(&input_id.host_id)
+    }
+
+    pub fn fill_twin_id(&self, twin_id: &mut TwinId) {
+        fill_host_id(&mut twin_id.host_id, self.host_id.as_str());
+    }
+
+    pub fn fill_feed_id(&self, feed_id: &mut FeedId) {
+        fill_host_id(&mut feed_id.host_id, self.host_id.as_str());
+    }
+
+    pub fn fill_input_id(&self, input_id: &mut InputId) {
+        fill_host_id(&mut input_id.host_id, self.host_id.as_str());
+    }
+
+    /// Fill in the empty host ids of the twins, feeds and inputs found with the host that
+    /// answered, or the local host.
+    pub fn normalize_search_response(&self, response: &mut SearchResponse) {
+        let payload = match response.payload.as_mut() {
+            Some(payload) => payload,
+            None => return,
+        };
+
+        fill_host_id(&mut payload.host_id, self.host_id.as_str());
+        let host_id = payload.host_id.as_str();
+
+        for twin in &mut payload.twins {
+            if let Some(twin_id) = twin.twin_id.as_mut() {
+                fill_host_id(&mut twin_id.host_id, host_id);
+            }
+
+            for feed in &mut twin.feeds {
+                if let Some(feed_id) = feed.feed_id.as_mut() {
+                    fill_host_id(&mut feed_id.host_id, host_id);
+                }
+            }
+
+            for input in &mut twin.inputs {
+                if let Some(input_id) = input.input_id.as_mut() {
+                    fill_host_id(&mut input_id.host_id, host_id);
+                }
+            }
+        }
+    }
+
+    /// Fill in the empty host ids of the described twin, its feeds and inputs with the host of
+    /// the twin, or the local host.
+    pub fn normalize_describe_twin_response(&self, response: &mut DescribeTwinResponse) {
+        let payload = match response.payload.as_mut() {
+            Some(payload) => payload,
+            None => return,
+        };
+
+        let mut host_id = self.host_id.to_string();
+
+        if let Some(twin_id) = payload.twin_id.as_mut() {
+            fill_host_id(&mut twin_id.host_id, &host_id);
+            host_id = twin_id.host_id.clone();
+        }
+
+        for feed in &mut payload.feeds {
+            if let Some(feed_id) = feed.feed_id.as_mut() {
+                fill_host_id(&mut feed_id.host_id, &host_id);
+            }
+        }
+
+        for input in &mut payload.inputs {
+            if let Some(input_id) = input.input_id.as_mut() {
+                fill_host_id(&mut input_id.host_id, &host_id);
+            }
+        }
+    }
+}
+
+fn fill_host_id(host_id: &mut String, default_host_id: &str) {
+    if host_id.is_empty() {
+        *host_id = default_host_id.to_string();
+    }
+}
@@ -0,0 +1,423 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+use crate::client::iotics::api::{FeedId, InputId, TwinId};
+
+const DID_PREFIX: &str = "did:iotics:";
+const BASE58_ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum IdError {
+    #[error("invalid DID [{0}]")]
+    InvalidDid(String),
+    #[error("invalid feed reference [{0}]")]
+    InvalidFeedRef(String),
+    #[error("invalid input reference [{0}]")]
+    InvalidInputRef(String),
+    #[error("missing {0} id")]
+    MissingId(&'static str),
+}
+
+fn validate_did(value: &str) -> Result<(), IdError> {
+    let method_specific_id = value
+        .strip_prefix(DID_PREFIX)
+        .ok_or_else(|| IdError::InvalidDid(value.to_string()))?;
+
+    if method_specific_id.is_empty()
+        || !method_specific_id
+            .chars()
+            .all(|c| BASE58_ALPHABET.contains(c))
+    {
+        return Err(IdError::InvalidDid(value.to_string()));
+    }
+
+    Ok(())
+}
+
+macro_rules! did_type {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+        #[serde(try_from = "String", into = "String")]
+        pub struct $name(String);
+
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = IdError;
+
+            fn from_str(value: &str) -> Result<Self, Self::Err> {
+                validate_did(value)?;
+                Ok(Self(value.to_string()))
+            }
+        }
+
+        impl TryFrom<String> for $name {
+            type Error = IdError;
+
+            fn try_from(value: String) -> Result<Self, Self::Error> {
+                validate_did(&value)?;
+                Ok(Self(value))
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+    };
+}
+
+did_type!(
+    /// DID of a twin, e.g. `did:iotics:iotXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX`.
+    TwinDid
+);
+
+did_type!(
+    /// DID of an IOTICS host.
+    HostDid
+);
+
+fn parse_host_id(host_id: &str) -> Result<Option<HostDid>, IdError> {
+    if host_id.is_empty() {
+        Ok(None)
+    } else {
+        host_id.parse().map(Some)
+    }
+}
+
+fn host_id_to_string(host: &Option<HostDid>) -> String {
+    host.as_ref()
+        .map(|host| host.to_string())
+        .unwrap_or_default()
+}
+
+impl TwinDid {
+    /// Build the [`TwinId`] of this twin, optionally located on a remote host.
+    pub fn to_twin_id(&self, host: Option<&HostDid>) -> TwinId {
+        TwinId {
+            id: self.0.clone(),
+            host_id: host.map(|host| host.to_string()).unwrap_or_default(),
+        }
+    }
+}
+
+impl From<&TwinDid> for TwinId {
+    fn from(value: &TwinDid) -> Self {
+        value.to_twin_id(None)
+    }
+}
+
+impl TryFrom<&TwinId> for TwinDid {
+    type Error = IdError;
+
+    fn try_from(value: &TwinId) -> Result<Self, Self::Error> {
+        value.id.parse()
+    }
+}
+
+/// Fully qualified reference to a feed.
+///
+/// Its string form is `[host_did/]twin_did/feed_id`, the host being `None` for the local host.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedRef {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<HostDid>,
+    pub twin: TwinDid,
+    pub feed: String,
+}
+
+impl FeedRef {
+    pub fn new(twin: TwinDid, feed: impl Into<String>) -> Self {
+        Self {
+            host: None,
+            twin,
+            feed: feed.into(),
+        }
+    }
+
+    pub fn with_host(mut self, host: Option<HostDid>) -> Self {
+        self.host = host;
+        self
+    }
+
+    /// [`TwinId`] of the twin owning this reference.
+    pub fn twin_id(&self) -> TwinId {
+        self.twin.to_twin_id(self.host.as_ref())
+    }
+}
+
+/// Fully qualified reference to an input.
+///
+/// Its string form is `[host_did/]twin_did/input_id`, the host being `None` for the local host.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InputRef {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<HostDid>,
+    pub twin: TwinDid,
+    pub input: String,
+}
+
+impl InputRef {
+    pub fn new(twin: TwinDid, input: impl Into<String>) -> Self {
+        Self {
+            host: None,
+            twin,
+            input: input.into(),
+        }
+    }
+
+    pub fn with_host(mut self, host: Option<HostDid>) -> Self {
+        self.host = host;
+        self
+    }
+
+    /// [`TwinId`] of the twin owning this reference.
+    pub fn twin_id(&self) -> TwinId {
+        self.twin.to_twin_id(self.host.as_ref())
+    }
+}
+
+/// Split `[host_did/]twin_did/id` into its parts.
+fn split_ref(value: &str) -> Option<(Option<&str>, &str, &str)> {
+    let parts = value.split('/').collect::<Vec<_>>();
+
+    match parts.as_slice() {
+        [twin, id] if !id.is_empty() => Some((None, *twin, *id)),
+        [host, twin, id] if !id.is_empty() => Some((Some(*host), *twin, *id)),
+        _ => None,
+    }
+}
+
+impl FromStr for FeedRef {
+    type Err = IdError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (host, twin, feed) =
+            split_ref(value).ok_or_else(|| IdError::InvalidFeedRef(value.to_string()))?;
+
+        Ok(Self {
+            host: host.map(HostDid::from_str).transpose()?,
+            twin: twin.parse()?,
+            feed: feed.to_string(),
+        })
+    }
+}
+
+impl FromStr for InputRef {
+    type Err = IdError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (host, twin, input) =
+            split_ref(value).ok_or_else(|| IdError::InvalidInputRef(value.to_string()))?;
+
+        Ok(Self {
+            host: host.map(HostDid::from_str).transpose()?,
+            twin: twin.parse()?,
+            input: input.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for FeedRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(host) = &self.host {
+            write!(f, "{}/", host)?;
+        }
+        write!(f, "{}/{}", self.twin, self.feed)
+    }
+}
+
+impl fmt::Display for InputRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(host) = &self.host {
+            write!(f, "{}/", host)?;
+        }
+        write!(f, "{}/{}", self.twin, self.input)
+    }
+}
+
+impl From<&FeedRef> for FeedId {
+    fn from(value: &FeedRef) -> Self {
+        FeedId {
+            id: value.feed.clone(),
+            twin_id: value.twin.to_string(),
+            host_id: host_id_to_string(&value.host),
+        }
+    }
+}
+
+impl TryFrom<&FeedId> for FeedRef {
+    type Error = IdError;
+
+    fn try_from(value: &FeedId) -> Result<Self, Self::Error> {
+        if value.id.is_empty() {
+            return Err(IdError::MissingId("feed"));
+        }
+
+        Ok(Self {
+            host: parse_host_id(&value.host_id)?,
+            twin: value.twin_id.parse()?,
+            feed: value.id.clone(),
+        })
+    }
+}
+
+impl From<&InputRef> for InputId {
+    fn from(value: &InputRef) -> Self {
+        InputId {
+            id: value.input.clone(),
+            twin_id: value.twin.to_string(),
+            host_id: host_id_to_string(&value.host),
+        }
+    }
+}
+
+impl TryFrom<&InputId> for InputRef {
+    type Error = IdError;
+
+    fn try_from(value: &InputId) -> Result<Self, Self::Error> {
+        if value.id.is_empty() {
+            return Err(IdError::MissingId("input"));
+        }
+
+        Ok(Self {
+            host: parse_host_id(&value.host_id)?,
+            twin: value.twin_id.parse()?,
+            input: value.id.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TWIN: &str = "did:iotics:iotHjrmKpPGWyEC4FFo4d6oyzVVk6MXLmEgY";
+    const HOST: &str = "did:iotics:iotVi2rm6G5ZbwZtwEyGW2v1BqJi7mQCqcJm";
+
+    #[test]
+    fn parses_a_did() {
+        let twin = TWIN.parse::<TwinDid>().unwrap();
+
+        assert_eq!(twin.as_str(), TWIN);
+        assert_eq!(twin.to_string(), TWIN);
+    }
+
+    #[test]
+    fn rejects_an_invalid_did() {
+        for value in [
+            "",
+            "did:iotics:",
+            "iotHjrmKpPGWyEC4FFo4d6oyzVVk6MXLmEgY",
+            "did:other:iotHjrmKpPGWyEC4FFo4d6oyzVVk6MXLmEgY",
+            // `0`, `O`, `I` and `l` aren't base58
+            "did:iotics:iot0OIl",
+            "did:iotics:iot/feed",
+        ] {
+            assert_eq!(
+                value.parse::<TwinDid>(),
+                Err(IdError::InvalidDid(value.to_string())),
+                "{}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn parses_a_local_feed_ref() {
+        let feed = format!("{}/temperature", TWIN).parse::<FeedRef>().unwrap();
+
+        assert_eq!(feed.host, None);
+        assert_eq!(feed.twin.as_str(), TWIN);
+        assert_eq!(feed.feed, "temperature");
+    }
+
+    #[test]
+    fn parses_a_remote_feed_ref() {
+        let value = format!("{}/{}/temperature", HOST, TWIN);
+        let feed = value.parse::<FeedRef>().unwrap();
+
+        assert_eq!(feed.host.as_ref().map(HostDid::as_str), Some(HOST));
+        assert_eq!(feed.twin.as_str(), TWIN);
+        assert_eq!(feed.feed, "temperature");
+        assert_eq!(feed.to_string(), value);
+    }
+
+    #[test]
+    fn rejects_an_invalid_feed_ref() {
+        for value in [
+            TWIN.to_string(),
+            format!("{}/", TWIN),
+            format!("{}/{}/{}/temperature", HOST, HOST, TWIN),
+        ] {
+            assert_eq!(
+                value.parse::<FeedRef>(),
+                Err(IdError::InvalidFeedRef(value.clone())),
+                "{}",
+                value
+            );
+        }
+
+        assert_eq!(
+            "twin/temperature".parse::<FeedRef>(),
+            Err(IdError::InvalidDid("twin".to_string()))
+        );
+    }
+
+    #[test]
+    fn converts_a_feed_ref_to_and_from_a_feed_id() {
+        let feed = FeedRef::new(TWIN.parse().unwrap(), "temperature")
+            .with_host(Some(HOST.parse().unwrap()));
+
+        let feed_id = FeedId::from(&feed);
+
+        assert_eq!(feed_id.id, "temperature");
+        assert_eq!(feed_id.twin_id, TWIN);
+        assert_eq!(feed_id.host_id, HOST);
+        assert_eq!(FeedRef::try_from(&feed_id), Ok(feed));
+    }
+
+    #[test]
+    fn rejects_a_feed_id_without_id() {
+        let feed_id = FeedId {
+            id: String::new(),
+            twin_id: TWIN.to_string(),
+            host_id: String::new(),
+        };
+
+        assert_eq!(FeedRef::try_from(&feed_id), Err(IdError::MissingId("feed")));
+    }
+
+    #[test]
+    fn parses_an_input_ref() {
+        let input = format!("{}/{}/switch", HOST, TWIN)
+            .parse::<InputRef>()
+            .unwrap();
+
+        assert_eq!(input.host.as_ref().map(HostDid::as_str), Some(HOST));
+        assert_eq!(input.twin.as_str(), TWIN);
+        assert_eq!(input.input, "switch");
+    }
+}
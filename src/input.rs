@@ -15,42 +15,40 @@ pub use crate::client::iotics::api::{
 };
 
 use crate::auth_builder::IntoAuthBuilder;
-use crate::channel::create_channel;
-use crate::helpers::generate_client_app_id;
+use crate::call_context::CallContext;
+use crate::channel::{create_channel, ConfigureClient};
+use crate::ids::InputRef;
+use crate::metric_names;
+use crate::telemetry::{increment, stream_event, Rpc, StreamEvent};
 
 pub async fn receive_input_messages(
     auth_builder: Arc<impl IntoAuthBuilder>,
-    twin_id: &str,
-    input_id: &str,
+    input: &InputRef,
 ) -> Result<mpsc::Receiver<Result<Vec<u8>, anyhow::Error>>, anyhow::Error> {
-    let channel = create_channel(auth_builder.clone(), None, None, None).await?;
-    receive_input_messages_with_channel(auth_builder, channel, twin_id, input_id).await
+    let channel = create_channel(
+        auth_builder.clone(),
+        auth_builder.get_config().channel_options,
+    )
+    .await?;
+    receive_input_messages_with_channel(auth_builder, channel, input).await
 }
 
 pub async fn receive_input_messages_with_channel(
     auth_builder: Arc<impl IntoAuthBuilder>,
     channel: Channel,
-    twin_id: &str,
-    input_id: &str,
+    input: &InputRef,
 ) -> Result<mpsc::Receiver<Result<Vec<u8>, anyhow::Error>>, anyhow::Error> {
-    let mut client = InputApiClient::new(channel);
-    let client_app_id = generate_client_app_id();
-    let transaction_ref = vec![client_app_id.clone()];
-
-    let headers = Headers {
-        client_app_id,
-        transaction_ref,
-        ..Default::default()
-    };
+    let config = auth_builder.get_config();
+    let mut client = InputApiClient::new(channel).configure(&config.channel_options);
+    let headers = CallContext::headers(config.call_context);
+    let transaction_ref = headers.transaction_ref.clone();
+
+    let rpc = Rpc::new("InputAPI", "ReceiveInputMessages", &headers).twin(input.twin.as_str());
 
     let mut request = tonic::Request::new(ReceiveInputMessageRequest {
         headers: Some(headers),
         args: Some(receive_input_message_request::Arguments {
-            input_id: Some(InputId {
-                id: input_id.to_string(),
-                twin_id: twin_id.to_string(),
-                ..Default::default()
-            }),
+            input_id: Some(InputId::from(input)),
         }),
     });
 
@@ -61,16 +59,45 @@ pub async fn receive_input_messages_with_channel(
         token.parse().context("parse token failed")?,
     );
 
+    rpc.inject(&mut request);
+
     let (tx, rx) = mpsc::channel::<Result<Vec<u8>, anyhow::Error>>(16384);
 
     let fut = async move {
-        let stream = client.receive_input_messages(request).await;
+        let stream = rpc.run(client.receive_input_messages(request)).await;
 
         match stream {
             Ok(mut stream) => {
+                stream_event(
+                    "receive_input_messages",
+                    &transaction_ref,
+                    StreamEvent::Opened,
+                );
+
                 let stream = stream.get_mut();
 
-                while let Ok(Some(response)) = stream.message().await {
+                loop {
+                    let response = match stream.message().await {
+                        Ok(Some(response)) => response,
+                        Ok(None) => break,
+                        Err(e) => {
+                            stream_event(
+                                "receive_input_messages",
+                                &transaction_ref,
+                                StreamEvent::Failed(&e),
+                            );
+                            let _ = tx.send(Err(e.into())).await;
+                            return;
+                        }
+                    };
+
+                    stream_event(
+                        "receive_input_messages",
+                        &transaction_ref,
+                        StreamEvent::Message,
+                    );
+                    increment(metric_names::INPUT_MESSAGES_RECEIVED_TOTAL, 1);
+
                     match response.payload {
                         Some(payload) => {
                             match payload.message {
@@ -101,8 +128,19 @@ pub async fn receive_input_messages_with_channel(
                         }
                     };
                 }
+
+                stream_event(
+                    "receive_input_messages",
+                    &transaction_ref,
+                    StreamEvent::Closed,
+                );
             }
             Err(e) => {
+                stream_event(
+                    "receive_input_messages",
+                    &transaction_ref,
+                    StreamEvent::Failed(&e),
+                );
                 let _ = tx.send(Err(e.into())).await;
             }
         }
@@ -114,39 +152,32 @@ pub async fn receive_input_messages_with_channel(
 
 pub async fn describe_input(
     auth_builder: Arc<impl IntoAuthBuilder>,
-    twin_id: &str,
-    input_id: &str,
-    remote_host_id: Option<&str>,
+    input: &InputRef,
 ) -> Result<DescribeInputResponse, anyhow::Error> {
-    let channel = create_channel(auth_builder.clone(), None, None, None).await?;
-    describe_input_with_channel(auth_builder, channel, twin_id, input_id, remote_host_id).await
+    let channel = create_channel(
+        auth_builder.clone(),
+        auth_builder.get_config().channel_options,
+    )
+    .await?;
+    describe_input_with_channel(auth_builder, channel, input).await
 }
 
 pub async fn describe_input_with_channel(
     auth_builder: Arc<impl IntoAuthBuilder>,
     channel: Channel,
-    twin_id: &str,
-    input_id: &str,
-    remote_host_id: Option<&str>,
+    input: &InputRef,
 ) -> Result<DescribeInputResponse, anyhow::Error> {
-    let mut client = InputApiClient::new(channel);
-    let client_app_id = generate_client_app_id();
-    let transaction_ref = vec![client_app_id.clone()];
-
-    let headers = Headers {
-        client_app_id,
-        transaction_ref: transaction_ref.clone(),
-        ..Default::default()
-    };
+    let config = auth_builder.get_config();
+    let mut client = InputApiClient::new(channel).configure(&config.channel_options);
+    let headers = CallContext::headers(config.call_context);
+    let transaction_ref = headers.transaction_ref.clone();
+
+    let rpc = Rpc::new("InputAPI", "DescribeInput", &headers).twin(input.twin.as_str());
 
     let mut request = tonic::Request::new(DescribeInputRequest {
         headers: Some(headers),
         args: Some(describe_input_request::Arguments {
-            input_id: Some(InputId {
-                id: input_id.to_string(),
-                twin_id: twin_id.to_string(),
-                host_id: remote_host_id.unwrap_or_default().to_string(),
-            }),
+            input_id: Some(InputId::from(input)),
         }),
     });
 
@@ -157,12 +188,17 @@ pub async fn describe_input_with_channel(
         token.parse().context("parse token failed")?,
     );
 
-    let result = client.describe_input(request).await.with_context(|| {
-        format!(
-            "Describing input failed, transaction ref [{}]",
-            transaction_ref.join(", ")
-        )
-    })?;
+    rpc.inject(&mut request);
+
+    let result = rpc
+        .run(client.describe_input(request))
+        .await
+        .with_context(|| {
+            format!(
+                "Describing input failed, transaction ref [{}]",
+                transaction_ref.join(", ")
+            )
+        })?;
     let result = result.into_inner();
 
     Ok(result)
@@ -170,37 +206,32 @@ pub async fn describe_input_with_channel(
 
 pub async fn delete_input(
     auth_builder: Arc<impl IntoAuthBuilder>,
-    twin_id: &str,
-    input_id: &str,
+    input: &InputRef,
 ) -> Result<DeleteInputResponse, anyhow::Error> {
-    let channel = create_channel(auth_builder.clone(), None, None, None).await?;
-    delete_input_with_client(auth_builder, channel, twin_id, input_id).await
+    let channel = create_channel(
+        auth_builder.clone(),
+        auth_builder.get_config().channel_options,
+    )
+    .await?;
+    delete_input_with_client(auth_builder, channel, input).await
 }
 
 pub async fn delete_input_with_client(
     auth_builder: Arc<impl IntoAuthBuilder>,
     channel: Channel,
-    twin_id: &str,
-    input_id: &str,
+    input: &InputRef,
 ) -> Result<DeleteInputResponse, anyhow::Error> {
-    let mut client = InputApiClient::new(channel);
-    let client_app_id = generate_client_app_id();
-    let transaction_ref = vec![client_app_id.clone()];
-
-    let headers = Headers {
-        client_app_id,
-        transaction_ref: transaction_ref.clone(),
-        ..Default::default()
-    };
+    let config = auth_builder.get_config();
+    let mut client = InputApiClient::new(channel).configure(&config.channel_options);
+    let headers = CallContext::headers(config.call_context);
+    let transaction_ref = headers.transaction_ref.clone();
+
+    let rpc = Rpc::new("InputAPI", "DeleteInput", &headers).twin(input.twin.as_str());
 
     let mut request = tonic::Request::new(DeleteInputRequest {
         headers: Some(headers),
         args: Some(delete_input_request::Arguments {
-            input_id: Some(InputId {
-                id: input_id.to_string(),
-                twin_id: twin_id.to_string(),
-                ..Default::default()
-            }),
+            input_id: Some(InputId::from(input)),
         }),
     });
 
@@ -211,12 +242,17 @@ pub async fn delete_input_with_client(
         token.parse().context("parse token failed")?,
     );
 
-    let result = client.delete_input(request).await.with_context(|| {
-        format!(
-            "Deleting input failed, transaction ref [{}]",
-            transaction_ref.join(", ")
-        )
-    })?;
+    rpc.inject(&mut request);
+
+    let result = rpc
+        .run(client.delete_input(request))
+        .await
+        .with_context(|| {
+            format!(
+                "Deleting input failed, transaction ref [{}]",
+                transaction_ref.join(", ")
+            )
+        })?;
     let result = result.into_inner();
 
     Ok(result)
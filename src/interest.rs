@@ -1,5 +1,8 @@
 use anyhow::Context;
+use futures::Stream;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::task::{Context as TaskContext, Poll};
 use std::time::SystemTime;
 use tonic::transport::Channel;
 use tonic::Streaming;
@@ -18,24 +21,27 @@ pub use crate::client::iotics::api::{
 };
 
 use crate::auth_builder::IntoAuthBuilder;
-use crate::channel::create_channel;
-use crate::helpers::generate_client_app_id;
+use crate::call_context::CallContext;
+use crate::channel::{create_channel, ConfigureClient};
+use crate::ids::{FeedRef, InputRef, TwinDid};
+use crate::metric_names;
+use crate::telemetry::{increment, stream_event, Rpc, StreamEvent};
 
 pub async fn follow(
     auth_builder: Arc<impl IntoAuthBuilder>,
-    followed_host_id: Option<&str>,
-    followed_twin_id: &str,
-    followed_feed_id: &str,
-    follower_twin_id: &str,
+    followed_feed: &FeedRef,
+    follower_twin_id: &TwinDid,
     fetch_last_stored: bool,
-) -> Result<Streaming<FetchInterestResponse>, anyhow::Error> {
-    let channel = create_channel(auth_builder.clone(), None, None, None).await?;
+) -> Result<FollowStream, anyhow::Error> {
+    let channel = create_channel(
+        auth_builder.clone(),
+        auth_builder.get_config().channel_options,
+    )
+    .await?;
     follow_with_channel(
         auth_builder,
         channel,
-        followed_host_id,
-        followed_twin_id,
-        followed_feed_id,
+        followed_feed,
         follower_twin_id,
         fetch_last_stored,
     )
@@ -45,35 +51,24 @@ pub async fn follow(
 pub async fn follow_with_channel(
     auth_builder: Arc<impl IntoAuthBuilder>,
     channel: Channel,
-    followed_host_id: Option<&str>,
-    followed_twin_id: &str,
-    followed_feed_id: &str,
-    follower_twin_id: &str,
+    followed_feed: &FeedRef,
+    follower_twin_id: &TwinDid,
     fetch_last_stored: bool,
-) -> Result<Streaming<FetchInterestResponse>, anyhow::Error> {
-    let mut client = InterestApiClient::new(channel);
-    let client_app_id = generate_client_app_id();
-    let transaction_ref = vec![client_app_id.clone()];
-
-    let headers = Headers {
-        client_app_id,
-        transaction_ref: transaction_ref.clone(),
-        ..Default::default()
-    };
-
-    let follower_twin_id_arg = TwinId {
-        id: follower_twin_id.to_string(),
-        ..Default::default()
-    };
+) -> Result<FollowStream, anyhow::Error> {
+    let config = auth_builder.get_config();
+    let mut client = InterestApiClient::new(channel).configure(&config.channel_options);
+    let headers = CallContext::headers(config.call_context);
+    let transaction_ref = headers.transaction_ref.clone();
 
     let interest = Interest {
-        follower_twin_id: Some(follower_twin_id_arg),
-        followed_feed_id: Some(FeedId {
-            id: followed_feed_id.to_string(),
-            twin_id: followed_twin_id.to_string(),
-            host_id: followed_host_id.unwrap_or_default().to_string(),
-        }),
+        follower_twin_id: Some(TwinId::from(follower_twin_id)),
+        followed_feed_id: Some(FeedId::from(followed_feed)),
     };
+
+    let rpc = Rpc::new("InterestAPI", "FetchInterests", &headers)
+        .twin(follower_twin_id.as_str())
+        .feed(&followed_feed.feed);
+
     let mut request = tonic::Request::new(FetchInterestRequest {
         headers: Some(headers),
         args: Some(FetchInterestArguments {
@@ -91,8 +86,10 @@ pub async fn follow_with_channel(
         token.parse().context("parse token failed")?,
     );
 
-    let stream = client
-        .fetch_interests(request)
+    rpc.inject(&mut request);
+
+    let stream = rpc
+        .run(client.fetch_interests(request))
         .await
         .with_context(|| {
             format!(
@@ -102,64 +99,95 @@ pub async fn follow_with_channel(
         })?
         .into_inner();
 
-    Ok(stream)
+    stream_event("follow", &transaction_ref, StreamEvent::Opened);
+    increment(metric_names::FOLLOW_STREAMS_OPENED_TOTAL, 1);
+
+    Ok(FollowStream {
+        inner: stream,
+        transaction_ref,
+    })
+}
+
+/// The samples shared by a followed feed, see [`follow`]. The samples received are recorded in the
+/// metrics and the lifecycle of the stream is logged.
+pub struct FollowStream {
+    inner: Streaming<FetchInterestResponse>,
+    transaction_ref: Vec<String>,
+}
+
+impl FollowStream {
+    /// The next sample, `None` once the host closed the stream.
+    pub async fn message(&mut self) -> Result<Option<FetchInterestResponse>, tonic::Status> {
+        let message = self.inner.message().await;
+        self.record(message.as_ref().map(Option::is_some));
+        message
+    }
+
+    /// The underlying gRPC stream, its samples aren't recorded.
+    pub fn into_inner(self) -> Streaming<FetchInterestResponse> {
+        self.inner
+    }
+
+    fn record(&self, received: Result<bool, &tonic::Status>) {
+        match received {
+            Ok(true) => {
+                stream_event("follow", &self.transaction_ref, StreamEvent::Message);
+                increment(metric_names::FEED_MESSAGES_RECEIVED_TOTAL, 1);
+            }
+            Ok(false) => stream_event("follow", &self.transaction_ref, StreamEvent::Closed),
+            Err(e) => stream_event("follow", &self.transaction_ref, StreamEvent::Failed(e)),
+        }
+    }
+}
+
+impl Stream for FollowStream {
+    type Item = Result<FetchInterestResponse, tonic::Status>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Option<Self::Item>> {
+        let poll = Pin::new(&mut self.inner).poll_next(cx);
+
+        if let Poll::Ready(item) = &poll {
+            match item {
+                Some(Ok(_)) => self.record(Ok(true)),
+                Some(Err(e)) => self.record(Err(e)),
+                None => self.record(Ok(false)),
+            }
+        }
+
+        poll
+    }
 }
 
 pub async fn send_input_message<T: Into<Vec<u8>>>(
     auth_builder: Arc<impl IntoAuthBuilder>,
-    receiver_host_id: Option<&str>,
-    receiver_twin_id: &str,
-    input_id: &str,
-    sender_twin_id: &str,
+    receiver_input: &InputRef,
+    sender_twin_id: &TwinDid,
     data: T,
 ) -> Result<(), anyhow::Error> {
-    let channel = create_channel(auth_builder.clone(), None, None, None).await?;
-    send_input_message_with_channel(
-        auth_builder,
-        channel,
-        receiver_host_id,
-        receiver_twin_id,
-        input_id,
-        sender_twin_id,
-        data,
+    let channel = create_channel(
+        auth_builder.clone(),
+        auth_builder.get_config().channel_options,
     )
-    .await
+    .await?;
+    send_input_message_with_channel(auth_builder, channel, receiver_input, sender_twin_id, data)
+        .await
 }
 
-#[allow(clippy::too_many_arguments)]
 pub async fn send_input_message_with_channel<T: Into<Vec<u8>>>(
     auth_builder: Arc<impl IntoAuthBuilder>,
     channel: Channel,
-    receiver_host_id: Option<&str>,
-    receiver_twin_id: &str,
-    input_id: &str,
-    sender_twin_id: &str,
+    receiver_input: &InputRef,
+    sender_twin_id: &TwinDid,
     data: T,
 ) -> Result<(), anyhow::Error> {
-    let mut client = InterestApiClient::new(channel);
-    let client_app_id = generate_client_app_id();
-    let transaction_ref = vec![client_app_id.clone()];
-
-    let headers = Headers {
-        client_app_id,
-        transaction_ref: transaction_ref.clone(),
-        ..Default::default()
-    };
-
-    let sender_twin_id = TwinId {
-        id: sender_twin_id.to_string(),
-        ..Default::default()
-    };
-
-    let dest_input_id = InputId {
-        id: input_id.to_string(),
-        twin_id: receiver_twin_id.to_string(),
-        host_id: receiver_host_id.unwrap_or_default().to_string(),
-    };
+    let config = auth_builder.get_config();
+    let mut client = InterestApiClient::new(channel).configure(&config.channel_options);
+    let headers = CallContext::headers(config.call_context);
+    let transaction_ref = headers.transaction_ref.clone();
 
     let interest = InputInterest {
-        dest_input_id: Some(dest_input_id),
-        sender_twin_id: Some(sender_twin_id),
+        dest_input_id: Some(InputId::from(receiver_input)),
+        sender_twin_id: Some(TwinId::from(sender_twin_id)),
     };
 
     let args = SendMessageArguments {
@@ -180,6 +208,9 @@ pub async fn send_input_message_with_channel<T: Into<Vec<u8>>>(
         }),
     };
 
+    let rpc =
+        Rpc::new("InterestAPI", "SendInputMessage", &headers).twin(receiver_input.twin.as_str());
+
     let mut request = tonic::Request::new(SendInputMessageRequest {
         headers: Some(headers.clone()),
         args: Some(args.clone()),
@@ -192,12 +223,17 @@ pub async fn send_input_message_with_channel<T: Into<Vec<u8>>>(
         "authorization",
         token.parse().context("parse token failed")?,
     );
-    if let Err(e) = client.send_input_message(request).await {
+
+    rpc.inject(&mut request);
+
+    if let Err(e) = rpc.run(client.send_input_message(request)).await {
         return Err(anyhow::Error::new(e).context(format!(
             "Sending message failed, transaction ref [{}]",
             transaction_ref.join(", ")
         )));
     }
 
+    increment(metric_names::INPUT_MESSAGES_SENT_TOTAL, 1);
+
     Ok(())
 }
@@ -0,0 +1,699 @@
+use anyhow::Context;
+use serde_json::{json, Map, Value as JsonValue};
+use std::collections::BTreeMap;
+
+use crate::client::iotics::api::property::Value;
+use crate::client::iotics::api::{
+    DescribeInputResponse, GeoLocation, Property, Value as FeedValue,
+};
+
+use crate::properties::common_keys::predicate::{
+    COMMENT, LABEL, LAT_PROPERTY, LONG_PROPERTY, RDF_TYPE_PROPERTY,
+};
+use crate::properties::literal::{data_type_iri, XSD_NAMESPACE};
+use crate::properties::{data_type, PropertyBuilder};
+use crate::search::TwinDetails as SearchTwinDetails;
+use crate::twin::{DescribeFeedResponse, DescribeTwinResponse, TwinDetails as ListTwinDetails};
+use crate::vocab;
+
+/// The prefixes used to compact IRIs in the `@context` of the generated documents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonLdContext {
+    prefixes: BTreeMap<String, String>,
+}
+
+impl Default for JsonLdContext {
+    /// Prefixes for `rdf`, `rdfs`, `xsd`, `geo`, `app` (`https://data.iotics.com/app#`) and
+    /// `client` (this crate's [`vocab`]).
+    fn default() -> Self {
+        Self::empty()
+            .with_prefix("rdf", "http://www.w3.org/1999/02/22-rdf-syntax-ns#")
+            .with_prefix("rdfs", "http://www.w3.org/2000/01/rdf-schema#")
+            .with_prefix("xsd", XSD_NAMESPACE)
+            .with_prefix("geo", "http://www.w3.org/2003/01/geo/wgs84_pos#")
+            .with_prefix("app", "https://data.iotics.com/app#")
+            .with_prefix("client", vocab::NAMESPACE)
+    }
+}
+
+impl JsonLdContext {
+    /// A context without any prefix, all the IRIs are kept expanded.
+    pub fn empty() -> Self {
+        Self {
+            prefixes: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_prefix(mut self, prefix: &str, namespace: &str) -> Self {
+        self.prefixes
+            .insert(prefix.to_string(), namespace.to_string());
+        self
+    }
+
+    /// Read the prefixes of a JSON-LD `@context`. Only plain `"prefix": "namespace"` and
+    /// `"term": { "@id": "iri" }` definitions are supported.
+    pub fn from_json(context: &JsonValue) -> Result<Self, anyhow::Error> {
+        let mut result = Self::empty();
+
+        let contexts = match context {
+            JsonValue::Array(contexts) => contexts.iter().collect::<Vec<_>>(),
+            context => vec![context],
+        };
+
+        for context in contexts {
+            let context = context
+                .as_object()
+                .context("only inline JSON-LD contexts are supported")?;
+
+            for (term, definition) in context {
+                let iri = match definition {
+                    JsonValue::String(iri) => iri.as_str(),
+                    JsonValue::Object(definition) => {
+                        match definition.get("@id").and_then(JsonValue::as_str) {
+                            Some(iri) => iri,
+                            None => continue,
+                        }
+                    }
+                    _ => continue,
+                };
+
+                result = result.with_prefix(term, iri);
+            }
+        }
+
+        Ok(result)
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        JsonValue::Object(
+            self.prefixes
+                .iter()
+                .map(|(prefix, namespace)| (prefix.clone(), JsonValue::String(namespace.clone())))
+                .collect(),
+        )
+    }
+
+    /// Compact `iri` with the longest matching prefix.
+    pub fn compact(&self, iri: &str) -> String {
+        self.prefixes
+            .iter()
+            .filter_map(|(prefix, namespace)| {
+                iri.strip_prefix(namespace.as_str())
+                    .filter(|local| !local.is_empty())
+                    .map(|local| (namespace.len(), format!("{}:{}", prefix, local)))
+            })
+            .max_by_key(|(length, _)| *length)
+            .map(|(_, compacted)| compacted)
+            .unwrap_or_else(|| iri.to_string())
+    }
+
+    /// Expand a term or a compact IRI, absolute IRIs are returned unchanged.
+    pub fn expand(&self, term: &str) -> String {
+        if let Some(iri) = self.prefixes.get(term) {
+            return iri.clone();
+        }
+
+        match term.split_once(':') {
+            Some((prefix, local)) if !local.starts_with("//") => match self.prefixes.get(prefix) {
+                Some(namespace) => format!("{}{}", namespace, local),
+                None => term.to_string(),
+            },
+            _ => term.to_string(),
+        }
+    }
+}
+
+struct EntityView<'a> {
+    iri: String,
+    types: Vec<&'a str>,
+    id: Option<(&'a str, &'a str)>,
+    store_last: Option<bool>,
+    location: Option<&'a GeoLocation>,
+    properties: &'a [Property],
+    values: &'a [FeedValue],
+    children: Vec<(&'a str, EntityView<'a>)>,
+}
+
+impl<'a> EntityView<'a> {
+    fn twin(
+        iri: &'a str,
+        location: Option<&'a GeoLocation>,
+        properties: &'a [Property],
+    ) -> EntityView<'a> {
+        EntityView {
+            iri: iri.to_string(),
+            types: Vec::new(),
+            id: None,
+            store_last: None,
+            location,
+            properties,
+            values: &[],
+            children: Vec::new(),
+        }
+    }
+
+    fn feed(
+        twin_iri: &str,
+        feed_id: &'a str,
+        store_last: bool,
+        properties: &'a [Property],
+        values: &'a [FeedValue],
+    ) -> EntityView<'a> {
+        EntityView {
+            iri: vocab::feed_iri(twin_iri, feed_id),
+            types: vec![vocab::FEED],
+            id: Some((vocab::FEED_ID, feed_id)),
+            store_last: Some(store_last),
+            location: None,
+            properties,
+            values,
+            children: Vec::new(),
+        }
+    }
+
+    fn input(
+        twin_iri: &str,
+        input_id: &'a str,
+        properties: &'a [Property],
+        values: &'a [FeedValue],
+    ) -> EntityView<'a> {
+        EntityView {
+            iri: vocab::input_iri(twin_iri, input_id),
+            types: vec![vocab::INPUT],
+            id: Some((vocab::INPUT_ID, input_id)),
+            store_last: None,
+            location: None,
+            properties,
+            values,
+            children: Vec::new(),
+        }
+    }
+
+    fn to_json(&self, context: &JsonLdContext) -> JsonValue {
+        let mut node = Map::new();
+        let mut types = self
+            .types
+            .iter()
+            .map(|iri| context.compact(iri))
+            .collect::<Vec<_>>();
+
+        node.insert("@id".to_string(), JsonValue::String(self.iri.clone()));
+
+        if let Some((key, id)) = self.id {
+            insert(&mut node, context.compact(key), json!(id));
+        }
+
+        if let Some(store_last) = self.store_last {
+            insert(
+                &mut node,
+                context.compact(vocab::STORE_LAST),
+                json!(store_last),
+            );
+        }
+
+        if let Some(location) = self.location {
+            insert(
+                &mut node,
+                context.compact(LAT_PROPERTY),
+                json!(location.lat),
+            );
+            insert(
+                &mut node,
+                context.compact(LONG_PROPERTY),
+                json!(location.lon),
+            );
+        }
+
+        for property in self.properties {
+            match &property.value {
+                Some(Value::UriValue(uri)) if property.key == RDF_TYPE_PROPERTY => {
+                    types.push(context.compact(&uri.value));
+                }
+                Some(value) => {
+                    insert(
+                        &mut node,
+                        context.compact(&property.key),
+                        value_to_json(value, context),
+                    );
+                }
+                None => {}
+            }
+        }
+
+        for value in self.values {
+            insert(
+                &mut node,
+                context.compact(vocab::HAS_VALUE),
+                feed_value_to_json(&self.iri, value, context),
+            );
+        }
+
+        for (key, child) in &self.children {
+            insert(&mut node, context.compact(key), child.to_json(context));
+        }
+
+        match types.len() {
+            0 => {}
+            1 => {
+                node.insert("@type".to_string(), JsonValue::String(types.remove(0)));
+            }
+            _ => {
+                node.insert("@type".to_string(), json!(types));
+            }
+        }
+
+        JsonValue::Object(node)
+    }
+
+    fn to_document(&self, context: &JsonLdContext) -> JsonValue {
+        let mut document = match self.to_json(context) {
+            JsonValue::Object(node) => node,
+            _ => unreachable!("entities are always serialized as objects"),
+        };
+
+        document.insert("@context".to_string(), context.to_json());
+
+        JsonValue::Object(document)
+    }
+}
+
+/// Insert a value under `key`, turning the entry into an array when the key is repeated.
+fn insert(node: &mut Map<String, JsonValue>, key: String, value: JsonValue) {
+    match node.get_mut(&key) {
+        Some(JsonValue::Array(values)) => values.push(value),
+        Some(existing) => {
+            let existing = existing.take();
+            node.insert(key, JsonValue::Array(vec![existing, value]));
+        }
+        None => {
+            node.insert(key, value);
+        }
+    }
+}
+
+fn value_to_json(value: &Value, context: &JsonLdContext) -> JsonValue {
+    match value {
+        Value::UriValue(uri) => json!({ "@id": context.compact(&uri.value) }),
+        Value::StringLiteralValue(literal) => json!(literal.value),
+        Value::LangLiteralValue(literal) if literal.lang.is_empty() => json!(literal.value),
+        Value::LangLiteralValue(literal) => json!({
+            "@value": literal.value,
+            "@language": literal.lang,
+        }),
+        Value::LiteralValue(literal) if literal.data_type.is_empty() => json!(literal.value),
+        Value::LiteralValue(literal) => json!({
+            "@value": literal.value,
+            "@type": context.compact(&data_type_iri(&literal.data_type)),
+        }),
+    }
+}
+
+fn feed_value_to_json(parent_iri: &str, value: &FeedValue, context: &JsonLdContext) -> JsonValue {
+    let mut node = Map::new();
+
+    node.insert(
+        "@id".to_string(),
+        json!(vocab::value_iri(parent_iri, &value.label)),
+    );
+    node.insert("@type".to_string(), json!(context.compact(vocab::VALUE)));
+    node.insert(context.compact(LABEL), json!(value.label));
+
+    if !value.comment.is_empty() {
+        node.insert(context.compact(COMMENT), json!(value.comment));
+    }
+
+    if !value.unit.is_empty() {
+        node.insert(
+            context.compact(vocab::VALUE_UNIT),
+            json!({ "@id": context.compact(&value.unit) }),
+        );
+    }
+
+    if !value.data_type.is_empty() {
+        node.insert(
+            context.compact(vocab::VALUE_DATA_TYPE),
+            json!(value.data_type),
+        );
+    }
+
+    JsonValue::Object(node)
+}
+
+/// JSON-LD document of a described twin, including its feeds, inputs and their values.
+pub fn describe_twin_to_json_ld(
+    twin: &DescribeTwinResponse,
+    context: &JsonLdContext,
+) -> Result<JsonValue, anyhow::Error> {
+    let payload = twin.payload.as_ref().context("failed to find payload")?;
+    let twin_id = payload.twin_id.as_ref().context("failed to find twin id")?;
+
+    let mut view = EntityView::twin(&twin_id.id, payload.location.as_ref(), &payload.properties);
+
+    for feed in &payload.feeds {
+        let feed_id = feed.feed_id.as_ref().context("failed to find feed id")?;
+        view.children.push((
+            vocab::HAS_FEED,
+            EntityView::feed(
+                &twin_id.id,
+                &feed_id.id,
+                feed.store_last,
+                &feed.properties,
+                &feed.values,
+            ),
+        ));
+    }
+
+    for input in &payload.inputs {
+        let input_id = input.input_id.as_ref().context("failed to find input id")?;
+        view.children.push((
+            vocab::HAS_INPUT,
+            EntityView::input(&twin_id.id, &input_id.id, &input.properties, &input.values),
+        ));
+    }
+
+    Ok(view.to_document(context))
+}
+
+/// JSON-LD document of a twin found by a search, including its feeds and inputs.
+pub fn search_twin_to_json_ld(
+    twin: &SearchTwinDetails,
+    context: &JsonLdContext,
+) -> Result<JsonValue, anyhow::Error> {
+    let twin_id = twin.twin_id.as_ref().context("failed to find twin id")?;
+
+    let mut view = EntityView::twin(&twin_id.id, twin.location.as_ref(), &twin.properties);
+
+    for feed in &twin.feeds {
+        let feed_id = feed.feed_id.as_ref().context("failed to find feed id")?;
+        view.children.push((
+            vocab::HAS_FEED,
+            EntityView::feed(
+                &twin_id.id,
+                &feed_id.id,
+                feed.store_last,
+                &feed.properties,
+                &feed.values,
+            ),
+        ));
+    }
+
+    for input in &twin.inputs {
+        let input_id = input.input_id.as_ref().context("failed to find input id")?;
+        view.children.push((
+            vocab::HAS_INPUT,
+            EntityView::input(&twin_id.id, &input_id.id, &input.properties, &input.values),
+        ));
+    }
+
+    Ok(view.to_document(context))
+}
+
+/// JSON-LD document of a twin returned by `list_all_twins`.
+pub fn list_twin_to_json_ld(
+    twin: &ListTwinDetails,
+    context: &JsonLdContext,
+) -> Result<JsonValue, anyhow::Error> {
+    let twin_id = twin.twin_id.as_ref().context("failed to find twin id")?;
+    let view = EntityView::twin(&twin_id.id, twin.location.as_ref(), &twin.properties);
+
+    Ok(view.to_document(context))
+}
+
+/// JSON-LD document of a described feed and its values.
+pub fn describe_feed_to_json_ld(
+    feed: &DescribeFeedResponse,
+    context: &JsonLdContext,
+) -> Result<JsonValue, anyhow::Error> {
+    let payload = feed.payload.as_ref().context("failed to find payload")?;
+    let feed_id = payload.feed_id.as_ref().context("failed to find feed id")?;
+    let result = payload.result.as_ref().context("failed to find result")?;
+
+    let view = EntityView::feed(
+        &feed_id.twin_id,
+        &feed_id.id,
+        result.store_last,
+        &result.properties,
+        &result.values,
+    );
+
+    Ok(view.to_document(context))
+}
+
+/// JSON-LD document of a described input and its values.
+pub fn describe_input_to_json_ld(
+    input: &DescribeInputResponse,
+    context: &JsonLdContext,
+) -> Result<JsonValue, anyhow::Error> {
+    let payload = input.payload.as_ref().context("failed to find payload")?;
+    let input_id = payload
+        .input_id
+        .as_ref()
+        .context("failed to find input id")?;
+    let result = payload.result.as_ref().context("failed to find result")?;
+
+    let view = EntityView::input(
+        &input_id.twin_id,
+        &input_id.id,
+        &result.properties,
+        &result.values,
+    );
+
+    Ok(view.to_document(context))
+}
+
+/// Build the properties of the top level node of a JSON-LD document, expanding the compact IRIs
+/// with the document `@context`. Nested nodes are mapped to `Uri` properties of their `@id`.
+pub fn properties_from_json_ld(document: &JsonValue) -> Result<Vec<Property>, anyhow::Error> {
+    let node = document
+        .as_object()
+        .context("the JSON-LD document must be an object")?;
+
+    let context = match node.get("@context") {
+        Some(context) => JsonLdContext::from_json(context)?,
+        None => JsonLdContext::empty(),
+    };
+
+    let mut properties = Vec::new();
+
+    for (key, value) in node {
+        match key.as_str() {
+            "@type" => {
+                for value in as_array(value) {
+                    let iri = value.as_str().context("@type values must be strings")?;
+                    properties.push(PropertyBuilder::build_uri_value(
+                        RDF_TYPE_PROPERTY,
+                        &context.expand(iri),
+                    ));
+                }
+            }
+            key if key.starts_with('@') => {}
+            key => {
+                let key = context.expand(key);
+
+                for value in as_array(value) {
+                    properties.push(json_to_property(&key, value, &context)?);
+                }
+            }
+        }
+    }
+
+    Ok(properties)
+}
+
+fn as_array(value: &JsonValue) -> Vec<&JsonValue> {
+    match value {
+        JsonValue::Array(values) => values.iter().collect(),
+        value => vec![value],
+    }
+}
+
+fn json_to_property(
+    key: &str,
+    value: &JsonValue,
+    context: &JsonLdContext,
+) -> Result<Property, anyhow::Error> {
+    let property = match value {
+        JsonValue::String(value) => PropertyBuilder::build_string_literal_value(key, value),
+        JsonValue::Bool(value) => PropertyBuilder::literal(key, *value),
+        JsonValue::Number(number) if number.is_i64() || number.is_u64() => {
+            PropertyBuilder::build_literal_value(key, data_type::INTEGER, &number.to_string())
+        }
+        JsonValue::Number(number) => {
+            PropertyBuilder::build_literal_value(key, data_type::DOUBLE, &number.to_string())
+        }
+        JsonValue::Object(node) => {
+            if let Some(iri) = node.get("@id").and_then(JsonValue::as_str) {
+                PropertyBuilder::build_uri_value(key, &context.expand(iri))
+            } else {
+                let literal = match node.get("@value") {
+                    Some(JsonValue::String(value)) => value.clone(),
+                    Some(JsonValue::Null) | None => {
+                        anyhow::bail!("missing @id or @value for [{}]", key)
+                    }
+                    Some(value) => value.to_string(),
+                };
+
+                let language = node.get("@language").and_then(JsonValue::as_str);
+                let datatype = node
+                    .get("@type")
+                    .and_then(JsonValue::as_str)
+                    .map(|datatype| context.expand(datatype));
+
+                match (language, datatype) {
+                    (Some(language), _) => {
+                        PropertyBuilder::build_lang_literal(key, language, &literal)
+                    }
+                    (None, Some(datatype)) => match datatype.strip_prefix(XSD_NAMESPACE) {
+                        Some(data_type::STRING) => {
+                            PropertyBuilder::build_string_literal_value(key, &literal)
+                        }
+                        Some(short_name) => {
+                            PropertyBuilder::build_literal_value(key, short_name, &literal)
+                        }
+                        None => PropertyBuilder::build_literal_value(key, &datatype, &literal),
+                    },
+                    (None, None) => PropertyBuilder::build_string_literal_value(key, &literal),
+                }
+            }
+        }
+        JsonValue::Null | JsonValue::Array(_) => {
+            anyhow::bail!("unsupported JSON-LD value for [{}]", key)
+        }
+    };
+
+    Ok(property)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::iotics::api::TwinId;
+
+    const TWIN: &str = "did:iotics:iotHjrmKpPGWyEC4FFo4d6oyzVVk6MXLmEgY";
+
+    fn sorted(mut properties: Vec<Property>) -> Vec<Property> {
+        properties.sort_by_key(|property| format!("{:?}", property));
+        properties
+    }
+
+    fn twin(properties: Vec<Property>) -> ListTwinDetails {
+        ListTwinDetails {
+            twin_id: Some(TwinId {
+                id: TWIN.to_string(),
+                ..Default::default()
+            }),
+            properties,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn round_trips_the_properties_of_a_twin() {
+        let properties = vec![
+            PropertyBuilder::build_uri_value(RDF_TYPE_PROPERTY, "http://example.com/Sensor"),
+            PropertyBuilder::build_lang_literal(LABEL, "en", "Thermometer"),
+            PropertyBuilder::build_lang_literal(LABEL, "fr", "Thermomètre"),
+            PropertyBuilder::build_string_literal_value(COMMENT, "In the kitchen"),
+            PropertyBuilder::build_uri_value(
+                "http://example.com/room",
+                "http://example.com/kitchen",
+            ),
+            PropertyBuilder::build_literal_value(
+                "http://example.com/floor",
+                data_type::INTEGER,
+                "2",
+            ),
+            PropertyBuilder::build_literal_value(
+                "http://example.com/ratio",
+                data_type::DECIMAL,
+                "0.25",
+            ),
+            PropertyBuilder::literal("http://example.com/enabled", true),
+        ];
+
+        let document =
+            list_twin_to_json_ld(&twin(properties.clone()), &JsonLdContext::default()).unwrap();
+
+        assert_eq!(document["@id"], json!(TWIN));
+        assert_eq!(document["@type"], json!("http://example.com/Sensor"));
+        assert_eq!(document["rdfs:comment"], json!("In the kitchen"));
+
+        let parsed = properties_from_json_ld(&document).unwrap();
+
+        assert_eq!(sorted(parsed), sorted(properties));
+    }
+
+    #[test]
+    fn serializes_an_empty_language_as_a_plain_string() {
+        let properties = vec![PropertyBuilder::build_lang_literal(
+            LABEL,
+            "",
+            "Thermometer",
+        )];
+
+        let document = list_twin_to_json_ld(&twin(properties), &JsonLdContext::default()).unwrap();
+
+        assert_eq!(document["rdfs:label"], json!("Thermometer"));
+    }
+
+    #[test]
+    fn expands_the_prefixes_of_the_document_context() {
+        let document = json!({
+            "@context": {
+                "ex": "http://example.com/",
+                "name": { "@id": "http://example.com/name" },
+            },
+            "@type": ["ex:Sensor"],
+            "name": { "@value": "Thermometer", "@language": "en" },
+            "ex:unit": { "@id": "ex:celsius" },
+            "ex:min": -20,
+            "ex:max": 50.5,
+        });
+
+        let parsed = properties_from_json_ld(&document).unwrap();
+
+        let expected = vec![
+            PropertyBuilder::build_uri_value(RDF_TYPE_PROPERTY, "http://example.com/Sensor"),
+            PropertyBuilder::build_lang_literal("http://example.com/name", "en", "Thermometer"),
+            PropertyBuilder::build_uri_value(
+                "http://example.com/unit",
+                "http://example.com/celsius",
+            ),
+            PropertyBuilder::build_literal_value(
+                "http://example.com/min",
+                data_type::INTEGER,
+                "-20",
+            ),
+            PropertyBuilder::build_literal_value(
+                "http://example.com/max",
+                data_type::DOUBLE,
+                "50.5",
+            ),
+        ];
+
+        assert_eq!(sorted(parsed), sorted(expected));
+    }
+
+    #[test]
+    fn compacts_with_the_longest_prefix() {
+        let context = JsonLdContext::empty()
+            .with_prefix("ex", "http://example.com/")
+            .with_prefix("exv", "http://example.com/vocab#");
+
+        assert_eq!(context.compact("http://example.com/vocab#unit"), "exv:unit");
+        assert_eq!(context.compact("http://example.com/thing"), "ex:thing");
+        assert_eq!(
+            context.compact("http://example.com/"),
+            "http://example.com/"
+        );
+        assert_eq!(context.expand("exv:unit"), "http://example.com/vocab#unit");
+        assert_eq!(context.expand("http://other.com/a"), "http://other.com/a");
+    }
+
+    #[test]
+    fn rejects_a_value_without_id_or_value() {
+        let document = json!({ "http://example.com/name": { "@language": "en" } });
+
+        assert!(properties_from_json_ld(&document).is_err());
+    }
+}
@@ -2,20 +2,37 @@
 #![allow(clippy::derive_partial_eq_without_eq)]
 
 mod auth_builder;
+mod call_context;
 mod channel;
+mod client_config;
 mod common;
 mod helpers;
+mod ids;
+mod telemetry;
 
 include!(concat!(env!("OUT_DIR"), "/client/mod.rs"));
 
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(feature = "connector")]
+pub mod connector;
+pub mod health;
 pub mod host;
 pub mod input;
 pub mod interest;
+pub mod json_ld;
+pub mod metric_names;
 pub mod properties;
+#[cfg(feature = "rdf")]
+pub mod rdf;
 pub mod search;
 pub mod twin;
+pub mod vocab;
 
-// re-export everything from `auth_builder`, `channel` and `common` in the root of the crate
+// re-export everything from `auth_builder`, `call_context`, `channel`, `client_config`, `common` and `ids` in the root of the crate
 pub use auth_builder::*;
+pub use call_context::*;
 pub use channel::*;
+pub use client_config::*;
 pub use common::*;
+pub use ids::*;
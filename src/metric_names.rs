@@ -0,0 +1,59 @@
+//! Names of the metrics recorded through the [`metrics`](https://docs.rs/metrics) facade when the
+//! `metrics` feature is enabled. They're exported by the recorder installed by the application,
+//! e.g. `metrics-exporter-prometheus`.
+
+/// Histogram of the RPC latencies in seconds, labelled by `service`, `method` and `status`.
+pub const RPC_DURATION_SECONDS: &str = "iotics_rpc_duration_seconds";
+/// Counter of the RPCs, labelled by `service`, `method` and `status`.
+pub const RPC_REQUESTS_TOTAL: &str = "iotics_rpc_requests_total";
+/// Counter of the feed samples shared.
+pub const FEED_SAMPLES_SHARED_TOTAL: &str = "iotics_feed_samples_shared_total";
+/// Counter of the feed samples received by the followers and the `follow` streams.
+pub const FEED_MESSAGES_RECEIVED_TOTAL: &str = "iotics_feed_messages_received_total";
+/// Counter of the feeds followed.
+pub const FOLLOW_STREAMS_OPENED_TOTAL: &str = "iotics_follow_streams_opened_total";
+/// Counter of the feeds followed again after their stream failed or ended.
+pub const FOLLOW_RECONNECTS_TOTAL: &str = "iotics_follow_reconnects_total";
+/// Counter of the input messages sent.
+pub const INPUT_MESSAGES_SENT_TOTAL: &str = "iotics_input_messages_sent_total";
+/// Counter of the input messages received.
+pub const INPUT_MESSAGES_RECEIVED_TOTAL: &str = "iotics_input_messages_received_total";
+/// Counter of the search responses received, one per host and page.
+pub const SEARCH_RESPONSES_TOTAL: &str = "iotics_search_responses_total";
+/// Counter of the twins found by the searches.
+pub const SEARCH_TWINS_FOUND_TOTAL: &str = "iotics_search_twins_found_total";
+/// Counter of the twins created.
+pub const TWINS_CREATED_TOTAL: &str = "iotics_twins_created_total";
+/// Counter of the twins updated.
+pub const TWINS_UPDATED_TOTAL: &str = "iotics_twins_updated_total";
+/// Counter of the twins deleted.
+pub const TWINS_DELETED_TOTAL: &str = "iotics_twins_deleted_total";
+/// Gauge of the samples buffered by the feed publishers, labelled by `publisher`.
+pub const FEED_PUBLISHER_BUFFERED: &str = "iotics_feed_publisher_buffered";
+/// Gauge of the size in bytes of the outbox logs, labelled by `dir`.
+pub const OUTBOX_SIZE_BYTES: &str = "iotics_outbox_size_bytes";
+
+/// Register the descriptions of the metrics with the installed recorder.
+#[cfg(feature = "metrics")]
+pub fn describe_metrics() {
+    use metrics::{describe_counter, describe_gauge, describe_histogram, Unit};
+
+    describe_histogram!(RPC_DURATION_SECONDS, Unit::Seconds, "RPC latency");
+    describe_counter!(RPC_REQUESTS_TOTAL, "RPCs made");
+    describe_counter!(FEED_SAMPLES_SHARED_TOTAL, "Feed samples shared");
+    describe_counter!(FEED_MESSAGES_RECEIVED_TOTAL, "Feed samples received");
+    describe_counter!(FOLLOW_STREAMS_OPENED_TOTAL, "Feeds followed");
+    describe_counter!(FOLLOW_RECONNECTS_TOTAL, "Feeds followed again");
+    describe_counter!(INPUT_MESSAGES_SENT_TOTAL, "Input messages sent");
+    describe_counter!(INPUT_MESSAGES_RECEIVED_TOTAL, "Input messages received");
+    describe_counter!(SEARCH_RESPONSES_TOTAL, "Search responses received");
+    describe_counter!(SEARCH_TWINS_FOUND_TOTAL, "Twins found by searches");
+    describe_counter!(TWINS_CREATED_TOTAL, "Twins created");
+    describe_counter!(TWINS_UPDATED_TOTAL, "Twins updated");
+    describe_counter!(TWINS_DELETED_TOTAL, "Twins deleted");
+    describe_gauge!(
+        FEED_PUBLISHER_BUFFERED,
+        "Samples buffered by the feed publishers"
+    );
+    describe_gauge!(OUTBOX_SIZE_BYTES, Unit::Bytes, "Size of the outbox logs");
+}
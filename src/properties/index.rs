@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+
+use crate::client::iotics::api::property::Value;
+use crate::client::iotics::api::Property;
+
+use super::{common_keys, LiteralValue, PropertyFinderError};
+
+/// The properties of a twin indexed by key, built once for repeated lookups.
+///
+/// It offers the same lookups as [`PropertyFinder`](super::PropertyFinder) without scanning
+/// all the properties each time, e.g. when reading many keys from a large twin listing.
+#[derive(Debug, Clone, Default)]
+pub struct PropertyIndex<'a> {
+    values: HashMap<&'a str, Vec<&'a Value>>,
+}
+
+impl<'a> PropertyIndex<'a> {
+    pub fn new(properties: &'a [Property]) -> Self {
+        let mut values: HashMap<&'a str, Vec<&'a Value>> = HashMap::new();
+
+        for property in properties {
+            let entry = values.entry(property.key.as_str()).or_default();
+
+            if let Some(value) = &property.value {
+                entry.push(value);
+            }
+        }
+
+        Self { values }
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.values.contains_key(key)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.values.keys().copied()
+    }
+
+    pub fn filter_values_by_key(&self, key: &str) -> Result<&[&'a Value], PropertyFinderError> {
+        match self.values.get(key) {
+            Some(values) if !values.is_empty() => Ok(values),
+            Some(_) => Err(PropertyFinderError::ValueNotFound),
+            None => Err(PropertyFinderError::KeyNotFound),
+        }
+    }
+
+    /// All the values under `key`, empty when the key is missing.
+    pub fn find_values(&self, key: &str) -> &[&'a Value] {
+        self.values.get(key).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn find_string_literal_value(&self, key: &str) -> Result<&'a str, PropertyFinderError> {
+        super::string_literal_value(self.filter_values_by_key(key)?)
+    }
+
+    pub fn find_string_literal_values(&self, key: &str) -> Vec<&'a str> {
+        super::string_literal_values(self.find_values(key))
+    }
+
+    pub fn find_uri_value(&self, key: &str) -> Result<&'a str, PropertyFinderError> {
+        super::uri_value(self.filter_values_by_key(key)?)
+    }
+
+    pub fn find_uri_values(&self, key: &str) -> Vec<&'a str> {
+        super::uri_values(self.find_values(key))
+    }
+
+    pub fn find_literal_value(
+        &self,
+        key: &str,
+        data_type: &str,
+    ) -> Result<&'a str, PropertyFinderError> {
+        super::literal_value(self.filter_values_by_key(key)?, data_type)
+    }
+
+    pub fn find<T: LiteralValue>(&self, key: &str) -> Result<T, PropertyFinderError> {
+        super::typed_literal(self.filter_values_by_key(key)?)
+    }
+
+    pub fn find_all<T: LiteralValue>(&self, key: &str) -> Result<Vec<T>, PropertyFinderError> {
+        super::typed_literals(self.find_values(key))
+    }
+
+    pub fn find_label(&self, lang: &str) -> Result<&'a str, PropertyFinderError> {
+        self.find_lang_literal_value(common_keys::predicate::LABEL, lang)
+    }
+
+    pub fn find_label_by_preference(&self, langs: &[&str]) -> Result<&'a str, PropertyFinderError> {
+        super::label_by_preference(
+            self.filter_values_by_key(common_keys::predicate::LABEL)?,
+            langs,
+        )
+    }
+
+    pub fn find_lang_literal_value(
+        &self,
+        key: &str,
+        lang: &str,
+    ) -> Result<&'a str, PropertyFinderError> {
+        super::lang_literal_value(self.filter_values_by_key(key)?, lang)
+            .ok_or(PropertyFinderError::LanguageNotFound)
+    }
+
+    pub fn find_lang_literal_value_by_preference(
+        &self,
+        key: &str,
+        langs: &[&str],
+    ) -> Result<&'a str, PropertyFinderError> {
+        super::lang_literal_value_by_preference(self.filter_values_by_key(key)?, langs)
+    }
+}
+
+impl<'a> From<&'a [Property]> for PropertyIndex<'a> {
+    fn from(properties: &'a [Property]) -> Self {
+        Self::new(properties)
+    }
+}
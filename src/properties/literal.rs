@@ -0,0 +1,393 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, SecondsFormat, Utc};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+pub type LiteralParseError = Box<dyn Error + Send + Sync>;
+
+pub const XSD_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema#";
+
+/// Expand an IOTICS data type (e.g. `integer`) into its XSD IRI.
+pub fn data_type_iri(data_type: &str) -> String {
+    if data_type.contains(':') {
+        data_type.to_string()
+    } else {
+        format!("{}{}", XSD_NAMESPACE, data_type)
+    }
+}
+
+/// XSD data types as used by [`crate::Literal::data_type`], without the `xsd:` prefix.
+pub mod data_type {
+    pub const STRING: &str = "string";
+    pub const BOOLEAN: &str = "boolean";
+    pub const DECIMAL: &str = "decimal";
+    pub const INTEGER: &str = "integer";
+    pub const LONG: &str = "long";
+    pub const INT: &str = "int";
+    pub const SHORT: &str = "short";
+    pub const BYTE: &str = "byte";
+    pub const NON_NEGATIVE_INTEGER: &str = "nonNegativeInteger";
+    pub const POSITIVE_INTEGER: &str = "positiveInteger";
+    pub const NEGATIVE_INTEGER: &str = "negativeInteger";
+    pub const NON_POSITIVE_INTEGER: &str = "nonPositiveInteger";
+    pub const UNSIGNED_LONG: &str = "unsignedLong";
+    pub const UNSIGNED_INT: &str = "unsignedInt";
+    pub const UNSIGNED_SHORT: &str = "unsignedShort";
+    pub const UNSIGNED_BYTE: &str = "unsignedByte";
+    pub const DOUBLE: &str = "double";
+    pub const FLOAT: &str = "float";
+    pub const DATE_TIME: &str = "dateTime";
+    pub const DATE: &str = "date";
+    pub const TIME: &str = "time";
+    pub const ANY_URI: &str = "anyURI";
+
+    pub const INTEGER_TYPES: &[&str] = &[
+        INTEGER,
+        LONG,
+        INT,
+        SHORT,
+        BYTE,
+        NON_NEGATIVE_INTEGER,
+        POSITIVE_INTEGER,
+        NEGATIVE_INTEGER,
+        NON_POSITIVE_INTEGER,
+        UNSIGNED_LONG,
+        UNSIGNED_INT,
+        UNSIGNED_SHORT,
+        UNSIGNED_BYTE,
+    ];
+}
+
+/// Conversion between a Rust value and the lexical form of an XSD typed literal.
+pub trait LiteralValue: Sized {
+    /// The data type used when building a literal from this value.
+    const DATA_TYPE: &'static str;
+
+    /// Whether a literal of `data_type` can be read as this value.
+    fn accepts(data_type: &str) -> bool {
+        data_type == Self::DATA_TYPE
+    }
+
+    fn to_lexical(&self) -> String;
+
+    fn from_lexical(value: &str) -> Result<Self, LiteralParseError>;
+}
+
+macro_rules! integer_literal {
+    ($($ty:ty),*) => {
+        $(
+            impl LiteralValue for $ty {
+                const DATA_TYPE: &'static str = data_type::INTEGER;
+
+                fn accepts(data_type: &str) -> bool {
+                    data_type::INTEGER_TYPES.contains(&data_type)
+                }
+
+                fn to_lexical(&self) -> String {
+                    self.to_string()
+                }
+
+                fn from_lexical(value: &str) -> Result<Self, LiteralParseError> {
+                    Ok(value.trim().trim_start_matches('+').parse()?)
+                }
+            }
+        )*
+    };
+}
+
+integer_literal!(i8, i16, i32, i64, i128, u8, u16, u32, u64, u128);
+
+macro_rules! float_literal {
+    ($ty:ty, $data_type:expr) => {
+        impl LiteralValue for $ty {
+            const DATA_TYPE: &'static str = $data_type;
+
+            fn accepts(data_type: &str) -> bool {
+                [data_type::DOUBLE, data_type::FLOAT, data_type::DECIMAL].contains(&data_type)
+                    || data_type::INTEGER_TYPES.contains(&data_type)
+            }
+
+            fn to_lexical(&self) -> String {
+                if self.is_nan() {
+                    "NaN".to_string()
+                } else if self.is_infinite() && self.is_sign_positive() {
+                    "INF".to_string()
+                } else if self.is_infinite() {
+                    "-INF".to_string()
+                } else {
+                    self.to_string()
+                }
+            }
+
+            fn from_lexical(value: &str) -> Result<Self, LiteralParseError> {
+                Ok(value.trim().parse()?)
+            }
+        }
+    };
+}
+
+float_literal!(f64, data_type::DOUBLE);
+float_literal!(f32, data_type::FLOAT);
+
+impl LiteralValue for bool {
+    const DATA_TYPE: &'static str = data_type::BOOLEAN;
+
+    fn to_lexical(&self) -> String {
+        self.to_string()
+    }
+
+    fn from_lexical(value: &str) -> Result<Self, LiteralParseError> {
+        match value.trim() {
+            "true" | "1" => Ok(true),
+            "false" | "0" => Ok(false),
+            other => Err(format!("invalid boolean [{}]", other).into()),
+        }
+    }
+}
+
+impl LiteralValue for String {
+    const DATA_TYPE: &'static str = data_type::STRING;
+
+    fn to_lexical(&self) -> String {
+        self.clone()
+    }
+
+    fn from_lexical(value: &str) -> Result<Self, LiteralParseError> {
+        Ok(value.to_string())
+    }
+}
+
+impl LiteralValue for DateTime<FixedOffset> {
+    const DATA_TYPE: &'static str = data_type::DATE_TIME;
+
+    fn to_lexical(&self) -> String {
+        self.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+    }
+
+    fn from_lexical(value: &str) -> Result<Self, LiteralParseError> {
+        let value = value.trim();
+
+        match DateTime::parse_from_rfc3339(value) {
+            Ok(date_time) => Ok(date_time),
+            // xsd:dateTime does not require a timezone, UTC is assumed when it is missing
+            Err(e) => match NaiveDateTime::from_str(value) {
+                Ok(date_time) => Ok(date_time.and_utc().fixed_offset()),
+                Err(_) => Err(e.into()),
+            },
+        }
+    }
+}
+
+impl LiteralValue for DateTime<Utc> {
+    const DATA_TYPE: &'static str = data_type::DATE_TIME;
+
+    fn to_lexical(&self) -> String {
+        self.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+    }
+
+    fn from_lexical(value: &str) -> Result<Self, LiteralParseError> {
+        DateTime::<FixedOffset>::from_lexical(value).map(|date_time| date_time.with_timezone(&Utc))
+    }
+}
+
+impl LiteralValue for NaiveDate {
+    const DATA_TYPE: &'static str = data_type::DATE;
+
+    fn to_lexical(&self) -> String {
+        self.format("%Y-%m-%d").to_string()
+    }
+
+    fn from_lexical(value: &str) -> Result<Self, LiteralParseError> {
+        Ok(NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")?)
+    }
+}
+
+impl LiteralValue for NaiveTime {
+    const DATA_TYPE: &'static str = data_type::TIME;
+
+    fn to_lexical(&self) -> String {
+        self.format("%H:%M:%S%.f").to_string()
+    }
+
+    fn from_lexical(value: &str) -> Result<Self, LiteralParseError> {
+        Ok(NaiveTime::parse_from_str(value.trim(), "%H:%M:%S%.f")?)
+    }
+}
+
+/// An `xsd:decimal` kept in its lexical form to avoid losing precision.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Decimal(String);
+
+impl Decimal {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = LiteralParseError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        let digits = value.strip_prefix(['+', '-']).unwrap_or(value);
+        let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+
+        let is_valid = !(integer.is_empty() && fraction.is_empty())
+            && integer.chars().all(|c| c.is_ascii_digit())
+            && fraction.chars().all(|c| c.is_ascii_digit());
+
+        if is_valid {
+            Ok(Self(value.to_string()))
+        } else {
+            Err(format!("invalid decimal [{}]", value).into())
+        }
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl LiteralValue for Decimal {
+    const DATA_TYPE: &'static str = data_type::DECIMAL;
+
+    fn accepts(data_type: &str) -> bool {
+        data_type == data_type::DECIMAL || data_type::INTEGER_TYPES.contains(&data_type)
+    }
+
+    fn to_lexical(&self) -> String {
+        self.0.clone()
+    }
+
+    fn from_lexical(value: &str) -> Result<Self, LiteralParseError> {
+        value.parse()
+    }
+}
+
+/// An `xsd:anyURI` literal.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AnyUri(pub String);
+
+impl fmt::Display for AnyUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl LiteralValue for AnyUri {
+    const DATA_TYPE: &'static str = data_type::ANY_URI;
+
+    fn to_lexical(&self) -> String {
+        self.0.clone()
+    }
+
+    fn from_lexical(value: &str) -> Result<Self, LiteralParseError> {
+        let value = value.trim();
+
+        if value.chars().any(char::is_whitespace) {
+            return Err(format!("invalid URI [{}]", value).into());
+        }
+
+        Ok(Self(value.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_the_data_types() {
+        assert_eq!(
+            data_type_iri(data_type::INTEGER),
+            "http://www.w3.org/2001/XMLSchema#integer"
+        );
+        assert_eq!(
+            data_type_iri("http://example.com/unit"),
+            "http://example.com/unit"
+        );
+    }
+
+    #[test]
+    fn parses_integers() {
+        assert_eq!(i32::from_lexical(" +42 ").unwrap(), 42);
+        assert_eq!(i64::from_lexical("-7").unwrap(), -7);
+        assert!(u8::from_lexical("256").is_err());
+        assert!(i32::from_lexical("4.2").is_err());
+
+        assert!(i64::accepts(data_type::UNSIGNED_SHORT));
+        assert!(!i64::accepts(data_type::DECIMAL));
+    }
+
+    #[test]
+    fn parses_floats() {
+        assert_eq!(f64::from_lexical("1.5E3").unwrap(), 1500.0);
+        assert_eq!(f64::from_lexical("INF").unwrap(), f64::INFINITY);
+        assert_eq!(f64::from_lexical("-INF").unwrap(), f64::NEG_INFINITY);
+        assert!(f64::from_lexical("NaN").unwrap().is_nan());
+
+        assert_eq!(f64::INFINITY.to_lexical(), "INF");
+        assert_eq!(f64::NEG_INFINITY.to_lexical(), "-INF");
+        assert_eq!(f64::NAN.to_lexical(), "NaN");
+
+        assert!(f32::accepts(data_type::INTEGER));
+    }
+
+    #[test]
+    fn parses_booleans() {
+        assert!(bool::from_lexical("true").unwrap());
+        assert!(bool::from_lexical("1").unwrap());
+        assert!(!bool::from_lexical("false").unwrap());
+        assert!(!bool::from_lexical("0").unwrap());
+        assert!(bool::from_lexical("yes").is_err());
+    }
+
+    #[test]
+    fn parses_date_times() {
+        let date_time = DateTime::<FixedOffset>::from_lexical("2024-06-25T10:30:00+02:00").unwrap();
+        assert_eq!(date_time.to_lexical(), "2024-06-25T10:30:00+02:00");
+
+        let utc = DateTime::<Utc>::from_lexical("2024-06-25T10:30:00.5+02:00").unwrap();
+        assert_eq!(utc.to_lexical(), "2024-06-25T08:30:00.500Z");
+    }
+
+    #[test]
+    fn assumes_utc_for_date_times_without_timezone() {
+        let date_time = DateTime::<Utc>::from_lexical("2024-06-25T10:30:00").unwrap();
+
+        assert_eq!(date_time.to_lexical(), "2024-06-25T10:30:00Z");
+    }
+
+    #[test]
+    fn parses_dates_and_times() {
+        let date = NaiveDate::from_lexical("2024-06-25").unwrap();
+        assert_eq!(date.to_lexical(), "2024-06-25");
+
+        let time = NaiveTime::from_lexical("10:30:00.25").unwrap();
+        assert_eq!(time.to_lexical(), "10:30:00.250");
+
+        assert!(NaiveDate::from_lexical("25/06/2024").is_err());
+    }
+
+    #[test]
+    fn keeps_the_lexical_form_of_decimals() {
+        for value in ["0.1000000000000000000001", "-1", "+.5", "3."] {
+            assert_eq!(Decimal::from_lexical(value).unwrap().as_str(), value);
+        }
+
+        for value in ["", ".", "1e3", "1.2.3", "-"] {
+            assert!(Decimal::from_lexical(value).is_err(), "{}", value);
+        }
+    }
+
+    #[test]
+    fn rejects_uris_with_whitespace() {
+        assert_eq!(
+            AnyUri::from_lexical(" http://example.com/a ").unwrap(),
+            AnyUri("http://example.com/a".to_string())
+        );
+        assert!(AnyUri::from_lexical("http://example.com/a b").is_err());
+    }
+}
@@ -0,0 +1,419 @@
+use thiserror::Error;
+
+use crate::client::iotics::api::property::Value;
+use crate::client::iotics::api::{LangLiteral, Literal, Property, StringLiteral, Uri};
+
+pub mod index;
+pub mod literal;
+pub mod validation;
+
+pub use index::PropertyIndex;
+pub use literal::{data_type, AnyUri, Decimal, LiteralValue};
+
+#[cfg(feature = "derive")]
+pub use iotics_grpc_client_derive::TwinProperties;
+
+pub mod common_keys {
+    pub mod predicate {
+        // generic
+        pub const RDF_TYPE_PROPERTY: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+        pub const LABEL: &str = "http://www.w3.org/2000/01/rdf-schema#label";
+        pub const COMMENT: &str = "http://www.w3.org/2000/01/rdf-schema#comment";
+
+        // models
+        pub const CREATED_FROM_PROPERTY: &str = "https://data.iotics.com/app#createdFrom";
+        pub const MODEL_PROPERTY: &str = "https://data.iotics.com/app#model";
+
+        // interactions
+        pub const INTERACTION_CONFIG_PROPERTY: &str =
+            "https://data.iotics.com/app#interactionConfig";
+
+        // allow list
+        pub const HOST_ALLOW_LIST_PROPERTY: &str = "http://data.iotics.com/public#hostAllowList";
+        pub const HOST_METADATA_ALLOW_LIST_PROPERTY: &str =
+            "http://data.iotics.com/public#hostMetadataAllowList";
+
+        // portal
+        pub const SPACE_NAME_PROPERTY: &str = "https://data.iotics.com/app#spaceName";
+        pub const COLOR_PROPERTY: &str = "https://data.iotics.com/app#color";
+        pub const CREATED_BY_PROPERTY: &str = "https://data.iotics.com/app#createdBy";
+        pub const UPDATED_BY_PROPERTY: &str = "https://data.iotics.com/app#updatedBy";
+
+        // location
+        pub const LAT_PROPERTY: &str = "http://www.w3.org/2003/01/geo/wgs84_pos#lat";
+        pub const LONG_PROPERTY: &str = "http://www.w3.org/2003/01/geo/wgs84_pos#long";
+    }
+
+    pub mod object {
+        // models
+        pub const MODEL_PROPERTY: &str = "https://data.iotics.com/app#Model";
+        pub const BY_MODEL_PROPERTY: &str = "https://data.iotics.com/app#ByModel";
+
+        // interactions
+        pub const BY_INTERACTION_ENGINE_PROPERTY: &str =
+            "https://data.iotics.com/app#ByInteractionEngine";
+        pub const BY_INTERACTION_PROPERTY: &str = "https://data.iotics.com/app#ByInteraction";
+        pub const INTERACTION_PROPERTY: &str = "https://data.iotics.com/app#Interaction";
+
+        // connectors
+        pub const BY_PUBLIC_CONNECTOR_PROPERTY: &str =
+            "https://data.iotics.com/app#ByPublicConnector";
+
+        // allow list
+        pub const ALL_HOST_PROPERTY: &str = "http://data.iotics.com/public#all";
+        pub const NO_HOST_PROPERTY: &str = "http://data.iotics.com/public#none";
+
+        // misc
+        pub const SET_LATER_PROPERTY: &str = "<SET-LATER>";
+    }
+}
+/// Conversion between a Rust type and the properties of a twin.
+///
+/// It can be derived with `#[derive(TwinProperties)]` when the `derive` feature is enabled.
+pub trait TwinProperties: Sized {
+    fn to_properties(&self) -> Vec<Property>;
+
+    fn from_properties(properties: &[Property]) -> Result<Self, PropertyFinderError>;
+}
+
+pub struct PropertyBuilder;
+
+impl PropertyBuilder {
+    pub fn build_label(lang: &str, value: &str) -> Property {
+        PropertyBuilder::build_lang_literal(common_keys::predicate::LABEL, lang, value)
+    }
+
+    pub fn build_lang_literal(key: &str, lang: &str, value: &str) -> Property {
+        Property {
+            key: key.to_string(),
+            value: Some(Value::LangLiteralValue(LangLiteral {
+                lang: lang.to_string(),
+                value: value.to_string(),
+            })),
+        }
+    }
+
+    pub fn build_uri_value(key: &str, value: &str) -> Property {
+        Property {
+            key: key.to_string(),
+            value: Some(Value::UriValue(Uri {
+                value: value.to_string(),
+            })),
+        }
+    }
+
+    pub fn build_literal_value(key: &str, data_type: &str, value: &str) -> Property {
+        Property {
+            key: key.to_string(),
+            value: Some(Value::LiteralValue(Literal {
+                data_type: data_type.to_string(),
+                value: value.to_string(),
+            })),
+        }
+    }
+
+    /// Build a typed literal, e.g. `PropertyBuilder::literal(key, 42i64)`.
+    pub fn literal<T: LiteralValue>(key: &str, value: T) -> Property {
+        PropertyBuilder::build_literal_value(key, T::DATA_TYPE, &value.to_lexical())
+    }
+
+    pub fn build_string_literal_value(key: &str, value: &str) -> Property {
+        Property {
+            key: key.to_string(),
+            value: Some(Value::StringLiteralValue(StringLiteral {
+                value: value.to_string(),
+            })),
+        }
+    }
+}
+
+pub struct PropertyFinder;
+
+impl PropertyFinder {
+    pub fn find_string_literal_value<'a>(
+        properties: &'a [Property],
+        key: &str,
+    ) -> Result<&'a str, PropertyFinderError> {
+        let values = PropertyFinder::filter_values_by_key(properties, key)?;
+        string_literal_value(&values)
+    }
+
+    /// All the string literals under `key`.
+    pub fn find_string_literal_values<'a>(properties: &'a [Property], key: &str) -> Vec<&'a str> {
+        string_literal_values(&PropertyFinder::find_values(properties, key))
+    }
+
+    pub fn find_uri_value<'a>(
+        properties: &'a [Property],
+        key: &str,
+    ) -> Result<&'a str, PropertyFinderError> {
+        let values = PropertyFinder::filter_values_by_key(properties, key)?;
+        uri_value(&values)
+    }
+
+    /// All the URIs under `key`, e.g. all the `rdf:type`s of a twin.
+    pub fn find_uri_values<'a>(properties: &'a [Property], key: &str) -> Vec<&'a str> {
+        uri_values(&PropertyFinder::find_values(properties, key))
+    }
+
+    /// Find the lexical form of the first literal under `key` with the given data type.
+    pub fn find_literal_value<'a>(
+        properties: &'a [Property],
+        key: &str,
+        data_type: &str,
+    ) -> Result<&'a str, PropertyFinderError> {
+        let values = PropertyFinder::filter_values_by_key(properties, key)?;
+        literal_value(&values, data_type)
+    }
+
+    /// Find the first typed literal under `key` that can be read as `T`, e.g.
+    /// `PropertyFinder::find::<f64>(properties, key)`.
+    pub fn find<T: LiteralValue>(
+        properties: &[Property],
+        key: &str,
+    ) -> Result<T, PropertyFinderError> {
+        let values = PropertyFinder::filter_values_by_key(properties, key)?;
+        typed_literal(&values)
+    }
+
+    /// Read all the typed literals under `key` that can be read as `T`.
+    pub fn find_all<T: LiteralValue>(
+        properties: &[Property],
+        key: &str,
+    ) -> Result<Vec<T>, PropertyFinderError> {
+        typed_literals(&PropertyFinder::find_values(properties, key))
+    }
+
+    pub fn find_label<'a>(
+        properties: &'a [Property],
+        lang: &str,
+    ) -> Result<&'a str, PropertyFinderError> {
+        PropertyFinder::find_lang_literal_value(properties, common_keys::predicate::LABEL, lang)
+    }
+
+    /// Find the label in the first available language of `langs`, falling back to a label stored
+    /// as a string literal.
+    pub fn find_label_by_preference<'a>(
+        properties: &'a [Property],
+        langs: &[&str],
+    ) -> Result<&'a str, PropertyFinderError> {
+        let values =
+            PropertyFinder::filter_values_by_key(properties, common_keys::predicate::LABEL)?;
+        label_by_preference(&values, langs)
+    }
+
+    pub fn find_lang_literal_value<'a>(
+        properties: &'a [Property],
+        key: &str,
+        lang: &str,
+    ) -> Result<&'a str, PropertyFinderError> {
+        let values = PropertyFinder::filter_values_by_key(properties, key)?;
+        lang_literal_value(&values, lang).ok_or(PropertyFinderError::LanguageNotFound)
+    }
+
+    /// Find the lang literal under `key` in the first available language of `langs`, e.g.
+    /// `["en-GB", "en", ""]`. An empty language also matches a string literal.
+    pub fn find_lang_literal_value_by_preference<'a>(
+        properties: &'a [Property],
+        key: &str,
+        langs: &[&str],
+    ) -> Result<&'a str, PropertyFinderError> {
+        let values = PropertyFinder::filter_values_by_key(properties, key)?;
+        lang_literal_value_by_preference(&values, langs)
+    }
+
+    /// All the values under `key`, empty when the key is missing.
+    pub fn find_values<'a>(properties: &'a [Property], key: &str) -> Vec<&'a Value> {
+        properties
+            .iter()
+            .filter(|property| property.key == key)
+            .filter_map(|property| property.value.as_ref())
+            .collect()
+    }
+
+    pub fn filter_values_by_key<'a>(
+        properties: &'a [Property],
+        key: &str,
+    ) -> Result<Vec<&'a Value>, PropertyFinderError> {
+        let properties = properties
+            .iter()
+            .filter(|property| property.key == key)
+            .collect::<Vec<_>>();
+
+        if !properties.is_empty() {
+            let values = properties
+                .iter()
+                .filter_map(|property| {
+                    if let Some(value) = &property.value {
+                        Some(value)
+                    } else {
+                        None
+                    }
+                })
+                .collect::<Vec<&Value>>();
+
+            if !values.is_empty() {
+                Ok(values)
+            } else {
+                Err(PropertyFinderError::ValueNotFound)
+            }
+        } else {
+            Err(PropertyFinderError::KeyNotFound)
+        }
+    }
+}
+
+// lookups shared by `PropertyFinder` and `PropertyIndex`, over the values of a single key
+
+fn string_literal_values<'a>(values: &[&'a Value]) -> Vec<&'a str> {
+    values
+        .iter()
+        .filter_map(|value| match value {
+            Value::StringLiteralValue(string_literal) => Some(string_literal.value.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn string_literal_value<'a>(values: &[&'a Value]) -> Result<&'a str, PropertyFinderError> {
+    string_literal_values(values)
+        .into_iter()
+        .next()
+        .ok_or(PropertyFinderError::ValueNotFound)
+}
+
+fn uri_values<'a>(values: &[&'a Value]) -> Vec<&'a str> {
+    values
+        .iter()
+        .filter_map(|value| match value {
+            Value::UriValue(uri) => Some(uri.value.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn uri_value<'a>(values: &[&'a Value]) -> Result<&'a str, PropertyFinderError> {
+    uri_values(values)
+        .into_iter()
+        .next()
+        .ok_or(PropertyFinderError::ValueNotFound)
+}
+
+fn literal_value<'a>(
+    values: &[&'a Value],
+    data_type: &str,
+) -> Result<&'a str, PropertyFinderError> {
+    let mut found_data_type = None;
+
+    for value in values {
+        if let Value::LiteralValue(literal) = value {
+            if literal.data_type == data_type {
+                return Ok(&literal.value);
+            }
+
+            found_data_type.get_or_insert_with(|| literal.data_type.clone());
+        }
+    }
+
+    match found_data_type {
+        Some(found) => Err(PropertyFinderError::DataTypeMismatch {
+            expected: data_type.to_string(),
+            found,
+        }),
+        None => Err(PropertyFinderError::ValueNotFound),
+    }
+}
+
+fn parse_literal<T: LiteralValue>(literal: &Literal) -> Result<T, PropertyFinderError> {
+    T::from_lexical(&literal.value).map_err(|e| PropertyFinderError::ParseError {
+        data_type: literal.data_type.clone(),
+        value: literal.value.clone(),
+        reason: e.to_string(),
+    })
+}
+
+fn typed_literal<T: LiteralValue>(values: &[&Value]) -> Result<T, PropertyFinderError> {
+    let mut found_data_type = None;
+
+    for value in values {
+        if let Value::LiteralValue(literal) = value {
+            if T::accepts(&literal.data_type) {
+                return parse_literal(literal);
+            }
+
+            found_data_type.get_or_insert_with(|| literal.data_type.clone());
+        }
+    }
+
+    match found_data_type {
+        Some(found) => Err(PropertyFinderError::DataTypeMismatch {
+            expected: T::DATA_TYPE.to_string(),
+            found,
+        }),
+        None => Err(PropertyFinderError::ValueNotFound),
+    }
+}
+
+fn typed_literals<T: LiteralValue>(values: &[&Value]) -> Result<Vec<T>, PropertyFinderError> {
+    values
+        .iter()
+        .filter_map(|value| match value {
+            Value::LiteralValue(literal) if T::accepts(&literal.data_type) => {
+                Some(parse_literal(literal))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn lang_literal_value<'a>(values: &[&'a Value], lang: &str) -> Option<&'a str> {
+    values.iter().find_map(|value| match value {
+        Value::LangLiteralValue(lang_literal) if lang_literal.lang == lang => {
+            Some(lang_literal.value.as_str())
+        }
+        _ => None,
+    })
+}
+
+fn lang_literal_value_by_preference<'a>(
+    values: &[&'a Value],
+    langs: &[&str],
+) -> Result<&'a str, PropertyFinderError> {
+    langs
+        .iter()
+        .find_map(|lang| match lang_literal_value(values, lang) {
+            Some(value) => Some(value),
+            None if lang.is_empty() => string_literal_values(values).into_iter().next(),
+            None => None,
+        })
+        .ok_or(PropertyFinderError::LanguageNotFound)
+}
+
+fn label_by_preference<'a>(
+    values: &[&'a Value],
+    langs: &[&str],
+) -> Result<&'a str, PropertyFinderError> {
+    lang_literal_value_by_preference(values, langs)
+        .or_else(|_| string_literal_value(values))
+        .map_err(|_| PropertyFinderError::LanguageNotFound)
+}
+
+#[derive(Error, Debug)]
+pub enum PropertyFinderError {
+    #[error("property key not found")]
+    KeyNotFound,
+    #[error("property value not found")]
+    ValueNotFound,
+    #[error("language not found")]
+    LanguageNotFound,
+    #[error("invalid property value [{value}]: {reason}")]
+    InvalidValue { value: String, reason: String },
+    #[error("property data type mismatch, expected [{expected}] found [{found}]")]
+    DataTypeMismatch { expected: String, found: String },
+    #[error("failed to parse [{value}] as [{data_type}]: {reason}")]
+    ParseError {
+        data_type: String,
+        value: String,
+        reason: String,
+    },
+}
@@ -0,0 +1,602 @@
+use std::collections::HashMap;
+use std::fmt;
+use thiserror::Error;
+
+use crate::client::iotics::api::property::Value;
+use crate::client::iotics::api::{
+    Property, PropertyUpdate, UpsertFeedWithMeta, UpsertInputWithMeta,
+};
+
+use crate::properties::common_keys::predicate;
+
+/// The kind of value expected for a predicate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    /// A `Uri` value.
+    Uri,
+    /// A `StringLiteral`, `LangLiteral` or `Literal` value.
+    Literal,
+    /// Any value.
+    Any,
+}
+
+impl ValueKind {
+    fn of(value: &Value) -> Self {
+        match value {
+            Value::UriValue(_) => ValueKind::Uri,
+            _ => ValueKind::Literal,
+        }
+    }
+}
+
+impl fmt::Display for ValueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueKind::Uri => f.write_str("URI"),
+            ValueKind::Literal => f.write_str("literal"),
+            ValueKind::Any => f.write_str("any value"),
+        }
+    }
+}
+
+/// The predicates known to a [`PropertyValidator`] and the kind of value each of them expects.
+#[derive(Debug, Clone, Default)]
+pub struct Vocabulary {
+    predicates: HashMap<String, ValueKind>,
+}
+
+impl Vocabulary {
+    /// A vocabulary made of the predicates in [`common_keys::predicate`](crate::properties::common_keys::predicate).
+    ///
+    /// The terms of [`crate::vocab`] only describe the feeds and inputs in exported documents, they
+    /// aren't part of it.
+    pub fn iotics() -> Self {
+        Self::default()
+            .with_predicate(predicate::RDF_TYPE_PROPERTY, ValueKind::Uri)
+            .with_predicate(predicate::LABEL, ValueKind::Literal)
+            .with_predicate(predicate::COMMENT, ValueKind::Literal)
+            .with_predicate(predicate::CREATED_FROM_PROPERTY, ValueKind::Uri)
+            .with_predicate(predicate::MODEL_PROPERTY, ValueKind::Uri)
+            .with_predicate(predicate::INTERACTION_CONFIG_PROPERTY, ValueKind::Literal)
+            .with_predicate(predicate::HOST_ALLOW_LIST_PROPERTY, ValueKind::Uri)
+            .with_predicate(predicate::HOST_METADATA_ALLOW_LIST_PROPERTY, ValueKind::Uri)
+            .with_predicate(predicate::SPACE_NAME_PROPERTY, ValueKind::Literal)
+            .with_predicate(predicate::COLOR_PROPERTY, ValueKind::Literal)
+            .with_predicate(predicate::CREATED_BY_PROPERTY, ValueKind::Literal)
+            .with_predicate(predicate::UPDATED_BY_PROPERTY, ValueKind::Literal)
+            .with_predicate(predicate::LAT_PROPERTY, ValueKind::Literal)
+            .with_predicate(predicate::LONG_PROPERTY, ValueKind::Literal)
+    }
+
+    pub fn with_predicate(mut self, predicate: &str, kind: ValueKind) -> Self {
+        self.predicates.insert(predicate.to_string(), kind);
+        self
+    }
+
+    /// The expected kind of value of `predicate`, `None` if the predicate is unknown.
+    pub fn kind_of(&self, predicate: &str) -> Option<ValueKind> {
+        self.predicates.get(predicate).copied()
+    }
+
+    /// Add the properties declared in a Turtle ontology.
+    ///
+    /// Subjects typed as `rdf:Property` or `owl:AnnotationProperty` accept any value,
+    /// `owl:ObjectProperty` a URI and `owl:DatatypeProperty` a literal. An `rdfs:range` in the
+    /// XSD namespace, `rdfs:Literal` or `rdf:langString` makes any of them a literal.
+    #[cfg(feature = "rdf")]
+    pub fn load_turtle(&mut self, turtle: &str) -> Result<(), anyhow::Error> {
+        use rio_api::model::{Subject, Term, Triple};
+        use rio_api::parser::TriplesParser;
+        use rio_turtle::TurtleParser;
+
+        use crate::properties::literal::XSD_NAMESPACE;
+
+        const RDF_PROPERTY: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#Property";
+        const RDF_LANG_STRING: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#langString";
+        const RDFS_RANGE: &str = "http://www.w3.org/2000/01/rdf-schema#range";
+        const RDFS_LITERAL: &str = "http://www.w3.org/2000/01/rdf-schema#Literal";
+        const OWL_OBJECT_PROPERTY: &str = "http://www.w3.org/2002/07/owl#ObjectProperty";
+        const OWL_DATATYPE_PROPERTY: &str = "http://www.w3.org/2002/07/owl#DatatypeProperty";
+        const OWL_ANNOTATION_PROPERTY: &str = "http://www.w3.org/2002/07/owl#AnnotationProperty";
+
+        let mut declared = HashMap::new();
+        let mut literal_ranges = HashMap::new();
+
+        TurtleParser::new(turtle.as_bytes(), None).parse_all(
+            &mut |triple: Triple<'_>| -> Result<(), anyhow::Error> {
+                let (subject, object) = match (triple.subject, triple.object) {
+                    (Subject::NamedNode(subject), Term::NamedNode(object)) => {
+                        (subject.iri, object.iri)
+                    }
+                    _ => return Ok(()),
+                };
+
+                match triple.predicate.iri {
+                    predicate::RDF_TYPE_PROPERTY => {
+                        let kind = match object {
+                            RDF_PROPERTY | OWL_ANNOTATION_PROPERTY => ValueKind::Any,
+                            OWL_OBJECT_PROPERTY => ValueKind::Uri,
+                            OWL_DATATYPE_PROPERTY => ValueKind::Literal,
+                            _ => return Ok(()),
+                        };
+                        declared.insert(subject.to_string(), kind);
+                    }
+                    RDFS_RANGE => {
+                        let is_literal = object.starts_with(XSD_NAMESPACE)
+                            || object == RDFS_LITERAL
+                            || object == RDF_LANG_STRING;
+                        literal_ranges.insert(subject.to_string(), is_literal);
+                    }
+                    _ => {}
+                }
+
+                Ok(())
+            },
+        )?;
+
+        for (predicate, kind) in declared {
+            let kind = match literal_ranges.get(&predicate) {
+                Some(true) => ValueKind::Literal,
+                Some(false) if kind == ValueKind::Any => ValueKind::Uri,
+                _ => kind,
+            };
+            self.predicates.insert(predicate, kind);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ValidationIssue {
+    #[error("unknown predicate [{key}]")]
+    UnknownPredicate { key: String },
+    #[error("predicate [{key}] expects a {expected} but found a {found}")]
+    WrongValueKind {
+        key: String,
+        expected: ValueKind,
+        found: ValueKind,
+    },
+    #[error("property [{key}] has no value")]
+    MissingValue { key: String },
+    #[error("invalid language tag [{lang}] for predicate [{key}]")]
+    InvalidLangTag { key: String, lang: String },
+    #[error("missing label")]
+    MissingLabel,
+    #[error("missing comment")]
+    MissingComment,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("property validation failed: {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+pub struct ValidationError(pub Vec<ValidationIssue>);
+
+/// Checks properties against a [`Vocabulary`] before they are sent.
+///
+/// It's used by every function creating or updating twins and feeds when it's set in
+/// [`ClientConfig::property_validator`](crate::ClientConfig::property_validator).
+#[derive(Debug, Clone)]
+pub struct PropertyValidator {
+    vocabulary: Vocabulary,
+    allow_unknown_predicates: bool,
+    require_label: bool,
+    require_comment: bool,
+}
+
+impl Default for PropertyValidator {
+    fn default() -> Self {
+        Self::new(Vocabulary::iotics())
+    }
+}
+
+impl PropertyValidator {
+    /// A validator rejecting unknown predicates and twins without a label or a comment.
+    pub fn new(vocabulary: Vocabulary) -> Self {
+        Self {
+            vocabulary,
+            allow_unknown_predicates: false,
+            require_label: true,
+            require_comment: true,
+        }
+    }
+
+    pub fn allow_unknown_predicates(mut self, allow: bool) -> Self {
+        self.allow_unknown_predicates = allow;
+        self
+    }
+
+    pub fn require_label(mut self, require: bool) -> Self {
+        self.require_label = require;
+        self
+    }
+
+    pub fn require_comment(mut self, require: bool) -> Self {
+        self.require_comment = require;
+        self
+    }
+
+    pub fn vocabulary(&self) -> &Vocabulary {
+        &self.vocabulary
+    }
+
+    pub fn vocabulary_mut(&mut self) -> &mut Vocabulary {
+        &mut self.vocabulary
+    }
+
+    /// Check the full set of properties of a twin, including the label and comment requirements.
+    pub fn validate_twin(&self, properties: &[Property]) -> Result<(), ValidationError> {
+        let mut issues = self.check_properties(properties);
+
+        if self.require_label && !properties.iter().any(|p| p.key == predicate::LABEL) {
+            issues.push(ValidationIssue::MissingLabel);
+        }
+
+        if self.require_comment && !properties.iter().any(|p| p.key == predicate::COMMENT) {
+            issues.push(ValidationIssue::MissingComment);
+        }
+
+        into_result(issues)
+    }
+
+    /// Check a twin with its feeds and inputs as sent by `upsert_twin`.
+    /// The label and comment requirements only apply to the twin itself.
+    pub fn validate_upsert(
+        &self,
+        properties: &[Property],
+        feeds: &[UpsertFeedWithMeta],
+        inputs: &[UpsertInputWithMeta],
+    ) -> Result<(), ValidationError> {
+        let mut issues = match self.validate_twin(properties) {
+            Ok(()) => Vec::new(),
+            Err(ValidationError(issues)) => issues,
+        };
+
+        for feed in feeds {
+            issues.extend(self.check_properties(&feed.properties));
+        }
+
+        for input in inputs {
+            issues.extend(self.check_properties(&input.properties));
+        }
+
+        into_result(issues)
+    }
+
+    /// Check the added properties of an update, a partial update doesn't need a label or a comment.
+    pub fn validate_update(&self, update: &PropertyUpdate) -> Result<(), ValidationError> {
+        into_result(self.check_properties(&update.added))
+    }
+
+    /// Check the predicates, value kinds and language tags, without the label and comment requirements.
+    pub fn validate_properties(&self, properties: &[Property]) -> Result<(), ValidationError> {
+        into_result(self.check_properties(properties))
+    }
+
+    fn check_properties(&self, properties: &[Property]) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+
+        for property in properties {
+            let value = match &property.value {
+                Some(value) => value,
+                None => {
+                    issues.push(ValidationIssue::MissingValue {
+                        key: property.key.clone(),
+                    });
+                    continue;
+                }
+            };
+
+            match self.vocabulary.kind_of(&property.key) {
+                Some(ValueKind::Any) => {}
+                Some(expected) if expected != ValueKind::of(value) => {
+                    issues.push(ValidationIssue::WrongValueKind {
+                        key: property.key.clone(),
+                        expected,
+                        found: ValueKind::of(value),
+                    });
+                }
+                Some(_) => {}
+                None if self.allow_unknown_predicates => {}
+                None => issues.push(ValidationIssue::UnknownPredicate {
+                    key: property.key.clone(),
+                }),
+            }
+
+            if let Value::LangLiteralValue(literal) = value {
+                if !is_valid_lang_tag(&literal.lang) {
+                    issues.push(ValidationIssue::InvalidLangTag {
+                        key: property.key.clone(),
+                        lang: literal.lang.clone(),
+                    });
+                }
+            }
+        }
+
+        issues
+    }
+}
+
+fn into_result(issues: Vec<ValidationIssue>) -> Result<(), ValidationError> {
+    if issues.is_empty() {
+        Ok(())
+    } else {
+        Err(ValidationError(issues))
+    }
+}
+
+/// Check the shape of a BCP 47 language tag, e.g. `en`, `en-GB` or `zh-Hant-TW`.
+/// An empty tag is accepted, the literal is then a plain string as in the RDF and JSON-LD documents.
+fn is_valid_lang_tag(lang: &str) -> bool {
+    if lang.is_empty() {
+        return true;
+    }
+
+    let mut subtags = lang.split('-');
+
+    let primary = subtags.next().unwrap_or_default();
+    let is_valid_primary =
+        (2..=8).contains(&primary.len()) && primary.chars().all(|c| c.is_ascii_alphabetic());
+
+    is_valid_primary
+        && subtags.all(|subtag| {
+            (1..=8).contains(&subtag.len()) && subtag.chars().all(|c| c.is_ascii_alphanumeric())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::properties::PropertyBuilder;
+    use crate::vocab;
+
+    #[test]
+    fn rejects_the_crate_vocabulary_on_twins() {
+        let validator = PropertyValidator::default()
+            .require_label(false)
+            .require_comment(false);
+
+        let properties = vec![PropertyBuilder::build_string_literal_value(
+            vocab::FEED_ID,
+            "temperature",
+        )];
+
+        assert_eq!(
+            validator.validate_twin(&properties),
+            Err(ValidationError(vec![ValidationIssue::UnknownPredicate {
+                key: vocab::FEED_ID.to_string()
+            }]))
+        );
+    }
+
+    fn validator() -> PropertyValidator {
+        PropertyValidator::default()
+            .require_label(false)
+            .require_comment(false)
+    }
+
+    fn issues(result: Result<(), ValidationError>) -> Vec<ValidationIssue> {
+        match result {
+            Ok(()) => Vec::new(),
+            Err(ValidationError(issues)) => issues,
+        }
+    }
+
+    #[test]
+    fn accepts_the_iotics_predicates() {
+        let properties = vec![
+            PropertyBuilder::build_uri_value(
+                predicate::RDF_TYPE_PROPERTY,
+                "http://example.com/Sensor",
+            ),
+            PropertyBuilder::build_lang_literal(predicate::LABEL, "en", "Sensor"),
+            PropertyBuilder::build_lang_literal(predicate::COMMENT, "en-GB", "A sensor"),
+            PropertyBuilder::build_literal_value(predicate::LAT_PROPERTY, "double", "51.5"),
+        ];
+
+        assert_eq!(
+            PropertyValidator::default().validate_twin(&properties),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn rejects_a_value_of_the_wrong_kind() {
+        let properties = vec![
+            PropertyBuilder::build_string_literal_value(
+                predicate::RDF_TYPE_PROPERTY,
+                "http://example.com/Sensor",
+            ),
+            PropertyBuilder::build_uri_value(predicate::LABEL, "http://example.com/label"),
+        ];
+
+        assert_eq!(
+            issues(validator().validate_properties(&properties)),
+            vec![
+                ValidationIssue::WrongValueKind {
+                    key: predicate::RDF_TYPE_PROPERTY.to_string(),
+                    expected: ValueKind::Uri,
+                    found: ValueKind::Literal,
+                },
+                ValidationIssue::WrongValueKind {
+                    key: predicate::LABEL.to_string(),
+                    expected: ValueKind::Literal,
+                    found: ValueKind::Uri,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_predicates_unless_allowed() {
+        let properties = vec![PropertyBuilder::build_string_literal_value(
+            "http://example.com/serialNumber",
+            "SN-1",
+        )];
+
+        assert_eq!(
+            issues(validator().validate_properties(&properties)),
+            vec![ValidationIssue::UnknownPredicate {
+                key: "http://example.com/serialNumber".to_string()
+            }]
+        );
+        assert_eq!(
+            validator()
+                .allow_unknown_predicates(true)
+                .validate_properties(&properties),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn still_checks_the_known_predicates_when_unknown_ones_are_allowed() {
+        let properties = vec![PropertyBuilder::build_uri_value(
+            predicate::LABEL,
+            "http://example.com/label",
+        )];
+
+        assert_eq!(
+            issues(
+                validator()
+                    .allow_unknown_predicates(true)
+                    .validate_properties(&properties)
+            ),
+            vec![ValidationIssue::WrongValueKind {
+                key: predicate::LABEL.to_string(),
+                expected: ValueKind::Literal,
+                found: ValueKind::Uri,
+            }]
+        );
+    }
+
+    #[test]
+    fn rejects_a_property_without_value() {
+        let properties = vec![Property {
+            key: predicate::LABEL.to_string(),
+            value: None,
+        }];
+
+        assert_eq!(
+            issues(validator().validate_properties(&properties)),
+            vec![ValidationIssue::MissingValue {
+                key: predicate::LABEL.to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn checks_the_language_tags() {
+        for lang in ["en", "en-GB", "zh-Hant-TW", "de-CH-1996", ""] {
+            assert!(is_valid_lang_tag(lang), "{}", lang);
+        }
+
+        for lang in ["e", "en_GB", "en-", "-en", "123", "en-toolongsubtag"] {
+            assert!(!is_valid_lang_tag(lang), "{}", lang);
+        }
+
+        let properties = vec![
+            PropertyBuilder::build_lang_literal(predicate::LABEL, "", "Sensor"),
+            PropertyBuilder::build_lang_literal(predicate::COMMENT, "en_GB", "A sensor"),
+        ];
+
+        assert_eq!(
+            issues(validator().validate_properties(&properties)),
+            vec![ValidationIssue::InvalidLangTag {
+                key: predicate::COMMENT.to_string(),
+                lang: "en_GB".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn requires_a_label_and_a_comment_on_twins_only() {
+        let validator = PropertyValidator::default();
+
+        assert_eq!(
+            issues(validator.validate_twin(&[])),
+            vec![
+                ValidationIssue::MissingLabel,
+                ValidationIssue::MissingComment
+            ]
+        );
+        assert_eq!(
+            validator.validate_update(&PropertyUpdate::default()),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn checks_the_properties_of_the_feeds_and_inputs() {
+        let twin = vec![
+            PropertyBuilder::build_lang_literal(predicate::LABEL, "en", "Sensor"),
+            PropertyBuilder::build_lang_literal(predicate::COMMENT, "en", "A sensor"),
+        ];
+        let feeds = vec![UpsertFeedWithMeta {
+            properties: vec![PropertyBuilder::build_uri_value(
+                predicate::LABEL,
+                "http://example.com/label",
+            )],
+            ..Default::default()
+        }];
+
+        assert_eq!(
+            issues(PropertyValidator::default().validate_upsert(&twin, &feeds, &[])),
+            vec![ValidationIssue::WrongValueKind {
+                key: predicate::LABEL.to_string(),
+                expected: ValueKind::Literal,
+                found: ValueKind::Uri,
+            }]
+        );
+    }
+
+    #[cfg(feature = "rdf")]
+    #[test]
+    fn loads_the_predicates_of_a_turtle_ontology() {
+        let ontology = r#"
+            @prefix rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#> .
+            @prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+            @prefix owl: <http://www.w3.org/2002/07/owl#> .
+            @prefix xsd: <http://www.w3.org/2001/XMLSchema#> .
+            @prefix ex: <http://example.com/> .
+
+            ex:room a owl:ObjectProperty .
+            ex:floor a owl:DatatypeProperty ; rdfs:range xsd:integer .
+            ex:note a owl:AnnotationProperty .
+            ex:serialNumber a rdf:Property ; rdfs:range xsd:string .
+            ex:owner a rdf:Property ; rdfs:range ex:Person .
+            ex:Sensor a owl:Class .
+        "#;
+
+        let mut vocabulary = Vocabulary::default();
+        vocabulary.load_turtle(ontology).unwrap();
+
+        assert_eq!(
+            vocabulary.kind_of("http://example.com/room"),
+            Some(ValueKind::Uri)
+        );
+        assert_eq!(
+            vocabulary.kind_of("http://example.com/floor"),
+            Some(ValueKind::Literal)
+        );
+        assert_eq!(
+            vocabulary.kind_of("http://example.com/note"),
+            Some(ValueKind::Any)
+        );
+        assert_eq!(
+            vocabulary.kind_of("http://example.com/serialNumber"),
+            Some(ValueKind::Literal)
+        );
+        assert_eq!(
+            vocabulary.kind_of("http://example.com/owner"),
+            Some(ValueKind::Uri)
+        );
+        assert_eq!(vocabulary.kind_of("http://example.com/Sensor"), None);
+    }
+
+    #[cfg(feature = "rdf")]
+    #[test]
+    fn rejects_an_invalid_turtle_ontology() {
+        assert!(Vocabulary::default()
+            .load_turtle("<http://example.com/room> a")
+            .is_err());
+    }
+}
@@ -0,0 +1,554 @@
+use anyhow::Context;
+use rio_api::formatter::TriplesFormatter;
+use rio_api::model::{Literal as RdfLiteral, NamedNode, Subject, Term, Triple};
+use rio_api::parser::TriplesParser;
+use rio_turtle::{NTriplesFormatter, TurtleFormatter, TurtleParser};
+
+use crate::client::iotics::api::property::Value;
+use crate::client::iotics::api::{Property, Value as FeedValue};
+
+use crate::properties::common_keys::predicate::{
+    COMMENT, LABEL, LAT_PROPERTY, LONG_PROPERTY, RDF_TYPE_PROPERTY,
+};
+use crate::properties::literal::{data_type_iri, XSD_NAMESPACE};
+use crate::properties::{data_type, PropertyBuilder};
+use crate::twin::DescribeTwinResponse;
+use crate::vocab;
+
+/// A triple whose predicate and object are stored as a [`Property`].
+pub type SubjectProperty = (String, Property);
+
+/// Serialize the properties of `subject` to Turtle.
+pub fn properties_to_turtle(
+    subject: &str,
+    properties: &[Property],
+) -> Result<String, anyhow::Error> {
+    let triples = properties
+        .iter()
+        .map(|property| (subject.to_string(), property.clone()))
+        .collect::<Vec<_>>();
+
+    to_turtle(&triples)
+}
+
+/// Serialize the properties of `subject` to N-Triples.
+pub fn properties_to_ntriples(
+    subject: &str,
+    properties: &[Property],
+) -> Result<String, anyhow::Error> {
+    let triples = properties
+        .iter()
+        .map(|property| (subject.to_string(), property.clone()))
+        .collect::<Vec<_>>();
+
+    to_ntriples(&triples)
+}
+
+/// Serialize a described twin, including its feeds, inputs and their values, to Turtle.
+pub fn twin_to_turtle(twin: &DescribeTwinResponse) -> Result<String, anyhow::Error> {
+    to_turtle(&twin_to_triples(twin)?)
+}
+
+/// Serialize a described twin, including its feeds, inputs and their values, to N-Triples.
+pub fn twin_to_ntriples(twin: &DescribeTwinResponse) -> Result<String, anyhow::Error> {
+    to_ntriples(&twin_to_triples(twin)?)
+}
+
+/// Parse a Turtle (or N-Triples) document and build the properties of `subject`.
+/// Triples about other subjects are ignored.
+pub fn properties_from_turtle(subject: &str, turtle: &str) -> Result<Vec<Property>, anyhow::Error> {
+    let mut properties = Vec::new();
+
+    TurtleParser::new(turtle.as_bytes(), None).parse_all(
+        &mut |triple: Triple<'_>| -> Result<(), anyhow::Error> {
+            match triple.subject {
+                Subject::NamedNode(node) if node.iri == subject => {
+                    properties.push(term_to_property(triple.predicate.iri, &triple.object)?);
+                }
+                _ => {}
+            }
+            Ok(())
+        },
+    )?;
+
+    Ok(properties)
+}
+
+/// Flatten a described twin into triples.
+pub fn twin_to_triples(twin: &DescribeTwinResponse) -> Result<Vec<SubjectProperty>, anyhow::Error> {
+    let payload = twin.payload.as_ref().context("failed to find payload")?;
+    let twin_id = payload.twin_id.as_ref().context("failed to find twin id")?;
+    let twin_iri = twin_id.id.clone();
+
+    let mut triples = Vec::new();
+
+    let mut push = |subject: &str, property: Property| {
+        triples.push((subject.to_string(), property));
+    };
+
+    for property in &payload.properties {
+        push(&twin_iri, property.clone());
+    }
+
+    if let Some(location) = &payload.location {
+        push(
+            &twin_iri,
+            PropertyBuilder::build_literal_value(
+                LAT_PROPERTY,
+                data_type::DOUBLE,
+                &location.lat.to_string(),
+            ),
+        );
+        push(
+            &twin_iri,
+            PropertyBuilder::build_literal_value(
+                LONG_PROPERTY,
+                data_type::DOUBLE,
+                &location.lon.to_string(),
+            ),
+        );
+    }
+
+    for feed in &payload.feeds {
+        let feed_id = feed.feed_id.as_ref().context("failed to find feed id")?;
+        let feed_iri = vocab::feed_iri(&twin_iri, &feed_id.id);
+
+        push(
+            &twin_iri,
+            PropertyBuilder::build_uri_value(vocab::HAS_FEED, &feed_iri),
+        );
+        push(
+            &feed_iri,
+            PropertyBuilder::build_uri_value(RDF_TYPE_PROPERTY, vocab::FEED),
+        );
+        push(
+            &feed_iri,
+            PropertyBuilder::build_string_literal_value(vocab::FEED_ID, &feed_id.id),
+        );
+        push(
+            &feed_iri,
+            PropertyBuilder::literal(vocab::STORE_LAST, feed.store_last),
+        );
+
+        for property in &feed.properties {
+            push(&feed_iri, property.clone());
+        }
+
+        for value in &feed.values {
+            for (subject, property) in value_to_triples(&feed_iri, value) {
+                push(&subject, property);
+            }
+        }
+    }
+
+    for input in &payload.inputs {
+        let input_id = input.input_id.as_ref().context("failed to find input id")?;
+        let input_iri = vocab::input_iri(&twin_iri, &input_id.id);
+
+        push(
+            &twin_iri,
+            PropertyBuilder::build_uri_value(vocab::HAS_INPUT, &input_iri),
+        );
+        push(
+            &input_iri,
+            PropertyBuilder::build_uri_value(RDF_TYPE_PROPERTY, vocab::INPUT),
+        );
+        push(
+            &input_iri,
+            PropertyBuilder::build_string_literal_value(vocab::INPUT_ID, &input_id.id),
+        );
+
+        for property in &input.properties {
+            push(&input_iri, property.clone());
+        }
+
+        for value in &input.values {
+            for (subject, property) in value_to_triples(&input_iri, value) {
+                push(&subject, property);
+            }
+        }
+    }
+
+    Ok(triples)
+}
+
+fn value_to_triples(parent_iri: &str, value: &FeedValue) -> Vec<SubjectProperty> {
+    let value_iri = vocab::value_iri(parent_iri, &value.label);
+
+    let mut triples = vec![
+        (
+            parent_iri.to_string(),
+            PropertyBuilder::build_uri_value(vocab::HAS_VALUE, &value_iri),
+        ),
+        (
+            value_iri.clone(),
+            PropertyBuilder::build_uri_value(RDF_TYPE_PROPERTY, vocab::VALUE),
+        ),
+        (
+            value_iri.clone(),
+            PropertyBuilder::build_string_literal_value(LABEL, &value.label),
+        ),
+    ];
+
+    if !value.comment.is_empty() {
+        triples.push((
+            value_iri.clone(),
+            PropertyBuilder::build_string_literal_value(COMMENT, &value.comment),
+        ));
+    }
+
+    if !value.unit.is_empty() {
+        triples.push((
+            value_iri.clone(),
+            PropertyBuilder::build_uri_value(vocab::VALUE_UNIT, &value.unit),
+        ));
+    }
+
+    if !value.data_type.is_empty() {
+        triples.push((
+            value_iri,
+            PropertyBuilder::build_string_literal_value(vocab::VALUE_DATA_TYPE, &value.data_type),
+        ));
+    }
+
+    triples
+}
+
+pub fn to_turtle(triples: &[SubjectProperty]) -> Result<String, anyhow::Error> {
+    let mut formatter = TurtleFormatter::new(Vec::new());
+    format_triples(&mut formatter, triples)?;
+    let output = formatter.finish()?;
+
+    Ok(String::from_utf8(output)?)
+}
+
+pub fn to_ntriples(triples: &[SubjectProperty]) -> Result<String, anyhow::Error> {
+    let mut formatter = NTriplesFormatter::new(Vec::new());
+    format_triples(&mut formatter, triples)?;
+    let output = formatter.finish()?;
+
+    Ok(String::from_utf8(output)?)
+}
+
+fn format_triples<F>(formatter: &mut F, triples: &[SubjectProperty]) -> Result<(), anyhow::Error>
+where
+    F: TriplesFormatter,
+    F::Error: std::error::Error + Send + Sync + 'static,
+{
+    for (subject, property) in triples {
+        let value = match &property.value {
+            Some(value) => value,
+            None => continue,
+        };
+
+        // keeps the expanded data type IRI alive while the triple is formatted
+        let datatype;
+
+        let object = match value {
+            Value::UriValue(uri) => Term::NamedNode(NamedNode { iri: &uri.value }),
+            Value::StringLiteralValue(literal) => Term::Literal(RdfLiteral::Simple {
+                value: &literal.value,
+            }),
+            // `"x"@` isn't valid, a literal without language is a plain string
+            Value::LangLiteralValue(literal) if literal.lang.is_empty() => {
+                Term::Literal(RdfLiteral::Simple {
+                    value: &literal.value,
+                })
+            }
+            Value::LangLiteralValue(literal) => Term::Literal(RdfLiteral::LanguageTaggedString {
+                value: &literal.value,
+                language: &literal.lang,
+            }),
+            // an untyped literal is a plain string, rather than typed with the bare XSD namespace
+            Value::LiteralValue(literal) if literal.data_type.is_empty() => {
+                Term::Literal(RdfLiteral::Simple {
+                    value: &literal.value,
+                })
+            }
+            Value::LiteralValue(literal) => {
+                datatype = data_type_iri(&literal.data_type);
+                Term::Literal(RdfLiteral::Typed {
+                    value: &literal.value,
+                    datatype: NamedNode { iri: &datatype },
+                })
+            }
+        };
+
+        formatter.format(&Triple {
+            subject: Subject::NamedNode(NamedNode { iri: subject }),
+            predicate: NamedNode { iri: &property.key },
+            object,
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Map an RDF object to a [`Property`], the reverse of the mapping used when serializing.
+pub fn term_to_property(predicate: &str, object: &Term<'_>) -> Result<Property, anyhow::Error> {
+    let property = match object {
+        Term::NamedNode(node) => PropertyBuilder::build_uri_value(predicate, node.iri),
+        Term::Literal(RdfLiteral::Simple { value }) => {
+            PropertyBuilder::build_string_literal_value(predicate, value)
+        }
+        Term::Literal(RdfLiteral::LanguageTaggedString { value, language }) => {
+            PropertyBuilder::build_lang_literal(predicate, language, value)
+        }
+        Term::Literal(RdfLiteral::Typed { value, datatype }) => {
+            match datatype.iri.strip_prefix(XSD_NAMESPACE) {
+                Some(data_type::STRING) => {
+                    PropertyBuilder::build_string_literal_value(predicate, value)
+                }
+                Some(data_type) => {
+                    PropertyBuilder::build_literal_value(predicate, data_type, value)
+                }
+                None => PropertyBuilder::build_literal_value(predicate, datatype.iri, value),
+            }
+        }
+        _ => anyhow::bail!(
+            "unsupported RDF object for predicate [{}], only IRIs and literals can be mapped",
+            predicate
+        ),
+    };
+
+    Ok(property)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::iotics::api::describe_twin_response::{FeedMeta, InputMeta, Payload};
+    use crate::client::iotics::api::{FeedId, GeoLocation, InputId, TwinId};
+
+    const TWIN: &str = "did:iotics:iotHjrmKpPGWyEC4FFo4d6oyzVVk6MXLmEgY";
+
+    fn sorted(mut properties: Vec<Property>) -> Vec<Property> {
+        properties.sort_by_key(|property| format!("{:?}", property));
+        properties
+    }
+
+    fn twin_properties() -> Vec<Property> {
+        vec![
+            PropertyBuilder::build_uri_value(RDF_TYPE_PROPERTY, "http://example.com/Sensor"),
+            PropertyBuilder::build_lang_literal(LABEL, "en", "Thermometer"),
+            PropertyBuilder::build_string_literal_value(COMMENT, "In the kitchen"),
+            PropertyBuilder::build_literal_value(
+                "http://example.com/floor",
+                data_type::INTEGER,
+                "2",
+            ),
+        ]
+    }
+
+    fn value(label: &str, data_type: &str) -> FeedValue {
+        FeedValue {
+            label: label.to_string(),
+            data_type: data_type.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn describe(feed_id: &str, input_id: &str) -> DescribeTwinResponse {
+        DescribeTwinResponse {
+            payload: Some(Payload {
+                twin_id: Some(TwinId {
+                    id: TWIN.to_string(),
+                    ..Default::default()
+                }),
+                location: Some(GeoLocation {
+                    lat: 51.5,
+                    lon: -0.25,
+                }),
+                properties: twin_properties(),
+                feeds: vec![FeedMeta {
+                    feed_id: Some(FeedId {
+                        id: feed_id.to_string(),
+                        ..Default::default()
+                    }),
+                    store_last: true,
+                    properties: vec![PropertyBuilder::build_lang_literal(LABEL, "en", "Readings")],
+                    values: vec![value("reading #1", data_type::DECIMAL)],
+                }],
+                inputs: vec![InputMeta {
+                    input_id: Some(InputId {
+                        id: input_id.to_string(),
+                        ..Default::default()
+                    }),
+                    properties: Vec::new(),
+                    values: vec![value("on", "")],
+                }],
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn expected_twin_properties(feed_id: &str, input_id: &str) -> Vec<Property> {
+        let mut properties = twin_properties();
+        properties.extend([
+            PropertyBuilder::build_literal_value(LAT_PROPERTY, data_type::DOUBLE, "51.5"),
+            PropertyBuilder::build_literal_value(LONG_PROPERTY, data_type::DOUBLE, "-0.25"),
+            PropertyBuilder::build_uri_value(vocab::HAS_FEED, &vocab::feed_iri(TWIN, feed_id)),
+            PropertyBuilder::build_uri_value(vocab::HAS_INPUT, &vocab::input_iri(TWIN, input_id)),
+        ]);
+        properties
+    }
+
+    #[test]
+    fn round_trips_a_described_twin_through_turtle() {
+        let turtle = twin_to_turtle(&describe("readings", "switch")).unwrap();
+
+        let properties = properties_from_turtle(TWIN, &turtle).unwrap();
+
+        assert_eq!(
+            sorted(properties),
+            sorted(expected_twin_properties("readings", "switch"))
+        );
+    }
+
+    #[test]
+    fn round_trips_a_described_twin_through_ntriples() {
+        let ntriples = twin_to_ntriples(&describe("readings", "switch")).unwrap();
+
+        let properties = properties_from_turtle(TWIN, &ntriples).unwrap();
+
+        assert_eq!(
+            sorted(properties),
+            sorted(expected_twin_properties("readings", "switch"))
+        );
+    }
+
+    #[test]
+    fn describes_the_feeds_and_their_values() {
+        let turtle = twin_to_turtle(&describe("readings", "switch")).unwrap();
+        let feed_iri = vocab::feed_iri(TWIN, "readings");
+        let value_iri = vocab::value_iri(&feed_iri, "reading #1");
+
+        assert_eq!(
+            sorted(properties_from_turtle(&feed_iri, &turtle).unwrap()),
+            sorted(vec![
+                PropertyBuilder::build_uri_value(RDF_TYPE_PROPERTY, vocab::FEED),
+                PropertyBuilder::build_string_literal_value(vocab::FEED_ID, "readings"),
+                PropertyBuilder::literal(vocab::STORE_LAST, true),
+                PropertyBuilder::build_lang_literal(LABEL, "en", "Readings"),
+                PropertyBuilder::build_uri_value(vocab::HAS_VALUE, &value_iri),
+            ])
+        );
+        assert_eq!(
+            sorted(properties_from_turtle(&value_iri, &turtle).unwrap()),
+            sorted(vec![
+                PropertyBuilder::build_uri_value(RDF_TYPE_PROPERTY, vocab::VALUE),
+                PropertyBuilder::build_string_literal_value(LABEL, "reading #1"),
+                PropertyBuilder::build_string_literal_value(
+                    vocab::VALUE_DATA_TYPE,
+                    data_type::DECIMAL
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn percent_encodes_the_ids_and_labels_in_the_iris() {
+        let describe = describe("readings #1", "set <point>");
+
+        for document in [
+            twin_to_turtle(&describe).unwrap(),
+            twin_to_ntriples(&describe).unwrap(),
+        ] {
+            // the documents with invalid IRIs wouldn't parse
+            let properties = properties_from_turtle(TWIN, &document).unwrap();
+
+            assert_eq!(
+                sorted(properties),
+                sorted(expected_twin_properties("readings #1", "set <point>"))
+            );
+        }
+
+        let input_iri = vocab::input_iri(TWIN, "set <point>");
+        assert_eq!(input_iri, format!("{}/inputs/set%20%3Cpoint%3E", TWIN));
+    }
+
+    #[test]
+    fn serializes_an_empty_language_as_a_plain_string() {
+        let properties = vec![PropertyBuilder::build_lang_literal(
+            LABEL,
+            "",
+            "Thermometer",
+        )];
+
+        let ntriples = properties_to_ntriples(TWIN, &properties).unwrap();
+
+        assert_eq!(
+            ntriples.trim(),
+            format!("<{}> <{}> \"Thermometer\" .", TWIN, LABEL)
+        );
+        assert_eq!(
+            properties_from_turtle(TWIN, &ntriples).unwrap(),
+            vec![PropertyBuilder::build_string_literal_value(
+                LABEL,
+                "Thermometer"
+            )]
+        );
+    }
+
+    #[test]
+    fn serializes_an_empty_data_type_as_a_plain_string() {
+        let properties = vec![PropertyBuilder::build_literal_value(
+            "http://example.com/serialNumber",
+            "",
+            "SN-1",
+        )];
+
+        let ntriples = properties_to_ntriples(TWIN, &properties).unwrap();
+
+        assert!(!ntriples.contains(XSD_NAMESPACE), "{}", ntriples);
+        assert_eq!(
+            properties_from_turtle(TWIN, &ntriples).unwrap(),
+            vec![PropertyBuilder::build_string_literal_value(
+                "http://example.com/serialNumber",
+                "SN-1"
+            )]
+        );
+    }
+
+    #[test]
+    fn reads_the_properties_of_a_turtle_document() {
+        let turtle = format!(
+            r#"
+            @prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+            @prefix xsd: <http://www.w3.org/2001/XMLSchema#> .
+
+            <{twin}> rdfs:label "Thermometer"@en ;
+                <http://example.com/floor> 2 ;
+                <http://example.com/model> <http://example.com/models/1> .
+
+            <http://example.com/other> rdfs:label "Other"@en .
+            "#,
+            twin = TWIN
+        );
+
+        let properties = properties_from_turtle(TWIN, &turtle).unwrap();
+
+        assert_eq!(
+            properties,
+            vec![
+                PropertyBuilder::build_lang_literal(LABEL, "en", "Thermometer"),
+                PropertyBuilder::build_literal_value(
+                    "http://example.com/floor",
+                    data_type::INTEGER,
+                    "2"
+                ),
+                PropertyBuilder::build_uri_value(
+                    "http://example.com/model",
+                    "http://example.com/models/1"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_a_blank_node_object() {
+        let turtle = format!("<{}> <http://example.com/part> [] .", TWIN);
+
+        assert!(properties_from_turtle(TWIN, &turtle).is_err());
+    }
+}
@@ -7,12 +7,15 @@ use tonic::metadata::{Ascii, MetadataValue};
 use tonic::transport::Channel;
 
 use crate::auth_builder::IntoAuthBuilder;
-use crate::channel::create_channel;
+use crate::call_context::CallContext;
+use crate::channel::{create_channel, ConfigureClient};
 use crate::client::google::protobuf::StringValue;
 use crate::client::iotics::api::search_api_client::SearchApiClient;
 use crate::client::iotics::api::search_request::Payload as SearchRequestPayload;
 use crate::client::iotics::api::{Headers, Limit, Offset, Range, Scope, SubscriptionHeaders};
 use crate::helpers::generate_client_app_id;
+use crate::metric_names;
+use crate::telemetry::{increment, stream_event, Rpc, StreamEvent};
 use crate::twin::PAGE_SIZE;
 
 pub use crate::client::iotics::api::search_request::payload::Filter;
@@ -27,7 +30,11 @@ pub async fn search(
     scope: Scope,
     timeout: Option<Duration>,
 ) -> Result<mpsc::Receiver<Result<SearchResponse, anyhow::Error>>, anyhow::Error> {
-    let channel = create_channel(auth_builder.clone(), None, None, None).await?;
+    let channel = create_channel(
+        auth_builder.clone(),
+        auth_builder.get_config().channel_options,
+    )
+    .await?;
     search_with_channel(auth_builder, channel, filter, scope, timeout).await
 }
 
@@ -38,9 +45,20 @@ pub async fn search_with_channel(
     scope: Scope,
     timeout: Option<Duration>,
 ) -> Result<mpsc::Receiver<Result<SearchResponse, anyhow::Error>>, anyhow::Error> {
-    let mut client = SearchApiClient::new(channel.clone());
-    let client_app_id = generate_client_app_id();
-    let transaction_ref = vec![client_app_id.clone()];
+    let config = auth_builder.get_config();
+    let mut client = SearchApiClient::new(channel.clone()).configure(&config.channel_options);
+    // the pages are requested later, each with a deadline from the time it's sent
+    let request_timeout = CallContext::current_request_timeout(&config.call_context);
+    let mut headers = CallContext::headers(config.call_context);
+
+    // the searches sharing a client app id receive the responses of each other, the responses of
+    // this search are told apart by a client ref unique to it, followed by the page
+    let client_ref = if headers.client_ref.is_empty() {
+        &headers.client_app_id
+    } else {
+        &headers.client_ref
+    };
+    headers.client_ref = format!("{}-{}", client_ref, generate_client_app_id());
 
     let token = auth_builder.get_token()?;
 
@@ -48,15 +66,17 @@ pub async fn search_with_channel(
 
     let mut results_client = client.clone();
     let results_token = token.clone();
-    let results_client_app_id = client_app_id.clone();
-    let results_transaction_ref = transaction_ref.clone();
+    let results_headers = headers.clone();
+    let results_transaction_ref = headers.transaction_ref.clone();
     let results_filter = filter.clone();
 
     let page = Arc::new(AtomicU32::new(0));
 
     let fut = async move {
+        let rpc = Rpc::new("SearchAPI", "ReceiveAllSearchResponses", &results_headers);
+
         let mut request = tonic::Request::new(SubscriptionHeaders {
-            client_app_id: results_client_app_id.clone(),
+            client_app_id: results_headers.client_app_id.clone(),
             transaction_ref: results_transaction_ref.clone(),
             ..Default::default()
         });
@@ -68,35 +88,71 @@ pub async fn search_with_channel(
             Ok(token) => {
                 request.metadata_mut().append("authorization", token);
 
-                let stream = results_client
-                    .clone()
-                    .receive_all_search_responses(request)
+                rpc.inject(&mut request);
+
+                let stream = rpc
+                    .run(results_client.clone().receive_all_search_responses(request))
                     .await;
 
                 match stream {
                     Ok(mut stream) => {
+                        stream_event("search", &results_transaction_ref, StreamEvent::Opened);
+
                         let stream = stream.get_mut();
 
-                        while let Ok(Some(result)) = stream.message().await {
+                        loop {
+                            let result = match stream.message().await {
+                                Ok(Some(result)) => result,
+                                Ok(None) => break,
+                                Err(e) => {
+                                    stream_event(
+                                        "search",
+                                        &results_transaction_ref,
+                                        StreamEvent::Failed(&e),
+                                    );
+                                    // ignore the potential error, the stream must be closed
+                                    let _ = tx.send(Err(e.into())).await;
+                                    return;
+                                }
+                            };
+
+                            let response_client_ref = match &result.headers {
+                                Some(headers) => headers.client_ref.as_str(),
+                                None => "",
+                            };
+
+                            let response_page = response_client_ref
+                                .strip_prefix(&results_headers.client_ref)
+                                .and_then(|suffix| suffix.strip_prefix('_'))
+                                .and_then(|page| page.parse::<u32>().ok());
+
+                            // a response of another search
+                            let response_page = match response_page {
+                                Some(response_page) => response_page,
+                                None => continue,
+                            };
+
+                            stream_event("search", &results_transaction_ref, StreamEvent::Message);
+                            increment(metric_names::SEARCH_RESPONSES_TOTAL, 1);
+
                             if let Some(payload) = &result.payload {
+                                increment(
+                                    metric_names::SEARCH_TWINS_FOUND_TOTAL,
+                                    payload.twins.len() as u64,
+                                );
+
                                 if payload.twins.len() >= PAGE_SIZE as usize {
                                     let current_page = page.load(Ordering::SeqCst);
 
-                                    if result
-                                        .headers
-                                        .as_ref()
-                                        .expect("this should not happen")
-                                        .client_ref
-                                        == format!("{}_{}", &results_client_app_id, current_page)
-                                    {
+                                    if response_page == current_page {
                                         let response = search_page_with_client(
                                             &mut results_client,
                                             &results_token,
                                             results_filter.clone(),
                                             scope,
                                             current_page + 1,
-                                            results_client_app_id.clone(),
-                                            results_transaction_ref.clone(),
+                                            results_headers.clone(),
+                                            request_timeout,
                                         )
                                         .await;
 
@@ -116,8 +172,11 @@ pub async fn search_with_channel(
                                 let _ = tx.send(Ok(result)).await;
                             }
                         }
+
+                        stream_event("search", &results_transaction_ref, StreamEvent::Closed);
                     }
                     Err(e) => {
+                        stream_event("search", &results_transaction_ref, StreamEvent::Failed(&e));
                         // ignore the potential error, the stream must be closed
                         let _ = tx.send(Err(e.into())).await;
                     }
@@ -145,8 +204,8 @@ pub async fn search_with_channel(
         filter,
         scope,
         0,
-        client_app_id,
-        transaction_ref,
+        headers,
+        request_timeout,
     )
     .await?;
 
@@ -159,14 +218,15 @@ async fn search_page_with_client(
     filter: Filter,
     scope: Scope,
     page: u32,
-    client_app_id: String,
-    transaction_ref: Vec<String>,
+    headers: Headers,
+    request_timeout: Option<Duration>,
 ) -> Result<(), anyhow::Error> {
+    let transaction_ref = headers.transaction_ref.clone();
+
     let headers = Headers {
-        client_app_id: client_app_id.clone(),
-        client_ref: format!("{}_{}", client_app_id, page),
-        transaction_ref: transaction_ref.clone(),
-        ..Default::default()
+        client_ref: format!("{}_{}", headers.client_ref, page),
+        request_timeout: request_timeout.map(CallContext::deadline),
+        ..headers
     };
 
     let payload = SearchRequestPayload {
@@ -175,6 +235,8 @@ async fn search_page_with_client(
         ..Default::default()
     };
 
+    let rpc = Rpc::new("SearchAPI", "DispatchSearchRequest", &headers);
+
     let mut request = tonic::Request::new(SearchRequest {
         lang: Some(StringValue {
             value: "en".to_string(),
@@ -192,11 +254,12 @@ async fn search_page_with_client(
 
     request.metadata_mut().append(
         "authorization",
-        token.parse().expect("Failed to parse token"),
+        token.parse().context("parse token failed")?,
     );
 
-    client
-        .dispatch_search_request(request)
+    rpc.inject(&mut request);
+
+    rpc.run(client.dispatch_search_request(request))
         .await
         .with_context(|| {
             format!(
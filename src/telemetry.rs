@@ -0,0 +1,210 @@
+//! Instrumentation of the RPCs, a no-op unless the `tracing` or `metrics` features are enabled.
+
+use std::future::Future;
+
+use crate::client::iotics::api::Headers;
+#[cfg(feature = "metrics")]
+use crate::metric_names;
+
+/// An RPC in flight, opened before the headers are moved into the request.
+pub struct Rpc {
+    #[cfg(feature = "metrics")]
+    service: &'static str,
+    #[cfg(feature = "metrics")]
+    method: &'static str,
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+}
+
+impl Rpc {
+    #[cfg_attr(
+        not(all(feature = "tracing", feature = "metrics")),
+        allow(unused_variables)
+    )]
+    pub fn new(service: &'static str, method: &'static str, headers: &Headers) -> Self {
+        Self {
+            #[cfg(feature = "metrics")]
+            service,
+            #[cfg(feature = "metrics")]
+            method,
+            #[cfg(feature = "tracing")]
+            span: tracing::info_span!(
+                "iotics_rpc",
+                rpc.service = service,
+                rpc.method = method,
+                client_app_id = %headers.client_app_id,
+                transaction_ref = %headers.transaction_ref.join(", "),
+                twin_id = tracing::field::Empty,
+                feed_id = tracing::field::Empty,
+                rpc.status_code = tracing::field::Empty,
+                latency_ms = tracing::field::Empty,
+            ),
+        }
+    }
+
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    pub fn twin(self, twin_id: &str) -> Self {
+        #[cfg(feature = "tracing")]
+        self.span.record("twin_id", twin_id);
+        self
+    }
+
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    pub fn feed(self, feed_id: &str) -> Self {
+        #[cfg(feature = "tracing")]
+        self.span.record("feed_id", feed_id);
+        self
+    }
+
+    /// Propagate the W3C trace context of the span in the request metadata, using the text map
+    /// propagator installed with `opentelemetry::global::set_text_map_propagator`.
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    pub fn inject<T>(&self, request: &mut tonic::Request<T>) {
+        #[cfg(feature = "tracing")]
+        {
+            use opentelemetry::propagation::TextMapPropagator;
+            use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+            let context = self.span.context();
+
+            opentelemetry::global::get_text_map_propagator(|propagator| {
+                propagator.inject_context(&context, &mut MetadataInjector(request.metadata_mut()))
+            });
+        }
+    }
+
+    /// Run the call in the span, recording its status code and latency.
+    pub async fn run<T>(
+        self,
+        call: impl Future<Output = Result<T, tonic::Status>>,
+    ) -> Result<T, tonic::Status> {
+        #[cfg(feature = "metrics")]
+        let (service, method, started_at) = (self.service, self.method, std::time::Instant::now());
+
+        #[cfg(feature = "tracing")]
+        let result = self.traced(call).await;
+
+        #[cfg(not(feature = "tracing"))]
+        let result = call.await;
+
+        #[cfg(feature = "metrics")]
+        {
+            let status = match &result {
+                Ok(_) => "Ok".to_string(),
+                Err(status) => format!("{:?}", status.code()),
+            };
+
+            metrics::histogram!(
+                metric_names::RPC_DURATION_SECONDS,
+                started_at.elapsed().as_secs_f64(),
+                "service" => service,
+                "method" => method,
+                "status" => status.clone()
+            );
+            metrics::counter!(
+                metric_names::RPC_REQUESTS_TOTAL,
+                1,
+                "service" => service,
+                "method" => method,
+                "status" => status
+            );
+        }
+
+        result
+    }
+
+    #[cfg(feature = "tracing")]
+    async fn traced<T>(
+        self,
+        call: impl Future<Output = Result<T, tonic::Status>>,
+    ) -> Result<T, tonic::Status> {
+        use tracing::Instrument;
+
+        let started_at = std::time::Instant::now();
+        let result = call.instrument(self.span.clone()).await;
+
+        self.span
+            .record("latency_ms", started_at.elapsed().as_millis() as u64);
+
+        match &result {
+            Ok(_) => {
+                self.span.record("rpc.status_code", "Ok");
+            }
+            Err(status) => {
+                self.span
+                    .record("rpc.status_code", tracing::field::debug(status.code()));
+                tracing::warn!(parent: &self.span, error = %status.message(), "RPC failed");
+            }
+        }
+
+        result
+    }
+}
+
+/// Lifecycle event of a long running stream: `follow`, `receive_input_messages` or `search`.
+#[derive(Clone, Copy)]
+pub enum StreamEvent<'a> {
+    Opened,
+    Message,
+    Closed,
+    Failed(&'a dyn std::fmt::Display),
+}
+
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub fn stream_event(stream: &'static str, transaction_ref: &[String], event: StreamEvent<'_>) {
+    #[cfg(feature = "tracing")]
+    {
+        let transaction_ref = transaction_ref.join(", ");
+
+        match event {
+            StreamEvent::Opened => {
+                tracing::info!(stream, transaction_ref = %transaction_ref, "stream opened")
+            }
+            StreamEvent::Message => {
+                tracing::trace!(stream, transaction_ref = %transaction_ref, "stream message")
+            }
+            StreamEvent::Closed => {
+                tracing::info!(stream, transaction_ref = %transaction_ref, "stream closed")
+            }
+            StreamEvent::Failed(error) => {
+                tracing::warn!(stream, transaction_ref = %transaction_ref, error = %error, "stream failed")
+            }
+        }
+    }
+}
+
+/// Log a problem that doesn't fail the current operation, e.g. a skipped item.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub fn warn(message: &dyn std::fmt::Display) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!("{}", message);
+}
+
+/// Increment a counter of [`crate::metric_names`].
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub fn increment(name: &'static str, value: u64) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(name, value);
+}
+
+/// Set a gauge of [`crate::metric_names`], `label` tells apart its instances, e.g. the feed publishers.
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub fn set_gauge(name: &'static str, label: (&'static str, &str), value: f64) {
+    #[cfg(feature = "metrics")]
+    metrics::gauge!(name, value, label.0 => label.1.to_string());
+}
+
+#[cfg(feature = "tracing")]
+struct MetadataInjector<'a>(&'a mut tonic::metadata::MetadataMap);
+
+#[cfg(feature = "tracing")]
+impl opentelemetry::propagation::Injector for MetadataInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        let key = tonic::metadata::MetadataKey::from_bytes(key.as_bytes());
+        let value = value.parse();
+
+        if let (Ok(key), Ok(value)) = (key, value) {
+            self.0.insert(key, value);
+        }
+    }
+}
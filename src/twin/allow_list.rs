@@ -0,0 +1,166 @@
+use anyhow::Context;
+use std::sync::Arc;
+use tonic::transport::Channel;
+
+use crate::client::iotics::api::property::Value;
+use crate::client::iotics::api::{Property, PropertyUpdate};
+
+use crate::auth_builder::IntoAuthBuilder;
+use crate::channel::create_channel;
+use crate::ids::{HostDid, TwinDid};
+use crate::properties::common_keys::{object, predicate};
+use crate::properties::{PropertyBuilder, PropertyFinder, PropertyFinderError};
+use crate::twin::crud::update_twin_with_channel;
+use crate::twin::DescribeTwinResponse;
+
+/// Visibility of a twin's data or metadata to other hosts.
+///
+/// It is stored on the twin as one `Uri` property per entry, keyed by
+/// [`predicate::HOST_ALLOW_LIST_PROPERTY`] or [`predicate::HOST_METADATA_ALLOW_LIST_PROPERTY`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AllowList {
+    /// All hosts are allowed.
+    All,
+    /// No remote host is allowed.
+    None,
+    /// Only the listed hosts are allowed.
+    Hosts(Vec<HostDid>),
+}
+
+impl AllowList {
+    /// Read the allow list stored under `key` from a list of properties.
+    pub fn from_properties(
+        properties: &[Property],
+        key: &str,
+    ) -> Result<Self, PropertyFinderError> {
+        let values = PropertyFinder::filter_values_by_key(properties, key)?;
+
+        let uris = values
+            .into_iter()
+            .filter_map(|value| match value {
+                Value::UriValue(uri) => Some(uri.value.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        if uris.is_empty() {
+            return Err(PropertyFinderError::ValueNotFound);
+        }
+
+        if uris.contains(&object::ALL_HOST_PROPERTY) {
+            return Ok(AllowList::All);
+        }
+
+        let hosts = uris
+            .into_iter()
+            .filter(|uri| *uri != object::NO_HOST_PROPERTY)
+            .map(|uri| {
+                uri.parse::<HostDid>()
+                    .map_err(|e| PropertyFinderError::InvalidValue {
+                        value: uri.to_string(),
+                        reason: e.to_string(),
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if hosts.is_empty() {
+            Ok(AllowList::None)
+        } else {
+            Ok(AllowList::Hosts(hosts))
+        }
+    }
+
+    /// Build the properties that represent the allow list under `key`.
+    pub fn to_properties(&self, key: &str) -> Vec<Property> {
+        match self {
+            AllowList::All => vec![PropertyBuilder::build_uri_value(
+                key,
+                object::ALL_HOST_PROPERTY,
+            )],
+            AllowList::Hosts(hosts) if !hosts.is_empty() => hosts
+                .iter()
+                .map(|host_id| PropertyBuilder::build_uri_value(key, host_id.as_str()))
+                .collect(),
+            // an empty list of hosts means that no host is allowed
+            _ => vec![PropertyBuilder::build_uri_value(
+                key,
+                object::NO_HOST_PROPERTY,
+            )],
+        }
+    }
+
+    /// Build the [`PropertyUpdate`] replacing any existing allow list under `key`.
+    pub fn to_property_update(&self, key: &str) -> PropertyUpdate {
+        PropertyUpdate {
+            deleted_by_key: vec![key.to_string()],
+            added: self.to_properties(key),
+            ..Default::default()
+        }
+    }
+}
+
+/// Read the data allow list of a described twin.
+pub fn find_host_allow_list(twin: &DescribeTwinResponse) -> Result<AllowList, anyhow::Error> {
+    find_allow_list(twin, predicate::HOST_ALLOW_LIST_PROPERTY)
+}
+
+/// Read the metadata allow list of a described twin.
+pub fn find_host_metadata_allow_list(
+    twin: &DescribeTwinResponse,
+) -> Result<AllowList, anyhow::Error> {
+    find_allow_list(twin, predicate::HOST_METADATA_ALLOW_LIST_PROPERTY)
+}
+
+fn find_allow_list(twin: &DescribeTwinResponse, key: &str) -> Result<AllowList, anyhow::Error> {
+    let payload = twin.payload.as_ref().context("failed to find payload")?;
+    let allow_list = AllowList::from_properties(&payload.properties, key)
+        .with_context(|| format!("failed to find allow list [{}]", key))?;
+
+    Ok(allow_list)
+}
+
+pub async fn update_host_allow_list(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    twin_id: &TwinDid,
+    allow_list: &AllowList,
+) -> Result<(), anyhow::Error> {
+    let channel = create_channel(
+        auth_builder.clone(),
+        auth_builder.get_config().channel_options,
+    )
+    .await?;
+    update_host_allow_list_with_channel(auth_builder, channel, twin_id, allow_list).await
+}
+
+pub async fn update_host_allow_list_with_channel(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    channel: Channel,
+    twin_id: &TwinDid,
+    allow_list: &AllowList,
+) -> Result<(), anyhow::Error> {
+    let properties = allow_list.to_property_update(predicate::HOST_ALLOW_LIST_PROPERTY);
+    update_twin_with_channel(auth_builder, channel, twin_id, properties).await
+}
+
+pub async fn update_host_metadata_allow_list(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    twin_id: &TwinDid,
+    allow_list: &AllowList,
+) -> Result<(), anyhow::Error> {
+    let channel = create_channel(
+        auth_builder.clone(),
+        auth_builder.get_config().channel_options,
+    )
+    .await?;
+    update_host_metadata_allow_list_with_channel(auth_builder, channel, twin_id, allow_list).await
+}
+
+pub async fn update_host_metadata_allow_list_with_channel(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    channel: Channel,
+    twin_id: &TwinDid,
+    allow_list: &AllowList,
+) -> Result<(), anyhow::Error> {
+    let properties = allow_list.to_property_update(predicate::HOST_METADATA_ALLOW_LIST_PROPERTY);
+    update_twin_with_channel(auth_builder, channel, twin_id, properties).await
+}
@@ -0,0 +1,385 @@
+use futures::stream::{self, StreamExt};
+use std::future::Future;
+use std::sync::Arc;
+use tonic::transport::Channel;
+
+use crate::client::iotics::api::{GeoLocation, Property};
+
+use crate::auth_builder::IntoAuthBuilder;
+use crate::channel::create_channel;
+use crate::ids::{HostDid, TwinDid};
+use crate::twin::crud::delete_twin_with_channel;
+use crate::twin::describe::describe_twin_with_channel;
+use crate::twin::upsert::upsert_twin_with_channel;
+use crate::twin::{
+    DescribeTwinResponse, UpsertFeedWithMeta, UpsertInputWithMeta, UpsertTwinResponse,
+};
+
+/// A concurrency suited to most hosts, e.g. for the `concurrency` of the bulk helpers.
+pub const DEFAULT_CONCURRENCY: usize = 10;
+
+/// A twin to upsert with [`upsert_twins`].
+#[derive(Debug, Clone)]
+pub struct UpsertTwinSpec {
+    pub twin_id: TwinDid,
+    pub properties: Vec<Property>,
+    pub feeds: Vec<UpsertFeedWithMeta>,
+    pub inputs: Vec<UpsertInputWithMeta>,
+    pub location: Option<GeoLocation>,
+}
+
+/// The outcome of the operation on one twin.
+#[derive(Debug)]
+pub struct BulkResult<T> {
+    pub twin_id: TwinDid,
+    pub result: Result<T, anyhow::Error>,
+}
+
+/// Reported to the progress callback each time an operation completes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BulkProgress {
+    pub total: usize,
+    pub completed: usize,
+    pub failed: usize,
+}
+
+/// Upsert all the `specs`, running up to `concurrency` requests at the same time over a single channel.
+///
+/// A failure doesn't stop the other upserts, the results are returned in the order of `specs`.
+/// A `concurrency` of 0 is treated as 1.
+pub async fn upsert_twins(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    specs: impl IntoIterator<Item = UpsertTwinSpec>,
+    concurrency: usize,
+    progress: impl FnMut(&BulkProgress),
+) -> Result<Vec<BulkResult<UpsertTwinResponse>>, anyhow::Error> {
+    let channel = create_channel(
+        auth_builder.clone(),
+        auth_builder.get_config().channel_options,
+    )
+    .await?;
+    Ok(upsert_twins_with_channel(auth_builder, channel, specs, concurrency, progress).await)
+}
+
+pub async fn upsert_twins_with_channel(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    channel: Channel,
+    specs: impl IntoIterator<Item = UpsertTwinSpec>,
+    concurrency: usize,
+    progress: impl FnMut(&BulkProgress),
+) -> Vec<BulkResult<UpsertTwinResponse>> {
+    let specs = specs
+        .into_iter()
+        .map(|spec| (spec.twin_id.clone(), spec))
+        .collect();
+
+    run_bulk(specs, concurrency, progress, |spec| {
+        let auth_builder = auth_builder.clone();
+        let channel = channel.clone();
+
+        async move {
+            let response = upsert_twin_with_channel(
+                auth_builder,
+                channel,
+                &spec.twin_id,
+                spec.properties,
+                spec.feeds,
+                spec.inputs,
+                spec.location,
+            )
+            .await?;
+
+            Ok(response.into_inner())
+        }
+    })
+    .await
+}
+
+/// Delete all the `twin_ids`, running up to `concurrency` requests at the same time over a single channel.
+///
+/// A failure doesn't stop the other deletions, the results are returned in the order of `twin_ids`.
+/// A `concurrency` of 0 is treated as 1.
+pub async fn delete_twins(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    twin_ids: impl IntoIterator<Item = TwinDid>,
+    concurrency: usize,
+    progress: impl FnMut(&BulkProgress),
+) -> Result<Vec<BulkResult<()>>, anyhow::Error> {
+    let channel = create_channel(
+        auth_builder.clone(),
+        auth_builder.get_config().channel_options,
+    )
+    .await?;
+    Ok(delete_twins_with_channel(auth_builder, channel, twin_ids, concurrency, progress).await)
+}
+
+pub async fn delete_twins_with_channel(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    channel: Channel,
+    twin_ids: impl IntoIterator<Item = TwinDid>,
+    concurrency: usize,
+    progress: impl FnMut(&BulkProgress),
+) -> Vec<BulkResult<()>> {
+    let twin_ids = twin_ids
+        .into_iter()
+        .map(|twin_id| (twin_id.clone(), twin_id))
+        .collect();
+
+    run_bulk(twin_ids, concurrency, progress, |twin_id| {
+        let auth_builder = auth_builder.clone();
+        let channel = channel.clone();
+
+        async move { delete_twin_with_channel(auth_builder, channel, &twin_id).await }
+    })
+    .await
+}
+
+/// Describe all the `twin_ids`, running up to `concurrency` requests at the same time over a single channel.
+///
+/// A failure doesn't stop the other descriptions, the results are returned in the order of `twin_ids`.
+/// A `concurrency` of 0 is treated as 1.
+pub async fn describe_twins(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    twin_ids: impl IntoIterator<Item = TwinDid>,
+    remote_host_id: Option<&HostDid>,
+    concurrency: usize,
+    progress: impl FnMut(&BulkProgress),
+) -> Result<Vec<BulkResult<DescribeTwinResponse>>, anyhow::Error> {
+    let channel = create_channel(
+        auth_builder.clone(),
+        auth_builder.get_config().channel_options,
+    )
+    .await?;
+    Ok(describe_twins_with_channel(
+        auth_builder,
+        channel,
+        twin_ids,
+        remote_host_id,
+        concurrency,
+        progress,
+    )
+    .await)
+}
+
+pub async fn describe_twins_with_channel(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    channel: Channel,
+    twin_ids: impl IntoIterator<Item = TwinDid>,
+    remote_host_id: Option<&HostDid>,
+    concurrency: usize,
+    progress: impl FnMut(&BulkProgress),
+) -> Vec<BulkResult<DescribeTwinResponse>> {
+    let twin_ids = twin_ids
+        .into_iter()
+        .map(|twin_id| (twin_id.clone(), twin_id))
+        .collect();
+
+    run_bulk(twin_ids, concurrency, progress, |twin_id| {
+        let auth_builder = auth_builder.clone();
+        let channel = channel.clone();
+
+        async move {
+            describe_twin_with_channel(auth_builder, channel, &twin_id, remote_host_id).await
+        }
+    })
+    .await
+}
+
+async fn run_bulk<I, T, F, Fut>(
+    items: Vec<(TwinDid, I)>,
+    concurrency: usize,
+    mut progress: impl FnMut(&BulkProgress),
+    operation: F,
+) -> Vec<BulkResult<T>>
+where
+    F: Fn(I) -> Fut,
+    Fut: Future<Output = Result<T, anyhow::Error>>,
+{
+    // a concurrency of 0 would never run anything
+    let concurrency = concurrency.max(1);
+
+    let mut state = BulkProgress {
+        total: items.len(),
+        completed: 0,
+        failed: 0,
+    };
+
+    let mut results = stream::iter(items.into_iter().enumerate())
+        .map(|(index, (twin_id, item))| {
+            let operation = operation(item);
+            async move { (index, twin_id, operation.await) }
+        })
+        .buffer_unordered(concurrency);
+
+    let mut collected = Vec::with_capacity(state.total);
+
+    while let Some((index, twin_id, result)) = results.next().await {
+        state.completed += 1;
+        if result.is_err() {
+            state.failed += 1;
+        }
+        progress(&state);
+
+        collected.push((index, BulkResult { twin_id, result }));
+    }
+
+    collected.sort_by_key(|(index, _)| *index);
+    collected.into_iter().map(|(_, result)| result).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    const TWIN: &str = "did:iotics:iotHjrmKpPGWyEC4FFo4d6oyzVVk6MXLmEgY";
+
+    fn items(count: usize) -> Vec<(TwinDid, usize)> {
+        (0..count)
+            .map(|index| (TWIN.parse().unwrap(), index))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn returns_the_results_in_the_order_of_the_items() {
+        // the first items complete last
+        let results = run_bulk(
+            items(5),
+            5,
+            |_| {},
+            |index| async move {
+                tokio::time::sleep(Duration::from_millis(10 * (5 - index) as u64)).await;
+                if index % 2 == 0 {
+                    Ok(index)
+                } else {
+                    Err(anyhow::anyhow!("failed [{}]", index))
+                }
+            },
+        )
+        .await;
+
+        let results = results
+            .into_iter()
+            .map(|result| result.result.map_err(|e| e.to_string()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            results,
+            vec![
+                Ok(0),
+                Err("failed [1]".to_string()),
+                Ok(2),
+                Err("failed [3]".to_string()),
+                Ok(4),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn runs_at_most_concurrency_operations_at_a_time() {
+        let running = &AtomicUsize::new(0);
+        let max_running = &AtomicUsize::new(0);
+
+        let results = run_bulk(
+            items(10),
+            3,
+            |_| {},
+            move |_| async move {
+                let now_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+                max_running.fetch_max(now_running, Ordering::SeqCst);
+
+                tokio::time::sleep(Duration::from_millis(5)).await;
+
+                running.fetch_sub(1, Ordering::SeqCst);
+                Ok(())
+            },
+        )
+        .await;
+
+        assert_eq!(results.len(), 10);
+        assert_eq!(max_running.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn runs_one_operation_at_a_time_with_a_concurrency_of_zero() {
+        let running = &AtomicUsize::new(0);
+        let max_running = &AtomicUsize::new(0);
+
+        let results = run_bulk(
+            items(3),
+            0,
+            |_| {},
+            move |_| async move {
+                let now_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+                max_running.fetch_max(now_running, Ordering::SeqCst);
+
+                tokio::time::sleep(Duration::from_millis(5)).await;
+
+                running.fetch_sub(1, Ordering::SeqCst);
+                Ok(())
+            },
+        )
+        .await;
+
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|result| result.result.is_ok()));
+        assert_eq!(max_running.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn reports_the_progress_of_each_operation() {
+        let mut progress = Vec::new();
+
+        run_bulk(
+            items(3),
+            1,
+            |state| progress.push(*state),
+            |index| async move {
+                if index == 1 {
+                    Err(anyhow::anyhow!("failed"))
+                } else {
+                    Ok(())
+                }
+            },
+        )
+        .await;
+
+        assert_eq!(
+            progress,
+            vec![
+                BulkProgress {
+                    total: 3,
+                    completed: 1,
+                    failed: 0
+                },
+                BulkProgress {
+                    total: 3,
+                    completed: 2,
+                    failed: 1
+                },
+                BulkProgress {
+                    total: 3,
+                    completed: 3,
+                    failed: 1
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn does_nothing_without_items() {
+        let mut calls = 0;
+
+        let results = run_bulk(
+            Vec::<(TwinDid, ())>::new(),
+            DEFAULT_CONCURRENCY,
+            |_| calls += 1,
+            |_| async { Ok(()) },
+        )
+        .await;
+
+        assert!(results.is_empty());
+        assert_eq!(calls, 0);
+    }
+}
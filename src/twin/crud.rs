@@ -7,6 +7,7 @@ use crate::client::iotics::api::create_feed_request::{
     Arguments as CreateFeedRequestArguments, Payload as CreateFeedRequestPayload,
 };
 use crate::client::iotics::api::create_twin_request::Payload as CreateTwinRequestPayload;
+use crate::client::iotics::api::delete_feed_request::Arguments as DeleteFeedRequestArguments;
 use crate::client::iotics::api::delete_twin_request::Arguments as DeleteTwinRequestArguments;
 use crate::client::iotics::api::feed_api_client::FeedApiClient;
 use crate::client::iotics::api::twin_api_client::TwinApiClient;
@@ -17,50 +18,56 @@ use crate::client::iotics::api::update_twin_request::{
     Arguments as UpdateTwinRequestArguments, Payload as UpdateTwinRequestPayload,
 };
 use crate::client::iotics::api::{
-    CreateFeedRequest, CreateTwinRequest, DeleteTwinRequest, FeedId, GeoLocation,
-    GeoLocationUpdate, Headers, Property, PropertyUpdate, TwinId, UpdateFeedRequest,
+    CreateFeedRequest, CreateTwinRequest, DeleteFeedRequest, DeleteTwinRequest, FeedId,
+    GeoLocation, GeoLocationUpdate, Property, PropertyUpdate, TwinId, UpdateFeedRequest,
     UpdateTwinRequest, Value as FeedValue, Values as FeedValues,
 };
 
 use crate::auth_builder::IntoAuthBuilder;
-use crate::channel::create_channel;
-use crate::helpers::generate_client_app_id;
+use crate::call_context::CallContext;
+use crate::channel::{create_channel, ConfigureClient};
+use crate::ids::{FeedRef, TwinDid};
+use crate::metric_names;
+use crate::telemetry::{increment, Rpc};
 
 pub async fn create_update_twin(
     auth_builder: Arc<impl IntoAuthBuilder>,
-    twin_id: &str,
+    twin_id: &TwinDid,
     properties: Vec<Property>,
     location: Option<GeoLocation>,
 ) -> Result<(), anyhow::Error> {
-    let channel = create_channel(auth_builder.clone(), None, None, None).await?;
+    let channel = create_channel(
+        auth_builder.clone(),
+        auth_builder.get_config().channel_options,
+    )
+    .await?;
     create_update_twin_with_channel(auth_builder, channel, twin_id, properties, location).await
 }
 
 pub async fn create_update_twin_with_channel(
     auth_builder: Arc<impl IntoAuthBuilder>,
     channel: Channel,
-    twin_id: &str,
+    twin_id: &TwinDid,
     properties: Vec<Property>,
     location: Option<GeoLocation>,
 ) -> Result<(), anyhow::Error> {
-    let mut client = TwinApiClient::new(channel);
-    let client_app_id = generate_client_app_id();
-    let transaction_ref = vec![client_app_id.clone()];
+    let config = auth_builder.get_config();
 
-    let headers = Headers {
-        client_app_id,
-        transaction_ref: transaction_ref.clone(),
-        ..Default::default()
-    };
+    if let Some(validator) = &config.property_validator {
+        validator.validate_twin(&properties)?;
+    }
+
+    let mut client = TwinApiClient::new(channel).configure(&config.channel_options);
+    let headers = CallContext::headers(config.call_context);
+    let transaction_ref = headers.transaction_ref.clone();
 
     let payload = CreateTwinRequestPayload {
         id: twin_id.to_string(),
     };
 
-    let twin_id = TwinId {
-        id: twin_id.to_string(),
-        ..Default::default()
-    };
+    let twin_id = TwinId::from(twin_id);
+
+    let rpc = Rpc::new("TwinAPI", "CreateTwin", &headers).twin(&twin_id.id);
 
     let mut request = tonic::Request::new(CreateTwinRequest {
         headers: Some(headers.clone()),
@@ -74,12 +81,20 @@ pub async fn create_update_twin_with_channel(
         token.parse().context("parse token failed")?,
     );
 
-    client.create_twin(request).await.with_context(|| {
-        format!(
-            "Creating twin failed, transaction ref [{}]",
-            transaction_ref.join(", ")
-        )
-    })?;
+    rpc.inject(&mut request);
+
+    rpc.run(client.create_twin(request))
+        .await
+        .with_context(|| {
+            format!(
+                "Creating twin failed, transaction ref [{}]",
+                transaction_ref.join(", ")
+            )
+        })?;
+
+    increment(metric_names::TWINS_CREATED_TOTAL, 1);
+
+    let rpc = Rpc::new("TwinAPI", "UpdateTwin", &headers).twin(&twin_id.id);
 
     let args = UpdateTwinRequestArguments {
         twin_id: Some(twin_id),
@@ -113,45 +128,52 @@ pub async fn create_update_twin_with_channel(
         token.parse().context("parse token failed")?,
     );
 
-    client.update_twin(request).await.with_context(|| {
-        format!(
-            "Updating twin failed, transaction ref [{}]",
-            transaction_ref.join(", ")
-        )
-    })?;
+    rpc.inject(&mut request);
+
+    rpc.run(client.update_twin(request))
+        .await
+        .with_context(|| {
+            format!(
+                "Updating twin failed, transaction ref [{}]",
+                transaction_ref.join(", ")
+            )
+        })?;
 
     Ok(())
 }
 
 pub async fn update_twin(
     auth_builder: Arc<impl IntoAuthBuilder>,
-    twin_id: &str,
+    twin_id: &TwinDid,
     properties: PropertyUpdate,
 ) -> Result<(), anyhow::Error> {
-    let channel = create_channel(auth_builder.clone(), None, None, None).await?;
+    let channel = create_channel(
+        auth_builder.clone(),
+        auth_builder.get_config().channel_options,
+    )
+    .await?;
     update_twin_with_channel(auth_builder, channel, twin_id, properties).await
 }
 
 pub async fn update_twin_with_channel(
     auth_builder: Arc<impl IntoAuthBuilder>,
     channel: Channel,
-    twin_id: &str,
+    twin_id: &TwinDid,
     properties: PropertyUpdate,
 ) -> Result<(), anyhow::Error> {
-    let mut client = TwinApiClient::new(channel);
-    let client_app_id = generate_client_app_id();
-    let transaction_ref = vec![client_app_id.clone()];
+    let config = auth_builder.get_config();
 
-    let headers = Headers {
-        client_app_id,
-        transaction_ref: transaction_ref.clone(),
-        ..Default::default()
-    };
+    if let Some(validator) = &config.property_validator {
+        validator.validate_update(&properties)?;
+    }
 
-    let twin_id = TwinId {
-        id: twin_id.to_string(),
-        ..Default::default()
-    };
+    let mut client = TwinApiClient::new(channel).configure(&config.channel_options);
+    let headers = CallContext::headers(config.call_context);
+    let transaction_ref = headers.transaction_ref.clone();
+
+    let twin_id = TwinId::from(twin_id);
+
+    let rpc = Rpc::new("TwinAPI", "UpdateTwin", &headers).twin(&twin_id.id);
 
     let args = UpdateTwinRequestArguments {
         twin_id: Some(twin_id),
@@ -175,76 +197,72 @@ pub async fn update_twin_with_channel(
         token.parse().context("parse token failed")?,
     );
 
-    client.update_twin(request).await.with_context(|| {
-        format!(
-            "Updating twin failed, transaction ref [{}]",
-            transaction_ref.join(", ")
-        )
-    })?;
+    rpc.inject(&mut request);
+
+    rpc.run(client.update_twin(request))
+        .await
+        .with_context(|| {
+            format!(
+                "Updating twin failed, transaction ref [{}]",
+                transaction_ref.join(", ")
+            )
+        })?;
+
+    increment(metric_names::TWINS_UPDATED_TOTAL, 1);
 
     Ok(())
 }
 
 pub async fn create_update_feed(
     auth_builder: Arc<impl IntoAuthBuilder>,
-    twin_id: &str,
-    feed_id: &str,
+    feed: &FeedRef,
     store_last: bool,
     properties: Vec<Property>,
     values: Vec<FeedValue>,
 ) -> Result<(), anyhow::Error> {
-    let channel = create_channel(auth_builder.clone(), None, None, None).await?;
-    create_update_feed_with_channel(
-        auth_builder,
-        channel,
-        twin_id,
-        feed_id,
-        store_last,
-        properties,
-        values,
+    let channel = create_channel(
+        auth_builder.clone(),
+        auth_builder.get_config().channel_options,
     )
-    .await
+    .await?;
+    create_update_feed_with_channel(auth_builder, channel, feed, store_last, properties, values)
+        .await
 }
 
 pub async fn create_update_feed_with_channel(
     auth_builder: Arc<impl IntoAuthBuilder>,
     channel: Channel,
-    twin_id: &str,
-    feed_id: &str,
+    feed: &FeedRef,
     store_last: bool,
     properties: Vec<Property>,
     values: Vec<FeedValue>,
 ) -> Result<(), anyhow::Error> {
-    let mut client = FeedApiClient::new(channel);
+    let config = auth_builder.get_config();
 
-    let feed_id_arg = FeedId {
-        id: feed_id.to_string(),
-        twin_id: twin_id.to_string(),
-        ..Default::default()
-    };
+    if let Some(validator) = &config.property_validator {
+        validator.validate_properties(&properties)?;
+    }
 
-    let twin_id = TwinId {
-        id: twin_id.to_string(),
-        ..Default::default()
-    };
+    let mut client = FeedApiClient::new(channel).configure(&config.channel_options);
 
-    let client_app_id = generate_client_app_id();
-    let transaction_ref = vec![client_app_id.clone()];
+    let feed_id_arg = FeedId::from(feed);
+    let twin_id = feed.twin_id();
 
-    let headers = Headers {
-        client_app_id,
-        transaction_ref: transaction_ref.clone(),
-        ..Default::default()
-    };
+    let headers = CallContext::headers(config.call_context);
+    let transaction_ref = headers.transaction_ref.clone();
 
     let args = CreateFeedRequestArguments {
         twin_id: Some(twin_id),
     };
 
     let payload = CreateFeedRequestPayload {
-        id: feed_id.to_string(),
+        id: feed.feed.clone(),
     };
 
+    let rpc = Rpc::new("FeedAPI", "CreateFeed", &headers)
+        .twin(feed.twin.as_str())
+        .feed(&feed.feed);
+
     let mut request = tonic::Request::new(CreateFeedRequest {
         headers: Some(headers.clone()),
         args: Some(args),
@@ -258,12 +276,16 @@ pub async fn create_update_feed_with_channel(
         token.parse().context("parse token failed")?,
     );
 
-    client.create_feed(request).await.with_context(|| {
-        format!(
-            "Creating feed failed, transaction ref [{}]",
-            transaction_ref.join(", ")
-        )
-    })?;
+    rpc.inject(&mut request);
+
+    rpc.run(client.create_feed(request))
+        .await
+        .with_context(|| {
+            format!(
+                "Creating feed failed, transaction ref [{}]",
+                transaction_ref.join(", ")
+            )
+        })?;
 
     let args = UpdateFeedRequestArguments {
         feed_id: Some(feed_id_arg),
@@ -282,6 +304,10 @@ pub async fn create_update_feed_with_channel(
         }),
     };
 
+    let rpc = Rpc::new("FeedAPI", "UpdateFeed", &headers)
+        .twin(feed.twin.as_str())
+        .feed(&feed.feed);
+
     let mut request = tonic::Request::new(UpdateFeedRequest {
         headers: Some(headers),
         args: Some(args),
@@ -295,43 +321,45 @@ pub async fn create_update_feed_with_channel(
         token.parse().context("parse token failed")?,
     );
 
-    client.update_feed(request).await.with_context(|| {
-        format!(
-            "Updating feed failed, transaction ref [{}]",
-            transaction_ref.join(", ")
-        )
-    })?;
+    rpc.inject(&mut request);
+
+    rpc.run(client.update_feed(request))
+        .await
+        .with_context(|| {
+            format!(
+                "Updating feed failed, transaction ref [{}]",
+                transaction_ref.join(", ")
+            )
+        })?;
 
     Ok(())
 }
 
 pub async fn delete_twin(
     auth_builder: Arc<impl IntoAuthBuilder>,
-    twin_id: &str,
+    twin_id: &TwinDid,
 ) -> Result<(), anyhow::Error> {
-    let channel = create_channel(auth_builder.clone(), None, None, None).await?;
+    let channel = create_channel(
+        auth_builder.clone(),
+        auth_builder.get_config().channel_options,
+    )
+    .await?;
     delete_twin_with_channel(auth_builder, channel, twin_id).await
 }
 
 pub async fn delete_twin_with_channel(
     auth_builder: Arc<impl IntoAuthBuilder>,
     channel: Channel,
-    twin_id: &str,
+    twin_id: &TwinDid,
 ) -> Result<(), anyhow::Error> {
-    let mut client = TwinApiClient::new(channel);
-    let client_app_id = generate_client_app_id();
-    let transaction_ref = vec![client_app_id.clone()];
+    let config = auth_builder.get_config();
+    let mut client = TwinApiClient::new(channel).configure(&config.channel_options);
+    let headers = CallContext::headers(config.call_context);
+    let transaction_ref = headers.transaction_ref.clone();
 
-    let twin_id = TwinId {
-        id: twin_id.to_string(),
-        ..Default::default()
-    };
+    let twin_id = TwinId::from(twin_id);
 
-    let headers = Headers {
-        client_app_id,
-        transaction_ref: transaction_ref.clone(),
-        ..Default::default()
-    };
+    let rpc = Rpc::new("TwinAPI", "DeleteTwin", &headers).twin(&twin_id.id);
 
     let args = DeleteTwinRequestArguments {
         twin_id: Some(twin_id),
@@ -349,12 +377,74 @@ pub async fn delete_twin_with_channel(
         token.parse().context("parse token failed")?,
     );
 
-    client.delete_twin(request).await.with_context(|| {
-        format!(
-            "Deleting twin failed, transaction ref [{}]",
-            transaction_ref.join(", ")
-        )
-    })?;
+    rpc.inject(&mut request);
+
+    rpc.run(client.delete_twin(request))
+        .await
+        .with_context(|| {
+            format!(
+                "Deleting twin failed, transaction ref [{}]",
+                transaction_ref.join(", ")
+            )
+        })?;
+
+    increment(metric_names::TWINS_DELETED_TOTAL, 1);
+
+    Ok(())
+}
+
+pub async fn delete_feed(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    feed: &FeedRef,
+) -> Result<(), anyhow::Error> {
+    let channel = create_channel(
+        auth_builder.clone(),
+        auth_builder.get_config().channel_options,
+    )
+    .await?;
+    delete_feed_with_channel(auth_builder, channel, feed).await
+}
+
+pub async fn delete_feed_with_channel(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    channel: Channel,
+    feed: &FeedRef,
+) -> Result<(), anyhow::Error> {
+    let config = auth_builder.get_config();
+    let mut client = FeedApiClient::new(channel).configure(&config.channel_options);
+    let headers = CallContext::headers(config.call_context);
+    let transaction_ref = headers.transaction_ref.clone();
+
+    let rpc = Rpc::new("FeedAPI", "DeleteFeed", &headers)
+        .twin(feed.twin.as_str())
+        .feed(&feed.feed);
+
+    let args = DeleteFeedRequestArguments {
+        feed_id: Some(FeedId::from(feed)),
+    };
+
+    let mut request = tonic::Request::new(DeleteFeedRequest {
+        headers: Some(headers),
+        args: Some(args),
+    });
+
+    let token = auth_builder.get_token()?;
+
+    request.metadata_mut().append(
+        "authorization",
+        token.parse().context("parse token failed")?,
+    );
+
+    rpc.inject(&mut request);
+
+    rpc.run(client.delete_feed(request))
+        .await
+        .with_context(|| {
+            format!(
+                "Deleting feed failed, transaction ref [{}]",
+                transaction_ref.join(", ")
+            )
+        })?;
 
     Ok(())
 }
@@ -0,0 +1,788 @@
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use std::collections::{HashSet, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::SystemTime;
+use thiserror::Error;
+use tokio::sync::{broadcast, Notify, Semaphore};
+use tokio::task::JoinHandle;
+use tonic::transport::Channel;
+
+use crate::client::iotics::api::feed_api_client::FeedApiClient;
+use crate::client::iotics::api::FeedData;
+
+use crate::auth_builder::IntoAuthBuilder;
+use crate::channel::{create_channel, ConfigureClient};
+use crate::ids::FeedRef;
+use crate::metric_names;
+use crate::telemetry::set_gauge;
+use crate::twin::share::{share_feed_data_with_client, to_timestamp};
+
+/// How samples of a feed waiting to be sent are combined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Coalescing {
+    /// Only the latest sample of each feed is kept, a new sample replaces the waiting one.
+    LatestWins,
+    /// All the samples are sent in order.
+    Queue,
+}
+
+/// What happens when a sample is published while the buffer is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// `publish` waits for a free slot, `try_publish` fails with [`PublishError::BufferFull`].
+    Block,
+    /// The oldest waiting sample is dropped to make room for the new one.
+    DropOldest,
+    /// The new sample is dropped and [`PublishError::BufferFull`] is returned.
+    DropNewest,
+}
+
+#[derive(Debug, Clone)]
+pub struct FeedPublisherConfig {
+    pub coalescing: Coalescing,
+    /// Maximum number of samples waiting to be sent, for all the feeds.
+    pub buffer_size: usize,
+    pub overflow: OverflowPolicy,
+    /// Maximum number of samples being sent at the same time.
+    pub concurrency: usize,
+    /// Retry once when sharing fails with an unknown error, see [`share_data`](crate::twin::share::share_data).
+    pub retry_unknown: bool,
+    pub mime: String,
+    /// Label of the metrics of the publisher, to tell apart the publishers of an application.
+    pub name: String,
+}
+
+impl Default for FeedPublisherConfig {
+    fn default() -> Self {
+        Self {
+            coalescing: Coalescing::Queue,
+            buffer_size: 1024,
+            overflow: OverflowPolicy::Block,
+            concurrency: 8,
+            retry_unknown: false,
+            mime: "application/json".to_string(),
+            name: "default".to_string(),
+        }
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum PublishError {
+    #[error("the feed publisher is closed")]
+    Closed,
+    #[error("the feed publisher buffer is full")]
+    BufferFull,
+}
+
+/// The outcome of sending one sample.
+#[derive(Debug, Clone)]
+pub struct Delivery {
+    pub feed: FeedRef,
+    pub occurred_at: SystemTime,
+    pub result: Result<(), Arc<anyhow::Error>>,
+}
+
+/// Counters of a [`FeedPublisher`] since it started.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FeedPublisherMetrics {
+    /// Samples added to the buffer.
+    pub accepted: u64,
+    /// Samples replaced by a newer sample of the same feed.
+    pub coalesced: u64,
+    /// Samples dropped because the buffer was full.
+    pub dropped: u64,
+    pub delivered: u64,
+    pub failed: u64,
+    /// Samples currently waiting to be sent.
+    pub buffered: usize,
+    /// Samples currently being sent.
+    pub in_flight: usize,
+}
+
+struct Sample {
+    feed: FeedRef,
+    occurred_at: SystemTime,
+    data: Vec<u8>,
+}
+
+#[derive(Default)]
+struct Buffer {
+    samples: VecDeque<Sample>,
+    in_flight: HashSet<FeedRef>,
+    closed: bool,
+}
+
+impl Buffer {
+    /// The oldest sample of a feed without a sample being sent, to keep the samples of a feed in order.
+    fn pop_ready(&mut self) -> Option<Sample> {
+        let position = self
+            .samples
+            .iter()
+            .position(|sample| !self.in_flight.contains(&sample.feed))?;
+
+        self.samples.remove(position)
+    }
+}
+
+#[derive(Default)]
+struct Counters {
+    accepted: AtomicU64,
+    coalesced: AtomicU64,
+    dropped: AtomicU64,
+    delivered: AtomicU64,
+    failed: AtomicU64,
+}
+
+struct Shared {
+    config: FeedPublisherConfig,
+    buffer: Mutex<Buffer>,
+    sample_ready: Notify,
+    space_available: Notify,
+    counters: Counters,
+    deliveries: broadcast::Sender<Delivery>,
+}
+
+impl Shared {
+    fn buffer(&self) -> MutexGuard<'_, Buffer> {
+        // the buffer is only mutated by short non-panicking sections, its content is still valid
+        self.buffer
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn record_buffered(&self, buffer: &Buffer) {
+        set_gauge(
+            metric_names::FEED_PUBLISHER_BUFFERED,
+            ("publisher", &self.config.name),
+            buffer.samples.len() as f64,
+        );
+    }
+
+    fn close(&self) {
+        self.buffer().closed = true;
+        self.sample_ready.notify_one();
+        self.space_available.notify_waiters();
+    }
+}
+
+/// Shares one sample, the RPC in production and a stub in the tests.
+type SendSample =
+    Arc<dyn Fn(FeedRef, FeedData) -> BoxFuture<'static, Result<(), anyhow::Error>> + Send + Sync>;
+
+/// Buffers samples for many feeds and shares them in the background over a single channel.
+///
+/// The samples are added with [`FeedPublisher::publish`]/[`FeedPublisher::try_publish`], or
+/// through the cloneable [`FeedSender`] handles of [`FeedPublisher::sender`] from other tasks.
+///
+/// It must be created inside a tokio runtime. Dropping it stops accepting samples, the buffered
+/// ones are still sent; use [`FeedPublisher::shutdown`] to wait for them.
+pub struct FeedPublisher {
+    sender: FeedSender,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl FeedPublisher {
+    pub async fn start<A>(
+        auth_builder: Arc<A>,
+        config: FeedPublisherConfig,
+    ) -> Result<Self, anyhow::Error>
+    where
+        A: IntoAuthBuilder + Send + Sync + 'static,
+    {
+        let channel = create_channel(
+            auth_builder.clone(),
+            auth_builder.get_config().channel_options,
+        )
+        .await?;
+        Ok(Self::start_with_channel(auth_builder, channel, config))
+    }
+
+    pub fn start_with_channel<A>(
+        auth_builder: Arc<A>,
+        channel: Channel,
+        config: FeedPublisherConfig,
+    ) -> Self
+    where
+        A: IntoAuthBuilder + Send + Sync + 'static,
+    {
+        let client =
+            FeedApiClient::new(channel).configure(&auth_builder.get_config().channel_options);
+        let retry_unknown = config.retry_unknown;
+
+        let send: SendSample = Arc::new(move |feed: FeedRef, sample: FeedData| {
+            let auth_builder = auth_builder.clone();
+            let mut client = client.clone();
+
+            async move {
+                let token = auth_builder.get_token()?;
+
+                share_feed_data_with_client(
+                    &mut client,
+                    &token,
+                    auth_builder.get_config().call_context,
+                    &feed,
+                    sample,
+                    retry_unknown,
+                )
+                .await
+            }
+            .boxed()
+        });
+
+        Self::start_with_send(config, send)
+    }
+
+    fn start_with_send(config: FeedPublisherConfig, send: SendSample) -> Self {
+        let (deliveries, _) = broadcast::channel(config.buffer_size.max(1));
+
+        let shared = Arc::new(Shared {
+            config,
+            buffer: Mutex::new(Buffer::default()),
+            sample_ready: Notify::new(),
+            space_available: Notify::new(),
+            counters: Counters::default(),
+            deliveries,
+        });
+
+        let worker = tokio::spawn(run(shared.clone(), send));
+
+        Self {
+            sender: FeedSender { shared },
+            worker: Some(worker),
+        }
+    }
+
+    /// Add a sample to the buffer, waiting for a free slot when the buffer is full and the
+    /// overflow policy is [`OverflowPolicy::Block`].
+    pub async fn publish(
+        &self,
+        feed: &FeedRef,
+        data: impl Into<Vec<u8>>,
+    ) -> Result<(), PublishError> {
+        self.sender.send(feed, data).await
+    }
+
+    /// Add a sample to the buffer without waiting, [`PublishError::BufferFull`] is returned
+    /// instead of blocking.
+    pub fn try_publish(
+        &self,
+        feed: &FeedRef,
+        data: impl Into<Vec<u8>>,
+    ) -> Result<(), PublishError> {
+        self.sender.try_send(feed, data)
+    }
+
+    /// A handle to add samples from other tasks, like the sender of a channel.
+    pub fn sender(&self) -> FeedSender {
+        self.sender.clone()
+    }
+
+    /// Receive the outcome of the samples sent from now on.
+    ///
+    /// A receiver falling behind by more than `buffer_size` deliveries misses the oldest ones.
+    pub fn deliveries(&self) -> broadcast::Receiver<Delivery> {
+        self.sender.shared.deliveries.subscribe()
+    }
+
+    pub fn metrics(&self) -> FeedPublisherMetrics {
+        let counters = &self.sender.shared.counters;
+        let buffer = self.sender.shared.buffer();
+
+        FeedPublisherMetrics {
+            accepted: counters.accepted.load(Ordering::Relaxed),
+            coalesced: counters.coalesced.load(Ordering::Relaxed),
+            dropped: counters.dropped.load(Ordering::Relaxed),
+            delivered: counters.delivered.load(Ordering::Relaxed),
+            failed: counters.failed.load(Ordering::Relaxed),
+            buffered: buffer.samples.len(),
+            in_flight: buffer.in_flight.len(),
+        }
+    }
+
+    /// Stop accepting samples and wait until the buffered ones are sent.
+    pub async fn shutdown(mut self) -> Result<(), anyhow::Error> {
+        self.sender.shared.close();
+
+        if let Some(worker) = self.worker.take() {
+            worker.await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Cloneable handle adding samples to the buffer of a [`FeedPublisher`].
+///
+/// The samples are rejected with [`PublishError::Closed`] once the publisher is dropped or shut down.
+#[derive(Clone)]
+pub struct FeedSender {
+    shared: Arc<Shared>,
+}
+
+impl FeedSender {
+    /// Same as [`FeedPublisher::publish`].
+    pub async fn send(&self, feed: &FeedRef, data: impl Into<Vec<u8>>) -> Result<(), PublishError> {
+        let mut sample = Sample {
+            feed: feed.clone(),
+            occurred_at: SystemTime::now(),
+            data: data.into(),
+        };
+
+        loop {
+            let space_available = self.shared.space_available.notified();
+
+            match self.push(sample) {
+                Ok(()) => return Ok(()),
+                Err(Overflow::Blocked(rejected)) => sample = rejected,
+                Err(Overflow::Failed(e)) => return Err(e),
+            }
+
+            space_available.await;
+        }
+    }
+
+    /// Same as [`FeedPublisher::try_publish`].
+    pub fn try_send(&self, feed: &FeedRef, data: impl Into<Vec<u8>>) -> Result<(), PublishError> {
+        let sample = Sample {
+            feed: feed.clone(),
+            occurred_at: SystemTime::now(),
+            data: data.into(),
+        };
+
+        match self.push(sample) {
+            Ok(()) => Ok(()),
+            Err(Overflow::Blocked(_)) => Err(PublishError::BufferFull),
+            Err(Overflow::Failed(e)) => Err(e),
+        }
+    }
+
+    fn push(&self, sample: Sample) -> Result<(), Overflow> {
+        let shared = &self.shared;
+        let mut buffer = shared.buffer();
+
+        if buffer.closed {
+            return Err(Overflow::Failed(PublishError::Closed));
+        }
+
+        if shared.config.coalescing == Coalescing::LatestWins {
+            if let Some(waiting) = buffer
+                .samples
+                .iter_mut()
+                .find(|waiting| waiting.feed == sample.feed)
+            {
+                *waiting = sample;
+                shared.counters.coalesced.fetch_add(1, Ordering::Relaxed);
+                shared.counters.accepted.fetch_add(1, Ordering::Relaxed);
+                shared.record_buffered(&buffer);
+                return Ok(());
+            }
+        }
+
+        if buffer.samples.len() >= shared.config.buffer_size.max(1) {
+            match shared.config.overflow {
+                OverflowPolicy::Block => return Err(Overflow::Blocked(sample)),
+                OverflowPolicy::DropNewest => {
+                    shared.counters.dropped.fetch_add(1, Ordering::Relaxed);
+                    shared.record_buffered(&buffer);
+                    return Err(Overflow::Failed(PublishError::BufferFull));
+                }
+                OverflowPolicy::DropOldest => {
+                    buffer.samples.pop_front();
+                    shared.counters.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+
+        buffer.samples.push_back(sample);
+        shared.counters.accepted.fetch_add(1, Ordering::Relaxed);
+        shared.record_buffered(&buffer);
+        drop(buffer);
+
+        shared.sample_ready.notify_one();
+
+        Ok(())
+    }
+}
+
+impl Drop for FeedPublisher {
+    fn drop(&mut self) {
+        self.sender.shared.close();
+    }
+}
+
+enum Overflow {
+    Blocked(Sample),
+    Failed(PublishError),
+}
+
+async fn run(shared: Arc<Shared>, send: SendSample) {
+    let concurrency = shared.config.concurrency.max(1);
+    let semaphore = Arc::new(Semaphore::new(concurrency));
+
+    loop {
+        let permit = match semaphore.clone().acquire_owned().await {
+            Ok(permit) => permit,
+            Err(_) => break,
+        };
+
+        let sample = loop {
+            let sample_ready = shared.sample_ready.notified();
+
+            {
+                let mut buffer = shared.buffer();
+
+                if let Some(sample) = buffer.pop_ready() {
+                    buffer.in_flight.insert(sample.feed.clone());
+                    shared.record_buffered(&buffer);
+                    break Some(sample);
+                }
+
+                if buffer.closed && buffer.samples.is_empty() {
+                    break None;
+                }
+            }
+
+            sample_ready.await;
+        };
+
+        let sample = match sample {
+            Some(sample) => sample,
+            None => break,
+        };
+
+        shared.space_available.notify_waiters();
+
+        let shared = shared.clone();
+        let send = send.clone();
+
+        tokio::spawn(async move {
+            let result = match to_feed_data(&shared.config, &sample) {
+                Ok(feed_data) => send(sample.feed.clone(), feed_data).await,
+                Err(e) => Err(e),
+            };
+
+            match result {
+                Ok(()) => shared.counters.delivered.fetch_add(1, Ordering::Relaxed),
+                Err(_) => shared.counters.failed.fetch_add(1, Ordering::Relaxed),
+            };
+
+            shared.buffer().in_flight.remove(&sample.feed);
+            shared.sample_ready.notify_one();
+
+            // ignore the potential error, there may be no receiver
+            let _ = shared.deliveries.send(Delivery {
+                feed: sample.feed,
+                occurred_at: sample.occurred_at,
+                result: result.map_err(Arc::new),
+            });
+
+            drop(permit);
+        });
+    }
+
+    // wait for the samples being sent
+    let _ = semaphore.acquire_many(concurrency as u32).await;
+}
+
+fn to_feed_data(config: &FeedPublisherConfig, sample: &Sample) -> Result<FeedData, anyhow::Error> {
+    Ok(FeedData {
+        occurred_at: Some(to_timestamp(sample.occurred_at)?),
+        mime: config.mime.clone(),
+        data: sample.data.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+    use tokio::time::timeout;
+
+    const TWIN: &str = "did:iotics:iotHjrmKpPGWyEC4FFo4d6oyzVVk6MXLmEgY";
+
+    fn feed(id: &str) -> FeedRef {
+        FeedRef::new(TWIN.parse().unwrap(), id)
+    }
+
+    /// A publisher whose samples wait for a permit of `gate` to be sent, reporting
+    /// `feed:data` when they start to be sent. A sample with the data `fail` is rejected.
+    struct Stub {
+        publisher: FeedPublisher,
+        started: mpsc::UnboundedReceiver<String>,
+        gate: Arc<Semaphore>,
+    }
+
+    impl Stub {
+        fn start(config: FeedPublisherConfig) -> Self {
+            let (started_tx, started) = mpsc::unbounded_channel();
+            let gate = Arc::new(Semaphore::new(0));
+            let send_gate = gate.clone();
+
+            let send: SendSample = Arc::new(move |feed: FeedRef, sample: FeedData| {
+                let started = started_tx.clone();
+                let gate = send_gate.clone();
+
+                async move {
+                    let data = String::from_utf8(sample.data).unwrap();
+                    let _ = started.send(format!("{}:{}", feed.feed, data));
+                    gate.acquire().await.unwrap().forget();
+
+                    if data == "fail" {
+                        anyhow::bail!("rejected");
+                    }
+                    Ok(())
+                }
+                .boxed()
+            });
+
+            Self {
+                publisher: FeedPublisher::start_with_send(config, send),
+                started,
+                gate,
+            }
+        }
+
+        fn open(&self) {
+            self.gate.add_permits(1000);
+        }
+
+        async fn next_started(&mut self) -> String {
+            timeout(Duration::from_secs(5), self.started.recv())
+                .await
+                .expect("no sample started to be sent")
+                .unwrap()
+        }
+
+        async fn nothing_started(&mut self) -> bool {
+            timeout(Duration::from_millis(50), self.started.recv())
+                .await
+                .is_err()
+        }
+    }
+
+    async fn wait_deliveries(deliveries: &mut broadcast::Receiver<Delivery>, count: usize) {
+        for _ in 0..count {
+            timeout(Duration::from_secs(5), deliveries.recv())
+                .await
+                .expect("no delivery")
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn queues_the_samples_of_a_feed_in_order() {
+        let mut stub = Stub::start(FeedPublisherConfig::default());
+        stub.open();
+
+        for data in ["1", "2", "3"] {
+            stub.publisher.publish(&feed("a"), data).await.unwrap();
+        }
+
+        stub.publisher.shutdown().await.unwrap();
+
+        let mut started = Vec::new();
+        while let Ok(sample) = stub.started.try_recv() {
+            started.push(sample);
+        }
+        assert_eq!(started, vec!["a:1", "a:2", "a:3"]);
+    }
+
+    #[tokio::test]
+    async fn keeps_the_latest_waiting_sample_of_a_feed() {
+        let mut stub = Stub::start(FeedPublisherConfig {
+            coalescing: Coalescing::LatestWins,
+            ..Default::default()
+        });
+        let mut deliveries = stub.publisher.deliveries();
+
+        stub.publisher.publish(&feed("a"), "1").await.unwrap();
+        assert_eq!(stub.next_started().await, "a:1");
+
+        stub.publisher.publish(&feed("a"), "2").await.unwrap();
+        stub.publisher.publish(&feed("a"), "3").await.unwrap();
+        stub.publisher.publish(&feed("b"), "1").await.unwrap();
+
+        assert_eq!(stub.next_started().await, "b:1");
+
+        let metrics = stub.publisher.metrics();
+        assert_eq!(metrics.accepted, 4);
+        assert_eq!(metrics.coalesced, 1);
+        assert_eq!(metrics.buffered, 1);
+        assert_eq!(metrics.in_flight, 2);
+
+        stub.open();
+        assert_eq!(stub.next_started().await, "a:3");
+
+        wait_deliveries(&mut deliveries, 3).await;
+        assert_eq!(stub.publisher.metrics().delivered, 3);
+    }
+
+    #[tokio::test]
+    async fn blocks_until_a_slot_is_free() {
+        let mut stub = Stub::start(FeedPublisherConfig {
+            buffer_size: 1,
+            concurrency: 1,
+            overflow: OverflowPolicy::Block,
+            ..Default::default()
+        });
+
+        stub.publisher.publish(&feed("a"), "1").await.unwrap();
+        assert_eq!(stub.next_started().await, "a:1");
+
+        stub.publisher.publish(&feed("b"), "1").await.unwrap();
+        assert_eq!(
+            stub.publisher.try_publish(&feed("b"), "2"),
+            Err(PublishError::BufferFull)
+        );
+
+        let sender = stub.publisher.sender();
+        let blocked = tokio::spawn(async move { sender.send(&feed("b"), "2").await });
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!blocked.is_finished());
+
+        stub.open();
+        blocked.await.unwrap().unwrap();
+
+        assert_eq!(stub.next_started().await, "b:1");
+        assert_eq!(stub.next_started().await, "b:2");
+        assert_eq!(stub.publisher.metrics().dropped, 0);
+    }
+
+    #[tokio::test]
+    async fn drops_the_oldest_sample_when_full() {
+        let mut stub = Stub::start(FeedPublisherConfig {
+            buffer_size: 2,
+            concurrency: 1,
+            overflow: OverflowPolicy::DropOldest,
+            ..Default::default()
+        });
+
+        stub.publisher.publish(&feed("a"), "1").await.unwrap();
+        assert_eq!(stub.next_started().await, "a:1");
+
+        for data in ["1", "2", "3"] {
+            stub.publisher.publish(&feed("b"), data).await.unwrap();
+        }
+
+        assert_eq!(stub.publisher.metrics().dropped, 1);
+
+        stub.open();
+        assert_eq!(stub.next_started().await, "b:2");
+        assert_eq!(stub.next_started().await, "b:3");
+    }
+
+    #[tokio::test]
+    async fn drops_the_newest_sample_when_full() {
+        let mut stub = Stub::start(FeedPublisherConfig {
+            buffer_size: 2,
+            concurrency: 1,
+            overflow: OverflowPolicy::DropNewest,
+            ..Default::default()
+        });
+
+        stub.publisher.publish(&feed("a"), "1").await.unwrap();
+        assert_eq!(stub.next_started().await, "a:1");
+
+        stub.publisher.publish(&feed("b"), "1").await.unwrap();
+        stub.publisher.publish(&feed("b"), "2").await.unwrap();
+        assert_eq!(
+            stub.publisher.publish(&feed("b"), "3").await,
+            Err(PublishError::BufferFull)
+        );
+
+        assert_eq!(stub.publisher.metrics().dropped, 1);
+
+        stub.open();
+        assert_eq!(stub.next_started().await, "b:1");
+        assert_eq!(stub.next_started().await, "b:2");
+    }
+
+    #[tokio::test]
+    async fn sends_at_most_concurrency_samples_at_a_time() {
+        let mut stub = Stub::start(FeedPublisherConfig {
+            concurrency: 2,
+            ..Default::default()
+        });
+
+        for id in ["a", "b", "c"] {
+            stub.publisher.publish(&feed(id), "1").await.unwrap();
+        }
+
+        assert_eq!(stub.next_started().await, "a:1");
+        assert_eq!(stub.next_started().await, "b:1");
+        assert!(stub.nothing_started().await);
+
+        let metrics = stub.publisher.metrics();
+        assert_eq!(metrics.in_flight, 2);
+        assert_eq!(metrics.buffered, 1);
+
+        stub.gate.add_permits(1);
+        assert_eq!(stub.next_started().await, "c:1");
+    }
+
+    #[tokio::test]
+    async fn sends_one_sample_of_a_feed_at_a_time() {
+        let mut stub = Stub::start(FeedPublisherConfig {
+            concurrency: 4,
+            ..Default::default()
+        });
+
+        stub.publisher.publish(&feed("a"), "1").await.unwrap();
+        stub.publisher.publish(&feed("a"), "2").await.unwrap();
+        stub.publisher.publish(&feed("b"), "1").await.unwrap();
+
+        assert_eq!(stub.next_started().await, "a:1");
+        assert_eq!(stub.next_started().await, "b:1");
+        // a:2 waits for a:1 even though there is room for more samples
+        assert!(stub.nothing_started().await);
+
+        stub.open();
+        assert_eq!(stub.next_started().await, "a:2");
+    }
+
+    #[tokio::test]
+    async fn reports_the_deliveries() {
+        let stub = Stub::start(FeedPublisherConfig::default());
+        let mut deliveries = stub.publisher.deliveries();
+        stub.open();
+
+        stub.publisher.publish(&feed("a"), "1").await.unwrap();
+        let delivery = deliveries.recv().await.unwrap();
+        assert_eq!(delivery.feed, feed("a"));
+        assert!(delivery.result.is_ok());
+
+        stub.publisher.publish(&feed("a"), "fail").await.unwrap();
+        let delivery = deliveries.recv().await.unwrap();
+        assert!(delivery.result.is_err());
+
+        let metrics = stub.publisher.metrics();
+        assert_eq!(metrics.delivered, 1);
+        assert_eq!(metrics.failed, 1);
+        assert_eq!(metrics.in_flight, 0);
+    }
+
+    #[tokio::test]
+    async fn sends_the_buffered_samples_on_shutdown() {
+        let mut stub = Stub::start(FeedPublisherConfig::default());
+        let sender = stub.publisher.sender();
+
+        for id in ["a", "b", "c"] {
+            sender.send(&feed(id), "1").await.unwrap();
+        }
+
+        stub.open();
+        stub.publisher.shutdown().await.unwrap();
+
+        let mut started = Vec::new();
+        while let Ok(sample) = stub.started.try_recv() {
+            started.push(sample);
+        }
+        assert_eq!(started.len(), 3);
+
+        assert_eq!(sender.try_send(&feed("a"), "2"), Err(PublishError::Closed));
+    }
+}
@@ -1,19 +1,48 @@
 use anyhow::Context;
+use futures::future::{BoxFuture, FutureExt};
+use futures::stream::{self, Stream, TryStreamExt};
 use std::sync::Arc;
+use tokio::task::JoinHandle;
 use tonic::transport::Channel;
 
 use crate::client::iotics::api::twin_api_client::TwinApiClient;
 use crate::client::iotics::api::{Headers, Limit, ListAllTwinsRequest, Offset, Range};
 
 use crate::auth_builder::IntoAuthBuilder;
-use crate::channel::create_channel;
-use crate::helpers::generate_client_app_id;
+use crate::call_context::CallContext;
+use crate::channel::{create_channel, ConfigureClient};
+use crate::telemetry::Rpc;
 use crate::twin::{TwinDetails, PAGE_SIZE};
 
+/// Paging of [`list_all_twins_stream`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ListOptions {
+    /// Number of twins requested per page.
+    pub page_size: u32,
+    /// Number of twins skipped before the first page.
+    pub offset: u32,
+    /// Request the next page in the background while the current one is consumed.
+    pub prefetch: bool,
+}
+
+impl Default for ListOptions {
+    fn default() -> Self {
+        Self {
+            page_size: PAGE_SIZE,
+            offset: 0,
+            prefetch: false,
+        }
+    }
+}
+
 pub async fn list_all_twins(
     auth_builder: Arc<impl IntoAuthBuilder>,
 ) -> Result<Vec<TwinDetails>, anyhow::Error> {
-    let channel = create_channel(auth_builder.clone(), None, None, None).await?;
+    let channel = create_channel(
+        auth_builder.clone(),
+        auth_builder.get_config().channel_options,
+    )
+    .await?;
     list_all_twins_with_channel(auth_builder, channel).await
 }
 
@@ -21,55 +50,365 @@ pub async fn list_all_twins_with_channel(
     auth_builder: Arc<impl IntoAuthBuilder>,
     channel: Channel,
 ) -> Result<Vec<TwinDetails>, anyhow::Error> {
-    let mut client = TwinApiClient::new(channel);
-    let client_app_id = generate_client_app_id();
-    let transaction_ref = vec![client_app_id.clone()];
-
-    let headers = Headers {
-        client_app_id,
-        transaction_ref: transaction_ref.clone(),
-        ..Default::default()
+    list_all_twins_stream_with_channel(auth_builder, channel, ListOptions::default())
+        .try_collect()
+        .await
+}
+
+/// List the twins of the host page by page, a page is only requested once the previous one
+/// has been consumed, or while it's consumed when `prefetch` is enabled.
+pub async fn list_all_twins_stream(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    options: ListOptions,
+) -> Result<impl Stream<Item = Result<TwinDetails, anyhow::Error>>, anyhow::Error> {
+    let channel = create_channel(
+        auth_builder.clone(),
+        auth_builder.get_config().channel_options,
+    )
+    .await?;
+    Ok(list_all_twins_stream_with_channel(
+        auth_builder,
+        channel,
+        options,
+    ))
+}
+
+pub fn list_all_twins_stream_with_channel(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    channel: Channel,
+    options: ListOptions,
+) -> impl Stream<Item = Result<TwinDetails, anyhow::Error>> {
+    let config = auth_builder.get_config();
+    // the pages are requested later, each with a deadline from the time it's sent
+    let request_timeout = CallContext::current_request_timeout(&config.call_context);
+    let headers = CallContext::headers(config.call_context);
+    let client = TwinApiClient::new(channel).configure(&config.channel_options);
+
+    list_pages(options, move |offset| {
+        let headers = Headers {
+            request_timeout: request_timeout.map(CallContext::deadline),
+            ..headers.clone()
+        };
+
+        fetch_page(
+            auth_builder.as_ref(),
+            &client,
+            &headers,
+            options.page_size,
+            offset,
+        )
+    })
+}
+
+type PageFuture = BoxFuture<'static, Result<Vec<TwinDetails>, anyhow::Error>>;
+
+/// Stream the twins of the pages returned by `fetch` for each offset.
+fn list_pages<F>(
+    options: ListOptions,
+    fetch: F,
+) -> impl Stream<Item = Result<TwinDetails, anyhow::Error>>
+where
+    F: Fn(u32) -> Result<PageFuture, anyhow::Error>,
+{
+    let state = ListState {
+        fetch,
+        options,
+        offset: options.offset,
+        next_page: NextPage::NotRequested,
     };
 
-    let mut twins = Vec::new();
-    let mut current_page = 0;
-    let mut get_next_page = true;
-
-    while get_next_page {
-        let mut request = tonic::Request::new(ListAllTwinsRequest {
-            headers: Some(headers.clone()),
-            range: Some(Range {
-                limit: Some(Limit { value: PAGE_SIZE }),
-                offset: Some(Offset {
-                    value: PAGE_SIZE * current_page,
-                }),
-            }),
-        });
-
-        let token = auth_builder.get_token()?;
-
-        request.metadata_mut().append(
-            "authorization",
-            token.parse().context("parse token failed")?,
-        );
-
-        let result = client.list_all_twins(request).await.with_context(|| {
-            format!(
-                "Listing twins failed, transaction ref [{}]",
-                transaction_ref.join(", ")
-            )
-        })?;
+    stream::try_unfold(state, ListState::next_page)
+        .map_ok(|twins| stream::iter(twins.into_iter().map(Ok)))
+        .try_flatten()
+}
+
+enum NextPage {
+    NotRequested,
+    Prefetched(Prefetch),
+    Done,
+}
+
+/// The request of the next page running in the background, aborted when the stream is dropped
+/// before it's consumed.
+struct Prefetch(JoinHandle<Result<Vec<TwinDetails>, anyhow::Error>>);
+
+impl Drop for Prefetch {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+struct ListState<F> {
+    fetch: F,
+    options: ListOptions,
+    offset: u32,
+    next_page: NextPage,
+}
+
+impl<F> ListState<F>
+where
+    F: Fn(u32) -> Result<PageFuture, anyhow::Error>,
+{
+    async fn next_page(mut self) -> Result<Option<(Vec<TwinDetails>, Self)>, anyhow::Error> {
+        let twins = match std::mem::replace(&mut self.next_page, NextPage::NotRequested) {
+            NextPage::Done => return Ok(None),
+            NextPage::NotRequested => (self.fetch)(self.offset)?.await?,
+            NextPage::Prefetched(mut prefetch) => (&mut prefetch.0)
+                .await
+                .context("prefetching twins failed")??,
+        };
+
+        self.offset += twins.len() as u32;
+
+        self.next_page = if twins.is_empty() || twins.len() < self.options.page_size as usize {
+            NextPage::Done
+        } else if self.options.prefetch {
+            NextPage::Prefetched(Prefetch(tokio::spawn((self.fetch)(self.offset)?)))
+        } else {
+            NextPage::NotRequested
+        };
+
+        Ok(Some((twins, self)))
+    }
+}
+
+/// Build the request of the page at `offset`, the returned future doesn't borrow anything so that
+/// it can be spawned.
+fn fetch_page(
+    auth_builder: &impl IntoAuthBuilder,
+    client: &TwinApiClient<Channel>,
+    headers: &Headers,
+    page_size: u32,
+    offset: u32,
+) -> Result<PageFuture, anyhow::Error> {
+    let mut client = client.clone();
+    let transaction_ref = headers.transaction_ref.clone();
+
+    let rpc = Rpc::new("TwinAPI", "ListAllTwins", headers);
+
+    let mut request = tonic::Request::new(ListAllTwinsRequest {
+        headers: Some(headers.clone()),
+        range: Some(Range {
+            limit: Some(Limit { value: page_size }),
+            offset: Some(Offset { value: offset }),
+        }),
+    });
+
+    let token = auth_builder.get_token()?;
+
+    request.metadata_mut().append(
+        "authorization",
+        token.parse().context("parse token failed")?,
+    );
+
+    rpc.inject(&mut request);
+
+    Ok(async move {
+        let result = rpc
+            .run(client.list_all_twins(request))
+            .await
+            .with_context(|| {
+                format!(
+                    "Listing twins failed, transaction ref [{}]",
+                    transaction_ref.join(", ")
+                )
+            })?;
         let result = result.into_inner();
 
         let payload = result.payload.context("failed to find payload")?;
 
-        if payload.twins.len() < PAGE_SIZE as usize {
-            get_next_page = false;
+        Ok(payload.twins)
+    }
+    .boxed())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use crate::client::iotics::api::TwinId;
+
+    fn twins(count: usize) -> Vec<TwinDetails> {
+        (0..count)
+            .map(|index| TwinDetails {
+                twin_id: Some(TwinId {
+                    id: format!("twin-{}", index),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn ids(twins: &[TwinDetails]) -> Vec<String> {
+        twins
+            .iter()
+            .map(|twin| twin.twin_id.as_ref().unwrap().id.clone())
+            .collect()
+    }
+
+    /// Serves the pages of `host_twins` and records the requested offsets.
+    fn fetch_from(
+        host_twins: Vec<TwinDetails>,
+        page_size: u32,
+        requested: Arc<Mutex<Vec<u32>>>,
+    ) -> impl Fn(u32) -> Result<PageFuture, anyhow::Error> {
+        move |offset| {
+            requested.lock().unwrap().push(offset);
+
+            let start = (offset as usize).min(host_twins.len());
+            let end = (start + page_size as usize).min(host_twins.len());
+            let page = host_twins[start..end].to_vec();
+
+            Ok(async move { Ok(page) }.boxed())
         }
+    }
+
+    #[tokio::test]
+    async fn lists_the_pages_from_the_offset() {
+        let requested = Arc::new(Mutex::new(Vec::new()));
+        let options = ListOptions {
+            page_size: 3,
+            offset: 1,
+            prefetch: false,
+        };
+
+        let listed = list_pages(options, fetch_from(twins(7), 3, requested.clone()))
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+
+        assert_eq!(ids(&listed), ids(&twins(7)[1..]));
+        // the last page is full, an empty one ends the listing
+        assert_eq!(*requested.lock().unwrap(), vec![1, 4, 7]);
+    }
+
+    #[tokio::test]
+    async fn stops_after_a_partial_page() {
+        let requested = Arc::new(Mutex::new(Vec::new()));
+        let options = ListOptions {
+            page_size: 3,
+            ..Default::default()
+        };
+
+        let listed = list_pages(options, fetch_from(twins(5), 3, requested.clone()))
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+
+        assert_eq!(ids(&listed), ids(&twins(5)));
+        assert_eq!(*requested.lock().unwrap(), vec![0, 3]);
+    }
+
+    #[tokio::test]
+    async fn requests_a_page_once_the_previous_one_is_consumed() {
+        let requested = Arc::new(Mutex::new(Vec::new()));
+        let options = ListOptions {
+            page_size: 2,
+            ..Default::default()
+        };
 
-        twins.extend(payload.twins);
-        current_page += 1;
+        let mut listed = Box::pin(list_pages(
+            options,
+            fetch_from(twins(5), 2, requested.clone()),
+        ));
+
+        listed.next().await.unwrap().unwrap();
+        listed.next().await.unwrap().unwrap();
+        assert_eq!(*requested.lock().unwrap(), vec![0]);
+
+        listed.next().await.unwrap().unwrap();
+        assert_eq!(*requested.lock().unwrap(), vec![0, 2]);
+    }
+
+    #[tokio::test]
+    async fn prefetches_the_next_page() {
+        let requested = Arc::new(Mutex::new(Vec::new()));
+        let options = ListOptions {
+            page_size: 2,
+            prefetch: true,
+            ..Default::default()
+        };
+
+        let mut listed = Box::pin(list_pages(
+            options,
+            fetch_from(twins(5), 2, requested.clone()),
+        ));
+
+        listed.next().await.unwrap().unwrap();
+        assert_eq!(*requested.lock().unwrap(), vec![0, 2]);
+
+        let rest = listed.try_collect::<Vec<_>>().await.unwrap();
+        assert_eq!(rest.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn returns_the_error_of_a_page() {
+        let options = ListOptions {
+            page_size: 2,
+            ..Default::default()
+        };
+
+        let listed = list_pages(options, |offset| {
+            if offset == 0 {
+                Ok(async { Ok(twins(2)) }.boxed())
+            } else {
+                Ok(async { Err(anyhow::anyhow!("unavailable")) }.boxed())
+            }
+        })
+        .collect::<Vec<_>>()
+        .await;
+
+        assert_eq!(listed.len(), 3);
+        assert!(listed[0].is_ok() && listed[1].is_ok());
+        assert!(listed[2].is_err());
     }
 
-    Ok(twins)
+    /// Sets the flag when the request holding it is dropped.
+    struct DropFlag(Arc<AtomicBool>);
+
+    impl Drop for DropFlag {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn aborts_the_prefetch_when_dropped() {
+        let dropped = Arc::new(AtomicBool::new(false));
+        let options = ListOptions {
+            page_size: 2,
+            prefetch: true,
+            ..Default::default()
+        };
+
+        let request_dropped = dropped.clone();
+        let mut listed = Box::pin(list_pages(options, move |offset| {
+            if offset == 0 {
+                return Ok(async { Ok(twins(2)) }.boxed());
+            }
+
+            // the next page never arrives
+            let flag = DropFlag(request_dropped.clone());
+            Ok(async move {
+                let _flag = flag;
+                futures::future::pending::<()>().await;
+                Ok(Vec::new())
+            }
+            .boxed())
+        }));
+
+        listed.next().await.unwrap().unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!dropped.load(Ordering::SeqCst));
+
+        drop(listed);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        assert!(dropped.load(Ordering::SeqCst));
+    }
 }
@@ -1,6 +1,10 @@
+pub mod allow_list;
+pub mod bulk;
 pub mod crud;
 pub mod describe;
+pub mod feed_publisher;
 pub mod list;
+pub mod outbox;
 pub mod share;
 pub mod upsert;
 
@@ -0,0 +1,791 @@
+use anyhow::Context;
+use prost::Message;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::Mutex;
+use tonic::transport::Channel;
+
+use crate::client::iotics::api::feed_api_client::FeedApiClient;
+use crate::client::iotics::api::share_feed_data_request::{
+    Arguments as ShareFeedDataRequestArguments, Payload as ShareFeedDataRequestPayload,
+};
+use crate::client::iotics::api::{FeedData, FeedId, ShareFeedDataRequest};
+
+use crate::auth_builder::IntoAuthBuilder;
+use crate::channel::{create_channel, ConfigureClient};
+use crate::helpers::{is_unreachable, Backoff};
+use crate::ids::FeedRef;
+use crate::metric_names;
+use crate::telemetry::set_gauge;
+use crate::twin::share::{share_feed_data_with_client, to_timestamp};
+
+const LOG_FILE_NAME: &str = "outbox.log";
+const TMP_FILE_NAME: &str = "outbox.log.tmp";
+
+#[derive(Debug, Clone)]
+pub struct OutboxConfig {
+    /// Directory of the log file, created if missing.
+    pub dir: PathBuf,
+    /// Maximum size of the log. Once it's reached, the oldest samples are dropped down to 3/4 of
+    /// it so that the log isn't rewritten on every new sample.
+    pub max_size_bytes: u64,
+    /// Samples that occurred longer ago are dropped instead of being replayed.
+    pub max_age: Duration,
+    /// Time waited before replaying the log again once the host was found unreachable, doubled
+    /// after each failed replay up to `max_retry_delay`.
+    pub min_retry_delay: Duration,
+    /// Longest time waited between two replays while the host is unreachable.
+    pub max_retry_delay: Duration,
+}
+
+impl OutboxConfig {
+    /// A 64 MiB outbox keeping samples for a day, retried after 1 second up to every minute.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            max_size_bytes: 64 * 1024 * 1024,
+            max_age: Duration::from_secs(24 * 60 * 60),
+            min_retry_delay: Duration::from_secs(1),
+            max_retry_delay: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Whether a sample passed to [`share_data_or_store`] was shared or stored in the outbox.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShareOutcome {
+    Shared,
+    Stored,
+}
+
+/// Persistent append-only log of the samples that couldn't be shared because the host was
+/// unreachable, replayed in order once it's reachable again.
+///
+/// The samples are stored as length-delimited `ShareFeedDataRequest`s with their original
+/// `occurred_at`. The file is only read when the log is replayed or compacted, and all the file
+/// I/O runs on the blocking thread pool.
+pub struct Outbox {
+    config: OutboxConfig,
+    // serializes the access to the log file
+    state: Mutex<LogState>,
+}
+
+struct LogState {
+    len: usize,
+    size_bytes: u64,
+    // number of times the log was compacted to make room for new samples
+    compactions: u64,
+    backoff: Backoff,
+    // the replays made by `share_data_or_store` are skipped until then
+    retry_at: Option<Instant>,
+}
+
+impl Outbox {
+    /// Open the log of `config.dir`, dropping the truncated entry an interrupted write may have left.
+    pub fn open(config: OutboxConfig) -> Result<Self, anyhow::Error> {
+        fs::create_dir_all(&config.dir).with_context(|| {
+            format!(
+                "failed to create the outbox directory [{}]",
+                config.dir.display()
+            )
+        })?;
+
+        let log_path = config.dir.join(LOG_FILE_NAME);
+        let (entries, size_bytes) = read_log(&log_path)?;
+
+        let file_size = fs::metadata(&log_path)
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+
+        // the following entries would be appended after the truncated one and never read back
+        if file_size != size_bytes {
+            rewrite_log(&config.dir, &entries)?;
+        }
+
+        record_size(&config.dir, size_bytes);
+
+        let state = LogState {
+            len: entries.len(),
+            size_bytes,
+            compactions: 0,
+            backoff: Backoff::new(config.min_retry_delay, config.max_retry_delay),
+            retry_at: None,
+        };
+
+        Ok(Self {
+            config,
+            state: Mutex::new(state),
+        })
+    }
+
+    /// Number of samples waiting to be replayed, including the expired ones.
+    pub async fn len(&self) -> usize {
+        self.state.lock().await.len
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.len().await == 0
+    }
+
+    /// Append a sample to the log.
+    pub async fn store(&self, feed: &FeedRef, sample: FeedData) -> Result<(), anyhow::Error> {
+        let entry = ShareFeedDataRequest {
+            headers: None,
+            args: Some(ShareFeedDataRequestArguments {
+                feed_id: Some(FeedId::from(feed)),
+            }),
+            payload: Some(ShareFeedDataRequestPayload {
+                sample: Some(sample),
+            }),
+        }
+        .encode_length_delimited_to_vec();
+
+        let entry_len = entry.len() as u64;
+
+        let mut state = self.state.lock().await;
+
+        if state.size_bytes + entry_len > self.config.max_size_bytes {
+            let dir = self.config.dir.clone();
+            let oldest = self.oldest();
+            let low_water_mark = low_water_mark(self.config.max_size_bytes);
+
+            let (len, size_bytes) = run_blocking(move || {
+                let (entries, _) = read_log(&dir.join(LOG_FILE_NAME))?;
+                let entries = compact(entries, oldest, low_water_mark, entry_len);
+                let size_bytes = rewrite_log(&dir, &entries)?;
+                Ok((entries.len(), size_bytes))
+            })
+            .await?;
+
+            state.len = len;
+            state.size_bytes = size_bytes;
+            state.compactions += 1;
+        }
+
+        let log_path = self.log_path();
+        run_blocking(move || append_to_log(&log_path, &entry)).await?;
+
+        state.len += 1;
+        state.size_bytes += entry_len;
+        record_size(&self.config.dir, state.size_bytes);
+
+        Ok(())
+    }
+
+    /// Share the stored samples in order, dropping the expired ones and the ones rejected by the host.
+    ///
+    /// It stops when the host is unreachable, keeping that sample and the following ones in the log,
+    /// and [`share_data_or_store`] doesn't replay the log again before the retry delay.
+    /// Returns the number of samples shared.
+    pub async fn replay(
+        &self,
+        auth_builder: Arc<impl IntoAuthBuilder>,
+        retry_unknown: bool,
+    ) -> Result<usize, anyhow::Error> {
+        let channel = create_channel(
+            auth_builder.clone(),
+            auth_builder.get_config().channel_options,
+        )
+        .await?;
+        self.replay_with_channel(auth_builder, channel, retry_unknown)
+            .await
+    }
+
+    pub async fn replay_with_channel(
+        &self,
+        auth_builder: Arc<impl IntoAuthBuilder>,
+        channel: Channel,
+        retry_unknown: bool,
+    ) -> Result<usize, anyhow::Error> {
+        let mut state = self.state.lock().await;
+
+        if state.len == 0 {
+            return Ok(0);
+        }
+
+        let log_path = self.log_path();
+        let (mut entries, _) = run_blocking(move || read_log(&log_path)).await?;
+
+        let config = auth_builder.get_config();
+        let mut client = FeedApiClient::new(channel).configure(&config.channel_options);
+        let token = auth_builder.get_token()?;
+        let oldest = self.oldest();
+
+        let mut shared = 0;
+        let mut unreachable = None;
+
+        for (index, entry) in entries.iter().enumerate() {
+            let (feed, sample) = match decode_entry(entry) {
+                Ok(Some((feed, sample))) if !is_expired(&sample, oldest) => (feed, sample),
+                _ => continue,
+            };
+
+            let result = share_feed_data_with_client(
+                &mut client,
+                &token,
+                config.call_context.clone(),
+                &feed,
+                sample,
+                retry_unknown,
+            )
+            .await;
+
+            match result {
+                Ok(()) => shared += 1,
+                Err(e) if is_unreachable(&e) => {
+                    unreachable = Some((index, e));
+                    break;
+                }
+                // retrying a rejected sample would fail again and block the following ones
+                Err(_) => {}
+            }
+        }
+
+        match unreachable {
+            Some((index, e)) => {
+                // nothing to remove when the first entry couldn't be sent
+                if index > 0 {
+                    self.rewrite(&mut state, entries.split_off(index)).await?;
+                }
+
+                let delay = state.backoff.next_delay();
+                state.retry_at = Some(Instant::now() + delay);
+
+                Err(e.context(format!(
+                    "Replaying the outbox failed after [{}] samples",
+                    shared
+                )))
+            }
+            None => {
+                self.rewrite(&mut state, Vec::new()).await?;
+                state.backoff.reset();
+                state.retry_at = None;
+
+                Ok(shared)
+            }
+        }
+    }
+
+    /// Whether [`share_data_or_store`] should try to reach the host, `false` while it waits
+    /// for the retry delay of the last failed replay.
+    async fn is_retry_due(&self) -> bool {
+        match self.state.lock().await.retry_at {
+            Some(retry_at) => Instant::now() >= retry_at,
+            None => true,
+        }
+    }
+
+    /// Wait for the retry delay before replaying, after the host was found unreachable.
+    async fn back_off(&self) {
+        let mut state = self.state.lock().await;
+        let delay = state.backoff.next_delay();
+        state.retry_at = Some(Instant::now() + delay);
+    }
+
+    async fn rewrite(
+        &self,
+        state: &mut LogState,
+        entries: Vec<ShareFeedDataRequest>,
+    ) -> Result<(), anyhow::Error> {
+        let dir = self.config.dir.clone();
+        let len = entries.len();
+
+        state.size_bytes = run_blocking(move || rewrite_log(&dir, &entries)).await?;
+        state.len = len;
+        record_size(&self.config.dir, state.size_bytes);
+
+        Ok(())
+    }
+
+    fn oldest(&self) -> SystemTime {
+        SystemTime::now()
+            .checked_sub(self.config.max_age)
+            .unwrap_or(SystemTime::UNIX_EPOCH)
+    }
+
+    fn log_path(&self) -> PathBuf {
+        self.config.dir.join(LOG_FILE_NAME)
+    }
+}
+
+async fn run_blocking<T, F>(f: F) -> Result<T, anyhow::Error>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, anyhow::Error> + Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .context("the outbox I/O task failed")?
+}
+
+/// Read the entries of the log and the size of the complete ones, a truncated entry left by an
+/// interrupted write is ignored.
+fn read_log(path: &Path) -> Result<(Vec<ShareFeedDataRequest>, u64), anyhow::Error> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((Vec::new(), 0)),
+        Err(e) => return Err(anyhow::Error::new(e).context("failed to read the outbox log")),
+    };
+
+    Ok(decode_log(&bytes))
+}
+
+fn decode_log(bytes: &[u8]) -> (Vec<ShareFeedDataRequest>, u64) {
+    let mut buffer = bytes;
+    let mut entries = Vec::new();
+    let mut size_bytes = 0;
+
+    while !buffer.is_empty() {
+        match ShareFeedDataRequest::decode_length_delimited(&mut buffer) {
+            Ok(entry) => {
+                size_bytes = (bytes.len() - buffer.len()) as u64;
+                entries.push(entry);
+            }
+            Err(_) => break,
+        }
+    }
+
+    (entries, size_bytes)
+}
+
+fn append_to_log(path: &Path, entry: &[u8]) -> Result<(), anyhow::Error> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .context("failed to open the outbox log")?;
+
+    file.write_all(entry)
+        .context("failed to write to the outbox log")?;
+    file.sync_data().context("failed to sync the outbox log")?;
+
+    Ok(())
+}
+
+/// Replace the log with `entries`, through a temporary file so that it's never left half written.
+/// Returns the size of the new log.
+fn rewrite_log(dir: &Path, entries: &[ShareFeedDataRequest]) -> Result<u64, anyhow::Error> {
+    let tmp_path = dir.join(TMP_FILE_NAME);
+
+    let mut file = File::create(&tmp_path).context("failed to create the outbox log")?;
+    let mut size_bytes = 0;
+
+    for entry in entries {
+        let bytes = entry.encode_length_delimited_to_vec();
+        file.write_all(&bytes)
+            .context("failed to write to the outbox log")?;
+        size_bytes += bytes.len() as u64;
+    }
+
+    file.sync_data().context("failed to sync the outbox log")?;
+    fs::rename(&tmp_path, dir.join(LOG_FILE_NAME)).context("failed to replace the outbox log")?;
+
+    Ok(size_bytes)
+}
+
+/// Drop the expired entries, then the oldest ones until `extra_bytes` more fit in `max_size_bytes`.
+fn compact(
+    entries: Vec<ShareFeedDataRequest>,
+    oldest: SystemTime,
+    max_size_bytes: u64,
+    extra_bytes: u64,
+) -> Vec<ShareFeedDataRequest> {
+    let mut entries = entries
+        .into_iter()
+        .filter(|entry| match decode_entry(entry) {
+            Ok(Some((_, sample))) => !is_expired(&sample, oldest),
+            _ => false,
+        })
+        .collect::<Vec<_>>();
+
+    let mut size = entries.iter().map(entry_len).sum::<u64>();
+
+    let mut dropped = 0;
+
+    while size + extra_bytes > max_size_bytes && dropped < entries.len() {
+        size -= entry_len(&entries[dropped]);
+        dropped += 1;
+    }
+
+    entries.drain(..dropped);
+    entries
+}
+
+/// Size the log is compacted down to once full, leaving room for many samples before the next
+/// compaction.
+fn low_water_mark(max_size_bytes: u64) -> u64 {
+    max_size_bytes - max_size_bytes / 4
+}
+
+fn record_size(dir: &Path, size_bytes: u64) {
+    set_gauge(
+        metric_names::OUTBOX_SIZE_BYTES,
+        ("dir", &dir.display().to_string()),
+        size_bytes as f64,
+    );
+}
+
+fn entry_len(entry: &ShareFeedDataRequest) -> u64 {
+    let len = entry.encoded_len();
+    (prost::length_delimiter_len(len) + len) as u64
+}
+
+fn decode_entry(
+    entry: &ShareFeedDataRequest,
+) -> Result<Option<(FeedRef, FeedData)>, anyhow::Error> {
+    let feed_id = entry.args.as_ref().and_then(|args| args.feed_id.as_ref());
+    let sample = entry
+        .payload
+        .as_ref()
+        .and_then(|payload| payload.sample.clone());
+
+    match (feed_id, sample) {
+        (Some(feed_id), Some(sample)) => Ok(Some((FeedRef::try_from(feed_id)?, sample))),
+        _ => Ok(None),
+    }
+}
+
+fn is_expired(sample: &FeedData, oldest: SystemTime) -> bool {
+    match &sample.occurred_at {
+        Some(occurred_at) => {
+            let occurred_at =
+                SystemTime::UNIX_EPOCH + Duration::from_secs(occurred_at.seconds.max(0) as u64);
+            occurred_at < oldest
+        }
+        None => false,
+    }
+}
+
+/// Share a sample, or store it in the outbox when the host is unreachable.
+///
+/// The samples already in the outbox are replayed first so that the samples of a feed stay in order.
+/// Only the connection failures and `Unavailable` count as unreachable, the other errors, including
+/// an invalid configuration or a timed out call the host may have handled, are returned without
+/// storing the sample.
+pub async fn share_data_or_store<T: Into<Vec<u8>>>(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    outbox: &Outbox,
+    feed: &FeedRef,
+    data: T,
+    retry_unknown: bool,
+) -> Result<ShareOutcome, anyhow::Error> {
+    let sample = FeedData {
+        occurred_at: Some(to_timestamp(SystemTime::now())?),
+        mime: "application/json".to_string(),
+        data: data.into(),
+    };
+
+    // the host was unreachable moments ago, keep the samples in order without connecting again
+    if !outbox.is_empty().await && !outbox.is_retry_due().await {
+        outbox.store(feed, sample).await?;
+        return Ok(ShareOutcome::Stored);
+    }
+
+    let channel = match create_channel(
+        auth_builder.clone(),
+        auth_builder.get_config().channel_options,
+    )
+    .await
+    {
+        Ok(channel) => channel,
+        Err(e) if is_unreachable(&e) => {
+            outbox.store(feed, sample).await?;
+            outbox.back_off().await;
+            return Ok(ShareOutcome::Stored);
+        }
+        Err(e) => return Err(e),
+    };
+
+    share_sample_or_store_with_channel(auth_builder, channel, outbox, feed, sample, retry_unknown)
+        .await
+}
+
+pub async fn share_data_or_store_with_channel<T: Into<Vec<u8>>>(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    channel: Channel,
+    outbox: &Outbox,
+    feed: &FeedRef,
+    data: T,
+    retry_unknown: bool,
+) -> Result<ShareOutcome, anyhow::Error> {
+    let sample = FeedData {
+        occurred_at: Some(to_timestamp(SystemTime::now())?),
+        mime: "application/json".to_string(),
+        data: data.into(),
+    };
+
+    share_sample_or_store_with_channel(auth_builder, channel, outbox, feed, sample, retry_unknown)
+        .await
+}
+
+async fn share_sample_or_store_with_channel(
+    auth_builder: Arc<impl IntoAuthBuilder>,
+    channel: Channel,
+    outbox: &Outbox,
+    feed: &FeedRef,
+    sample: FeedData,
+    retry_unknown: bool,
+) -> Result<ShareOutcome, anyhow::Error> {
+    if !outbox.is_empty().await {
+        // the host was unreachable moments ago, keep the samples in order without trying again
+        if !outbox.is_retry_due().await {
+            outbox.store(feed, sample).await?;
+            return Ok(ShareOutcome::Stored);
+        }
+
+        if let Err(e) = outbox
+            .replay_with_channel(auth_builder.clone(), channel.clone(), retry_unknown)
+            .await
+        {
+            if is_unreachable(&e) {
+                outbox.store(feed, sample).await?;
+                return Ok(ShareOutcome::Stored);
+            }
+            return Err(e);
+        }
+    }
+
+    let config = auth_builder.get_config();
+    let mut client = FeedApiClient::new(channel).configure(&config.channel_options);
+    let token = auth_builder.get_token()?;
+
+    match share_feed_data_with_client(
+        &mut client,
+        &token,
+        config.call_context,
+        feed,
+        sample.clone(),
+        retry_unknown,
+    )
+    .await
+    {
+        Ok(()) => Ok(ShareOutcome::Shared),
+        Err(e) if is_unreachable(&e) => {
+            outbox.store(feed, sample).await?;
+            outbox.back_off().await;
+            Ok(ShareOutcome::Stored)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::generate_client_app_id;
+
+    const TWIN: &str = "did:iotics:iotHjrmKpPGWyEC4FFo4d6oyzVVk6MXLmEgY";
+
+    fn feed() -> FeedRef {
+        FeedRef::new(TWIN.parse().unwrap(), "temperature")
+    }
+
+    fn sample(occurred_at: SystemTime, data: &[u8]) -> FeedData {
+        FeedData {
+            occurred_at: Some(to_timestamp(occurred_at).unwrap()),
+            mime: "application/json".to_string(),
+            data: data.to_vec(),
+        }
+    }
+
+    fn entry(occurred_at: SystemTime, data: &[u8]) -> ShareFeedDataRequest {
+        ShareFeedDataRequest {
+            headers: None,
+            args: Some(ShareFeedDataRequestArguments {
+                feed_id: Some(FeedId::from(&feed())),
+            }),
+            payload: Some(ShareFeedDataRequestPayload {
+                sample: Some(sample(occurred_at, data)),
+            }),
+        }
+    }
+
+    fn encode(entries: &[ShareFeedDataRequest]) -> Vec<u8> {
+        entries
+            .iter()
+            .flat_map(|entry| entry.encode_length_delimited_to_vec())
+            .collect()
+    }
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("iotics-outbox-{}", generate_client_app_id()))
+    }
+
+    #[test]
+    fn decodes_the_entries_of_the_log() {
+        let now = SystemTime::now();
+        let entries = vec![entry(now, b"1"), entry(now, b"2")];
+        let bytes = encode(&entries);
+
+        assert_eq!(decode_log(&bytes), (entries, bytes.len() as u64));
+    }
+
+    #[test]
+    fn ignores_a_truncated_entry() {
+        let now = SystemTime::now();
+        let complete = encode(&[entry(now, b"1")]);
+
+        let mut bytes = complete.clone();
+        let truncated = encode(&[entry(now, b"2")]);
+        bytes.extend_from_slice(&truncated[..truncated.len() - 3]);
+
+        let (entries, size_bytes) = decode_log(&bytes);
+
+        assert_eq!(entries, vec![entry(now, b"1")]);
+        assert_eq!(size_bytes, complete.len() as u64);
+    }
+
+    #[test]
+    fn expires_the_samples_older_than_the_limit() {
+        let now = SystemTime::now();
+        let oldest = now - Duration::from_secs(60);
+
+        assert!(is_expired(
+            &sample(now - Duration::from_secs(120), b"1"),
+            oldest
+        ));
+        assert!(!is_expired(&sample(now, b"1"), oldest));
+
+        let without_time = FeedData {
+            occurred_at: None,
+            ..sample(now, b"1")
+        };
+        assert!(!is_expired(&without_time, oldest));
+    }
+
+    #[test]
+    fn compacts_the_expired_entries_first() {
+        let now = SystemTime::now();
+        let oldest = now - Duration::from_secs(60);
+
+        let entries = vec![
+            entry(now - Duration::from_secs(120), b"expired"),
+            entry(now, b"1"),
+            entry(now, b"2"),
+        ];
+
+        let compacted = compact(entries, oldest, u64::MAX, 0);
+
+        assert_eq!(compacted, vec![entry(now, b"1"), entry(now, b"2")]);
+    }
+
+    #[test]
+    fn compacts_the_oldest_entries_to_make_room() {
+        let now = SystemTime::now();
+        let entries = vec![entry(now, b"1"), entry(now, b"2"), entry(now, b"3")];
+        let entry_size = entry_len(&entries[0]);
+
+        // room for the new entry and two of the stored ones
+        let compacted = compact(entries, SystemTime::UNIX_EPOCH, 3 * entry_size, entry_size);
+
+        assert_eq!(compacted, vec![entry(now, b"2"), entry(now, b"3")]);
+    }
+
+    #[test]
+    fn drops_the_entries_without_feed() {
+        let now = SystemTime::now();
+        let without_feed = ShareFeedDataRequest {
+            args: None,
+            ..entry(now, b"1")
+        };
+
+        let compacted = compact(
+            vec![without_feed, entry(now, b"2")],
+            SystemTime::UNIX_EPOCH,
+            u64::MAX,
+            0,
+        );
+
+        assert_eq!(compacted, vec![entry(now, b"2")]);
+    }
+
+    #[tokio::test]
+    async fn stores_and_reopens_the_log() {
+        let dir = temp_dir();
+        let now = SystemTime::now();
+
+        let outbox = Outbox::open(OutboxConfig::new(&dir)).unwrap();
+        assert!(outbox.is_empty().await);
+
+        outbox.store(&feed(), sample(now, b"1")).await.unwrap();
+        outbox.store(&feed(), sample(now, b"2")).await.unwrap();
+        assert_eq!(outbox.len().await, 2);
+
+        drop(outbox);
+
+        // an interrupted write leaves a truncated entry at the end of the log
+        let truncated = encode(&[entry(now, b"3")]);
+        append_to_log(&dir.join(LOG_FILE_NAME), &truncated[..truncated.len() - 3]).unwrap();
+
+        let outbox = Outbox::open(OutboxConfig::new(&dir)).unwrap();
+        assert_eq!(outbox.len().await, 2);
+
+        outbox.store(&feed(), sample(now, b"4")).await.unwrap();
+
+        let (entries, _) = read_log(&dir.join(LOG_FILE_NAME)).unwrap();
+        assert_eq!(
+            entries,
+            vec![entry(now, b"1"), entry(now, b"2"), entry(now, b"4")]
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn stays_under_the_size_limit() {
+        let dir = temp_dir();
+        let now = SystemTime::now();
+        let entry_size = entry_len(&entry(now, b"1"));
+
+        let config = OutboxConfig {
+            max_size_bytes: 4 * entry_size,
+            ..OutboxConfig::new(&dir)
+        };
+
+        let outbox = Outbox::open(config).unwrap();
+
+        for data in [b"1", b"2", b"3", b"4", b"5"] {
+            outbox.store(&feed(), sample(now, data)).await.unwrap();
+        }
+
+        // compacted down to 3 entries, including the new one
+        assert_eq!(outbox.len().await, 3);
+
+        let (entries, size_bytes) = read_log(&dir.join(LOG_FILE_NAME)).unwrap();
+        assert_eq!(
+            entries,
+            vec![entry(now, b"3"), entry(now, b"4"), entry(now, b"5")]
+        );
+        assert_eq!(size_bytes, 3 * entry_size);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn compacts_the_full_log_once_per_many_samples() {
+        let dir = temp_dir();
+        let now = SystemTime::now();
+        let entry_size = entry_len(&entry(now, b"1"));
+
+        let config = OutboxConfig {
+            max_size_bytes: 20 * entry_size,
+            ..OutboxConfig::new(&dir)
+        };
+
+        let outbox = Outbox::open(config).unwrap();
+
+        for _ in 0..100 {
+            outbox.store(&feed(), sample(now, b"1")).await.unwrap();
+        }
+
+        // the 21st sample compacts the log down to 14 entries, leaving room for 6 samples until the
+        // next compaction
+        assert_eq!(outbox.state.lock().await.compactions, 14);
+        assert!(outbox.len().await <= 20);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
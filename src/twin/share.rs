@@ -9,80 +9,112 @@ use crate::client::iotics::api::feed_api_client::FeedApiClient;
 use crate::client::iotics::api::share_feed_data_request::{
     Arguments as ShareFeedDataRequestArguments, Payload as ShareFeedDataRequestPayload,
 };
-use crate::client::iotics::api::{FeedData, FeedId, Headers, ShareFeedDataRequest};
+use crate::client::iotics::api::{FeedData, FeedId, ShareFeedDataRequest};
 
 use crate::auth_builder::IntoAuthBuilder;
-use crate::channel::create_channel;
-use crate::helpers::generate_client_app_id;
+use crate::call_context::CallContext;
+use crate::channel::{create_channel, ConfigureClient};
+use crate::ids::FeedRef;
+use crate::metric_names;
+use crate::telemetry::{increment, Rpc};
 
 pub async fn share_data<T: Into<Vec<u8>>>(
     auth_builder: Arc<impl IntoAuthBuilder>,
-    twin_id: &str,
-    feed_id: &str,
+    feed: &FeedRef,
     data: T,
     retry_unknown: bool,
 ) -> Result<(), anyhow::Error> {
-    let channel = create_channel(auth_builder.clone(), None, None, None).await?;
-    share_data_with_channel(auth_builder, channel, twin_id, feed_id, data, retry_unknown).await
+    let channel = create_channel(
+        auth_builder.clone(),
+        auth_builder.get_config().channel_options,
+    )
+    .await?;
+    share_data_with_channel(auth_builder, channel, feed, data, retry_unknown).await
 }
 
 pub async fn share_data_with_channel<T: Into<Vec<u8>>>(
     auth_builder: Arc<impl IntoAuthBuilder>,
     channel: Channel,
-    twin_id: &str,
-    feed_id: &str,
+    feed: &FeedRef,
     data: T,
     retry_unknown: bool,
 ) -> Result<(), anyhow::Error> {
-    let mut client = FeedApiClient::new(channel);
-    let client_app_id = generate_client_app_id();
-    let transaction_ref = vec![client_app_id.clone()];
-
-    let headers = Headers {
-        client_app_id,
-        transaction_ref: transaction_ref.clone(),
-        ..Default::default()
-    };
+    let config = auth_builder.get_config();
+    let mut client = FeedApiClient::new(channel).configure(&config.channel_options);
 
-    let args = ShareFeedDataRequestArguments {
-        feed_id: Some(FeedId {
-            id: feed_id.to_string(),
-            twin_id: twin_id.to_string(),
-            ..Default::default()
-        }),
+    let sample = FeedData {
+        occurred_at: Some(to_timestamp(SystemTime::now())?),
+        mime: "application/json".to_string(),
+        data: data.into(),
     };
 
-    let dtm = std::time::SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?;
-    let timestamp = Timestamp {
+    let token = auth_builder.get_token()?;
+
+    share_feed_data_with_client(
+        &mut client,
+        &token,
+        config.call_context,
+        feed,
+        sample,
+        retry_unknown,
+    )
+    .await
+}
+
+pub(crate) fn to_timestamp(time: SystemTime) -> Result<Timestamp, anyhow::Error> {
+    let dtm = time.duration_since(SystemTime::UNIX_EPOCH)?;
+
+    Ok(Timestamp {
         seconds: dtm.as_secs() as i64,
         nanos: 0,
+    })
+}
+
+/// Share a sample with an existing client, so that it can be reused across calls.
+pub(crate) async fn share_feed_data_with_client(
+    client: &mut FeedApiClient<Channel>,
+    token: &str,
+    call_context: CallContext,
+    feed: &FeedRef,
+    sample: FeedData,
+    retry_unknown: bool,
+) -> Result<(), anyhow::Error> {
+    let headers = CallContext::headers(call_context);
+    let transaction_ref = headers.transaction_ref.clone();
+
+    let args = ShareFeedDataRequestArguments {
+        feed_id: Some(FeedId::from(feed)),
     };
 
     let payload = ShareFeedDataRequestPayload {
-        sample: Some(FeedData {
-            occurred_at: Some(timestamp),
-            mime: "application/json".to_string(),
-            data: data.into(),
-        }),
+        sample: Some(sample),
     };
 
+    let rpc = Rpc::new("FeedAPI", "ShareFeedData", &headers)
+        .twin(feed.twin.as_str())
+        .feed(&feed.feed);
+
     let mut request = tonic::Request::new(ShareFeedDataRequest {
         headers: Some(headers.clone()),
         args: Some(args.clone()),
         payload: Some(payload.clone()),
     });
 
-    let token = auth_builder.get_token()?;
-
     request.metadata_mut().append(
         "authorization",
         token.parse().context("parse token failed")?,
     );
 
-    let result = client.share_feed_data(request).await;
+    rpc.inject(&mut request);
+
+    let result = rpc.run(client.share_feed_data(request)).await;
 
     if let Err(e) = result {
         if retry_unknown && e.code() == Code::Unknown {
+            let rpc = Rpc::new("FeedAPI", "ShareFeedData", &headers)
+                .twin(feed.twin.as_str())
+                .feed(&feed.feed);
+
             let mut request = tonic::Request::new(ShareFeedDataRequest {
                 headers: Some(headers),
                 args: Some(args),
@@ -94,12 +126,16 @@ pub async fn share_data_with_channel<T: Into<Vec<u8>>>(
                 token.parse().context("parse token failed")?,
             );
 
-            client.share_feed_data(request).await.with_context(|| {
-                format!(
-                    "Sharing data failed, transaction ref [{}]",
-                    transaction_ref.join(", ")
-                )
-            })?;
+            rpc.inject(&mut request);
+
+            rpc.run(client.share_feed_data(request))
+                .await
+                .with_context(|| {
+                    format!(
+                        "Sharing data failed, transaction ref [{}]",
+                        transaction_ref.join(", ")
+                    )
+                })?;
         } else {
             return Err(anyhow::Error::new(e).context(format!(
                 "Sharing data failed, transaction ref [{}]",
@@ -108,5 +144,7 @@ pub async fn share_data_with_channel<T: Into<Vec<u8>>>(
         }
     }
 
+    increment(metric_names::FEED_SAMPLES_SHARED_TOTAL, 1);
+
     Ok(())
 }
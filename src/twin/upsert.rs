@@ -5,23 +5,29 @@ use tonic::{Request, Response};
 
 use crate::client::iotics::api::twin_api_client::TwinApiClient;
 use crate::client::iotics::api::upsert_twin_request::Payload as UpsertTwinRequestPayload;
-use crate::client::iotics::api::{GeoLocation, Headers, Property, TwinId, UpsertTwinRequest};
+use crate::client::iotics::api::{GeoLocation, Property, TwinId, UpsertTwinRequest};
 
 use crate::auth_builder::IntoAuthBuilder;
-use crate::channel::create_channel;
-use crate::helpers::generate_client_app_id;
+use crate::call_context::CallContext;
+use crate::channel::{create_channel, ConfigureClient};
+use crate::ids::TwinDid;
+use crate::telemetry::Rpc;
 use crate::twin::{UpsertFeedWithMeta, UpsertInputWithMeta, UpsertTwinResponse};
 
 #[allow(clippy::too_many_arguments)]
 pub async fn upsert_twin(
     auth_builder: Arc<impl IntoAuthBuilder>,
-    twin_id: &str,
+    twin_id: &TwinDid,
     properties: Vec<Property>,
     feeds: Vec<UpsertFeedWithMeta>,
     inputs: Vec<UpsertInputWithMeta>,
     location: Option<GeoLocation>,
 ) -> Result<Response<UpsertTwinResponse>, anyhow::Error> {
-    let channel = create_channel(auth_builder.clone(), None, None, None).await?;
+    let channel = create_channel(
+        auth_builder.clone(),
+        auth_builder.get_config().channel_options,
+    )
+    .await?;
     upsert_twin_with_channel(
         auth_builder,
         channel,
@@ -38,33 +44,32 @@ pub async fn upsert_twin(
 pub async fn upsert_twin_with_channel(
     auth_builder: Arc<impl IntoAuthBuilder>,
     channel: Channel,
-    twin_id: &str,
+    twin_id: &TwinDid,
     properties: Vec<Property>,
     feeds: Vec<UpsertFeedWithMeta>,
     inputs: Vec<UpsertInputWithMeta>,
     location: Option<GeoLocation>,
 ) -> Result<Response<UpsertTwinResponse>, anyhow::Error> {
-    let mut client = TwinApiClient::new(channel);
-    let client_app_id = generate_client_app_id();
-    let transaction_ref = vec![client_app_id.clone()];
+    let config = auth_builder.get_config();
 
-    let headers = Headers {
-        client_app_id,
-        transaction_ref: transaction_ref.clone(),
-        ..Default::default()
-    };
+    if let Some(validator) = &config.property_validator {
+        validator.validate_upsert(&properties, &feeds, &inputs)?;
+    }
+
+    let mut client = TwinApiClient::new(channel).configure(&config.channel_options);
+    let headers = CallContext::headers(config.call_context);
+    let transaction_ref = headers.transaction_ref.clone();
 
     let payload = UpsertTwinRequestPayload {
-        twin_id: Some(TwinId {
-            id: twin_id.to_string(),
-            ..Default::default()
-        }),
+        twin_id: Some(TwinId::from(twin_id)),
         properties,
         feeds,
         inputs,
         location,
     };
 
+    let rpc = Rpc::new("TwinAPI", "UpsertTwin", &headers).twin(twin_id.as_str());
+
     let mut request = Request::new(UpsertTwinRequest {
         headers: Some(headers),
         payload: Some(payload),
@@ -77,12 +82,17 @@ pub async fn upsert_twin_with_channel(
         token.parse().context("parse token failed")?,
     );
 
-    let response = client.upsert_twin(request).await.with_context(|| {
-        format!(
-            "Upserting twin failed, transaction ref [{}]",
-            transaction_ref.join(", ")
-        )
-    })?;
+    rpc.inject(&mut request);
+
+    let response = rpc
+        .run(client.upsert_twin(request))
+        .await
+        .with_context(|| {
+            format!(
+                "Upserting twin failed, transaction ref [{}]",
+                transaction_ref.join(", ")
+            )
+        })?;
 
     Ok(response)
 }
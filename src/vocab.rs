@@ -0,0 +1,87 @@
+//! This crate's own vocabulary for the feeds, inputs and values of the twins in the RDF and
+//! JSON-LD documents, see [`crate::json_ld`].
+//!
+//! The host doesn't expose the feeds and inputs as triples, these terms are only the mapping
+//! chosen by this crate. They aren't IOTICS platform keys and aren't meant to be set on twins.
+
+/// Namespace of the terms of this vocabulary.
+pub const NAMESPACE: &str = "https://github.com/Iotic-Labs/iotics-grpc-client-rs/vocab#";
+
+// predicates
+pub const HAS_FEED: &str = "https://github.com/Iotic-Labs/iotics-grpc-client-rs/vocab#hasFeed";
+pub const HAS_INPUT: &str = "https://github.com/Iotic-Labs/iotics-grpc-client-rs/vocab#hasInput";
+pub const HAS_VALUE: &str = "https://github.com/Iotic-Labs/iotics-grpc-client-rs/vocab#hasValue";
+pub const FEED_ID: &str = "https://github.com/Iotic-Labs/iotics-grpc-client-rs/vocab#feedId";
+pub const INPUT_ID: &str = "https://github.com/Iotic-Labs/iotics-grpc-client-rs/vocab#inputId";
+pub const STORE_LAST: &str = "https://github.com/Iotic-Labs/iotics-grpc-client-rs/vocab#storeLast";
+pub const VALUE_UNIT: &str = "https://github.com/Iotic-Labs/iotics-grpc-client-rs/vocab#valueUnit";
+pub const VALUE_DATA_TYPE: &str =
+    "https://github.com/Iotic-Labs/iotics-grpc-client-rs/vocab#valueDataType";
+
+// classes
+pub const FEED: &str = "https://github.com/Iotic-Labs/iotics-grpc-client-rs/vocab#Feed";
+pub const INPUT: &str = "https://github.com/Iotic-Labs/iotics-grpc-client-rs/vocab#Input";
+pub const VALUE: &str = "https://github.com/Iotic-Labs/iotics-grpc-client-rs/vocab#Value";
+
+/// IRI of a feed of the twin `twin_iri`, `{twin_iri}/feeds/{feed_id}`.
+pub fn feed_iri(twin_iri: &str, feed_id: &str) -> String {
+    format!("{}/feeds/{}", twin_iri, encode_path_segment(feed_id))
+}
+
+/// IRI of an input of the twin `twin_iri`, `{twin_iri}/inputs/{input_id}`.
+pub fn input_iri(twin_iri: &str, input_id: &str) -> String {
+    format!("{}/inputs/{}", twin_iri, encode_path_segment(input_id))
+}
+
+/// IRI of a value of the feed or input `parent_iri`, `{parent_iri}/values/{label}`.
+pub fn value_iri(parent_iri: &str, label: &str) -> String {
+    format!("{}/values/{}", parent_iri, encode_path_segment(label))
+}
+
+/// Percent-encode everything but the unreserved characters of RFC 3986, so that ids and labels
+/// with spaces, `#`, `/` or `>` make valid IRIs.
+fn encode_path_segment(segment: &str) -> String {
+    let mut encoded = String::with_capacity(segment.len());
+
+    for byte in segment.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TWIN: &str = "did:iotics:iotHjrmKpPGWyEC4FFo4d6oyzVVk6MXLmEgY";
+
+    #[test]
+    fn keeps_the_unreserved_characters() {
+        assert_eq!(
+            feed_iri(TWIN, "temperature_1.raw-v2~"),
+            format!("{}/feeds/temperature_1.raw-v2~", TWIN)
+        );
+    }
+
+    #[test]
+    fn percent_encodes_the_path_segments() {
+        assert_eq!(
+            input_iri(TWIN, "set point#1"),
+            format!("{}/inputs/set%20point%231", TWIN)
+        );
+        assert_eq!(
+            value_iri("http://example.com/feeds/a", "a/b<c>"),
+            "http://example.com/feeds/a/values/a%2Fb%3Cc%3E"
+        );
+        assert_eq!(
+            value_iri("http://example.com/feeds/a", "température"),
+            "http://example.com/feeds/a/values/temp%C3%A9rature"
+        );
+    }
+}
@@ -0,0 +1,131 @@
+#![cfg(feature = "derive")]
+
+use iotics_grpc_client::properties::common_keys::predicate::{COMMENT, LABEL};
+use iotics_grpc_client::properties::{PropertyBuilder, PropertyFinderError, TwinProperties};
+use iotics_grpc_client::TwinDid;
+
+const OWNER: &str = "https://example.com/owner";
+const HOMEPAGE: &str = "https://example.com/homepage";
+const FLOOR: &str = "https://example.com/floor";
+const ACTIVE: &str = "https://example.com/active";
+const SERIAL_NUMBER: &str = "https://example.com/serialNumber";
+
+const TWIN: &str = "did:iotics:iotHjrmKpPGWyEC4FFo4d6oyzVVk6MXLmEgY";
+
+#[derive(Debug, PartialEq, TwinProperties)]
+struct Sensor {
+    #[twin_property(predicate = LABEL, lang = "en")]
+    label: String,
+    #[twin_property(predicate = COMMENT, lang = "en")]
+    comment: Option<String>,
+    #[twin_property(predicate = OWNER, uri)]
+    owner: TwinDid,
+    #[twin_property(predicate = HOMEPAGE, uri)]
+    homepage: Option<String>,
+    #[twin_property(predicate = FLOOR, datatype = "integer")]
+    floor: u32,
+    #[twin_property(predicate = ACTIVE)]
+    active: bool,
+    #[twin_property(predicate = SERIAL_NUMBER)]
+    serial_number: String,
+    #[twin_property(skip)]
+    readings: Vec<f64>,
+}
+
+fn sensor() -> Sensor {
+    Sensor {
+        label: "Sensor".to_string(),
+        comment: None,
+        owner: TWIN.parse().unwrap(),
+        homepage: Some("https://example.com/sensor".to_string()),
+        floor: 3,
+        active: true,
+        serial_number: "SN-1".to_string(),
+        readings: Vec::new(),
+    }
+}
+
+#[test]
+fn converts_the_fields_to_properties() {
+    let properties = sensor().to_properties();
+
+    assert_eq!(
+        properties,
+        vec![
+            PropertyBuilder::build_lang_literal(LABEL, "en", "Sensor"),
+            PropertyBuilder::build_uri_value(OWNER, TWIN),
+            PropertyBuilder::build_uri_value(HOMEPAGE, "https://example.com/sensor"),
+            PropertyBuilder::build_literal_value(FLOOR, "integer", "3"),
+            PropertyBuilder::literal(ACTIVE, true),
+            PropertyBuilder::build_string_literal_value(SERIAL_NUMBER, "SN-1"),
+        ]
+    );
+}
+
+#[test]
+fn round_trips_the_fields() {
+    let sensor = Sensor {
+        comment: Some("On the roof".to_string()),
+        readings: vec![1.0],
+        ..sensor()
+    };
+
+    let read = Sensor::from_properties(&sensor.to_properties()).unwrap();
+
+    // the skipped fields are left to their default value
+    assert_eq!(
+        read,
+        Sensor {
+            readings: Vec::new(),
+            ..sensor
+        }
+    );
+}
+
+#[test]
+fn fails_on_a_missing_required_property() {
+    let properties = sensor()
+        .to_properties()
+        .into_iter()
+        .filter(|property| property.key != OWNER)
+        .collect::<Vec<_>>();
+
+    assert!(matches!(
+        Sensor::from_properties(&properties),
+        Err(PropertyFinderError::KeyNotFound)
+    ));
+}
+
+#[test]
+fn fails_on_an_invalid_uri() {
+    let properties = sensor()
+        .to_properties()
+        .into_iter()
+        .map(|property| match property.key.as_str() {
+            OWNER => PropertyBuilder::build_uri_value(OWNER, "https://example.com/not-a-did"),
+            _ => property,
+        })
+        .collect::<Vec<_>>();
+
+    assert!(matches!(
+        Sensor::from_properties(&properties),
+        Err(PropertyFinderError::ParseError { data_type, .. }) if data_type == "uri"
+    ));
+}
+
+#[test]
+fn fails_on_an_unparsable_literal() {
+    let properties = sensor()
+        .to_properties()
+        .into_iter()
+        .map(|property| match property.key.as_str() {
+            FLOOR => PropertyBuilder::build_literal_value(FLOOR, "integer", "ground"),
+            _ => property,
+        })
+        .collect::<Vec<_>>();
+
+    assert!(matches!(
+        Sensor::from_properties(&properties),
+        Err(PropertyFinderError::ParseError { .. })
+    ));
+}
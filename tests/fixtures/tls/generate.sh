@@ -0,0 +1,25 @@
+#!/usr/bin/env sh
+# Generates the private CA, host and client certificates used by the TLS tests of `src/channel.rs`.
+set -e
+cd "$(dirname "$0")"
+
+key() {
+    openssl genpkey -algorithm EC -pkeyopt ec_paramgen_curve:P-256 -out "$1"
+}
+
+key ca.key
+openssl req -x509 -new -key ca.key -days 36500 -subj "/CN=iotics test CA" \
+    -addext "basicConstraints=critical,CA:TRUE" -addext "keyUsage=critical,keyCertSign,cRLSign" \
+    -out ca.pem
+
+key host.key
+openssl req -new -key host.key -subj "/CN=localhost" -out host.csr
+printf "basicConstraints=CA:FALSE\nsubjectAltName=DNS:localhost,IP:127.0.0.1\nextendedKeyUsage=serverAuth\n" > host.ext
+openssl x509 -req -in host.csr -CA ca.pem -CAkey ca.key -CAcreateserial -days 36500 -extfile host.ext -out host.pem
+
+key client.key
+openssl req -new -key client.key -subj "/CN=iotics test client" -out client.csr
+printf "basicConstraints=CA:FALSE\nextendedKeyUsage=clientAuth\n" > client.ext
+openssl x509 -req -in client.csr -CA ca.pem -CAkey ca.key -CAcreateserial -days 36500 -extfile client.ext -out client.pem
+
+rm -f ca.key ca.srl host.csr host.ext client.csr client.ext